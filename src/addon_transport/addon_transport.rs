@@ -1,7 +1,7 @@
 use crate::runtime::TryEnvFuture;
-use crate::types::addon::{Manifest, ResourcePath, ResourceResponse};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponseWithMetadata};
 
 pub trait AddonTransport {
-    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponse>;
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata>;
     fn manifest(&self) -> TryEnvFuture<Manifest>;
 }
@@ -2,7 +2,7 @@ use crate::addon_transport::http_transport::legacy::AddonLegacyTransport;
 use crate::addon_transport::AddonTransport;
 use crate::constants::{ADDON_LEGACY_PATH, ADDON_MANIFEST_PATH};
 use crate::runtime::{Env, EnvError, EnvFutureExt, TryEnvFuture};
-use crate::types::addon::{Manifest, ResourcePath, ResourceResponse};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponseWithMetadata};
 use futures::future;
 use http::Request;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
@@ -24,7 +24,7 @@ impl<E: Env> AddonHTTPTransport<E> {
 }
 
 impl<E: Env> AddonTransport for AddonHTTPTransport<E> {
-    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponse> {
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
         if self.transport_url.path().ends_with(ADDON_LEGACY_PATH) {
             return AddonLegacyTransport::<E>::new(&self.transport_url).resource(path);
         }
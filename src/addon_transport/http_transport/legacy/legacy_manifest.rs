@@ -1,4 +1,4 @@
-use crate::types::addon::{Manifest, ManifestCatalog, ManifestResource};
+use crate::types::addon::{Manifest, ManifestCatalog, ManifestExtra, ManifestResource};
 use semver::Version;
 use serde::Deserialize;
 
@@ -53,19 +53,26 @@ impl From<LegacyManifest> for Manifest {
     fn from(m: LegacyManifest) -> Self {
         // Catalogs: if there are sorts, add a catalog for each type for each sort
         // if there are no sorts, do that just for the types
+        // Catalogs support paging and genres, the search is a separate catalog for each type
         let types = m.types.to_owned();
         let is_find = m.methods.iter().any(|x| x == "meta.find");
-        let catalogs: Vec<ManifestCatalog> = if is_find {
+        let is_search = m.methods.iter().any(|x| x == "meta.search");
+        let find_extra = ManifestExtra::Short {
+            required: vec![],
+            supported: vec!["genre".to_owned(), "skip".to_owned()],
+        };
+        let mut catalogs: Vec<ManifestCatalog> = if is_find {
             match m.sorts {
                 Some(sorts) => sorts
                     .iter()
                     .flat_map(|sort| {
                         let types = sort.types.as_ref().unwrap_or(&types);
+                        let find_extra = &find_extra;
                         types.iter().cloned().map(move |t| ManifestCatalog {
                             r#type: t,
                             id: sort.id.to_owned(),
                             name: sort.name.to_owned(),
-                            extra: Default::default(),
+                            extra: find_extra.to_owned(),
                         })
                     })
                     .collect(),
@@ -75,13 +82,24 @@ impl From<LegacyManifest> for Manifest {
                         r#type: t.to_owned(),
                         id: "top".to_owned(),
                         name: None,
-                        extra: Default::default(),
+                        extra: find_extra.to_owned(),
                     })
                     .collect(),
             }
         } else {
             vec![]
         };
+        if is_search {
+            catalogs.extend(types.iter().map(|t| ManifestCatalog {
+                r#type: t.to_owned(),
+                id: "search".to_owned(),
+                name: None,
+                extra: ManifestExtra::Short {
+                    required: vec!["search".to_owned()],
+                    supported: vec!["search".to_owned(), "skip".to_owned()],
+                },
+            }));
+        }
 
         // id_prefixes: the previous id_property is pretty much equivalent,
         // with the following differences:
@@ -99,7 +117,7 @@ impl From<LegacyManifest> for Manifest {
                 .collect()
         });
 
-        // resources: only those two are supported by the legacy mapper
+        // resources: only those are supported by the legacy mapper
         let mut resources: Vec<ManifestResource> = vec![];
         if m.methods.iter().any(|x| x == "meta.get") {
             resources.push(ManifestResource::Short("meta".into()))
@@ -107,7 +125,10 @@ impl From<LegacyManifest> for Manifest {
         if m.methods.iter().any(|x| x == "stream.find") {
             resources.push(ManifestResource::Short("stream".into()))
         }
-        if m.methods.iter().any(|x| x == "subtitles.get") {
+        if m.methods
+            .iter()
+            .any(|x| x == "subtitles.find" || x == "subtitles.get")
+        {
             resources.push(ManifestResource::Short("subtitles".into()))
         }
 
@@ -1,6 +1,10 @@
 use crate::addon_transport::AddonTransport;
+use crate::constants::{
+    CATALOG_PAGE_SIZE, GENRE_EXTRA_NAME, SEARCH_EXTRA_NAME, SKIP_EXTRA_NAME, VIDEO_HASH_EXTRA_NAME,
+    VIDEO_SIZE_EXTRA_NAME,
+};
 use crate::runtime::{Env, EnvError, EnvFutureExt, TryEnvFuture};
-use crate::types::addon::{Manifest, ResourcePath, ResourceResponse};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponse, ResourceResponseWithMetadata};
 use crate::types::resource::{MetaItem, MetaItemPreview, Stream, Subtitles};
 use futures::{future, TryFutureExt};
 use http::Request;
@@ -14,6 +18,8 @@ use self::legacy_manifest::LegacyManifestResp;
 
 const IMDB_PREFIX: &str = "tt";
 const YT_PREFIX: &str = "UC";
+const TOP_CATALOG_ID: &str = "top";
+const POPULARITY_SORT_PROP: &str = "popularity";
 
 // this is base64 for {"params":[],"method":"meta","id":1,"jsonrpc":"2.0"}
 const MANIFEST_REQUEST_PARAM: &str =
@@ -58,9 +64,22 @@ pub enum JsonRPCResp<T> {
 }
 
 #[derive(Deserialize)]
+pub struct SearchResult {
+    #[serde(default)]
+    pub results: Vec<MetaItemPreview>,
+}
+
+// The result may be `null` or without `all` when no subtitles are found
+#[derive(Default, Deserialize)]
 pub struct SubtitlesResult {
-    pub id: String,
-    pub all: Vec<Subtitles>,
+    #[serde(default)]
+    pub all: Vec<LegacySubtitles>,
+}
+
+#[derive(Deserialize)]
+pub struct LegacySubtitles {
+    pub url: Url,
+    pub lang: String,
 }
 
 impl From<Vec<MetaItemPreview>> for ResourceResponse {
@@ -78,10 +97,25 @@ impl From<Vec<Stream>> for ResourceResponse {
         ResourceResponse::Streams { streams }
     }
 }
-impl From<SubtitlesResult> for ResourceResponse {
-    fn from(subtitles_result: SubtitlesResult) -> Self {
+impl From<SearchResult> for ResourceResponse {
+    fn from(search_result: SearchResult) -> Self {
+        ResourceResponse::Metas {
+            metas: search_result.results,
+        }
+    }
+}
+impl From<Option<SubtitlesResult>> for ResourceResponse {
+    fn from(subtitles_result: Option<SubtitlesResult>) -> Self {
         ResourceResponse::Subtitles {
-            subtitles: subtitles_result.all,
+            subtitles: subtitles_result
+                .unwrap_or_default()
+                .all
+                .into_iter()
+                .map(|subtitles| Subtitles {
+                    lang: subtitles.lang,
+                    url: subtitles.url,
+                })
+                .collect(),
         }
     }
 }
@@ -116,28 +150,34 @@ impl<'a, T: Env> AddonLegacyTransport<'a, T> {
 }
 
 impl<'a, T: Env> AddonTransport for AddonLegacyTransport<'a, T> {
-    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponse> {
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
         let fetch_req = match build_legacy_req(self.transport_url, path) {
             Ok(r) => r,
             Err(e) => return future::err(e).boxed_env(),
         };
 
         match &path.resource as &str {
+            "catalog" if path.get_extra_first_value(SEARCH_EXTRA_NAME).is_some() => {
+                T::fetch::<_, JsonRPCResp<SearchResult>>(fetch_req)
+                    .and_then(map_response)
+                    .map_ok(|response| ResourceResponse::from(response).into())
+                    .boxed_env()
+            }
             "catalog" => T::fetch::<_, JsonRPCResp<Vec<MetaItemPreview>>>(fetch_req)
                 .and_then(map_response)
-                .map_ok(Into::into)
+                .map_ok(|response| ResourceResponse::from(response).into())
                 .boxed_env(),
             "meta" => T::fetch::<_, JsonRPCResp<MetaItem>>(fetch_req)
                 .and_then(map_response)
-                .map_ok(Into::into)
+                .map_ok(|response| ResourceResponse::from(response).into())
                 .boxed_env(),
             "stream" => T::fetch::<_, JsonRPCResp<Vec<Stream>>>(fetch_req)
                 .and_then(map_response)
-                .map_ok(Into::into)
+                .map_ok(|response| ResourceResponse::from(response).into())
                 .boxed_env(),
-            "subtitles" => T::fetch::<_, JsonRPCResp<SubtitlesResult>>(fetch_req)
+            "subtitles" => T::fetch::<_, JsonRPCResp<Option<SubtitlesResult>>>(fetch_req)
                 .and_then(map_response)
-                .map_ok(Into::into)
+                .map_ok(|response| ResourceResponse::from(response).into())
                 .boxed_env(),
             _ => future::err(LegacyErr::UnsupportedResource.into()).boxed_env(),
         }
@@ -153,42 +193,28 @@ impl<'a, T: Env> AddonTransport for AddonLegacyTransport<'a, T> {
 }
 
 fn build_legacy_req(transport_url: &Url, path: &ResourcePath) -> Result<Request<()>, EnvError> {
-    // Limitations of this legacy adapter:
-    // * does not support subtitles
-    // * does not support searching (meta.search)
-    // Those limitations are intentional, to avoid this getting complicated
-    // They affect functionality very little - there are no subtitles add-ons using the legacy
-    // protocol (other than OpenSubtitles, which will be ported) and there's only one
-    // known legacy add-on that support search (Stremio/stremio #379)
     let r#type = &path.r#type;
     let id = &path.id;
     let q_json = match &path.resource as &str {
-        "catalog" => {
-            let genre = path.get_extra_first_value("genre");
-            let query = if let Some(genre) = genre {
-                json!({ "type": r#type, "genre": genre })
-            } else {
-                json!({ "type": r#type })
-            };
-            // Just follows the convention set out by stremboard
-            // L287 cffb94e4a9c57f5872e768eff25164b53f004a2b
-            let sort = if id != "top" {
-                json!({ id.to_owned(): -1, "popularity": -1 })
-            } else {
-                serde_json::Value::Null
-            };
-            build_jsonrpc(
+        "catalog" => match path.get_extra_first_value(SEARCH_EXTRA_NAME) {
+            Some(search) => build_jsonrpc(
+                "meta.search",
+                json!({
+                    "query": search,
+                    "limit": CATALOG_PAGE_SIZE,
+                    "skip": skip_from_path(path),
+                }),
+            ),
+            None => build_jsonrpc(
                 "meta.find",
                 json!({
-                    "query": query,
-                    "limit": 100,
-                    "sort": sort,
-                    "skip": path.get_extra_first_value("skip")
-                        .map(|s| s.parse::<u32>().unwrap_or(0))
-                        .unwrap_or(0),
+                    "query": query_from_catalog(path),
+                    "limit": CATALOG_PAGE_SIZE,
+                    "sort": sort_from_catalog_id(id),
+                    "skip": skip_from_path(path),
                 }),
-            )
-        }
+            ),
+        },
         "meta" => build_jsonrpc("meta.get", json!({ "query": query_from_id(id) })),
         "stream" => {
             // Just use the query, but add "type" to it
@@ -203,10 +229,20 @@ fn build_legacy_req(transport_url: &Url, path: &ResourcePath) -> Result<Request<
             query.insert("type".into(), serde_json::Value::String(r#type.to_owned()));
             build_jsonrpc("stream.find", json!({ "query": query }))
         }
-        "subtitles" => build_jsonrpc(
-            "subtitles.find",
-            json!({ "query": json!({ "itemHash": id.replace(':', " ") }) }),
-        ),
+        "subtitles" => {
+            let mut query = serde_json::Map::new();
+            query.insert("itemHash".into(), json!(item_hash_from_id(id)));
+            if let Some(video_hash) = path.get_extra_first_value(VIDEO_HASH_EXTRA_NAME) {
+                query.insert("videoHash".into(), json!(video_hash));
+            }
+            if let Some(video_size) = path
+                .get_extra_first_value(VIDEO_SIZE_EXTRA_NAME)
+                .and_then(|video_size| video_size.parse::<u64>().ok())
+            {
+                query.insert("videoSize".into(), json!(video_size));
+            }
+            build_jsonrpc("subtitles.find", json!({ "query": query }))
+        }
         _ => return Err(LegacyErr::UnsupportedRequest.into()),
     };
     // NOTE: this is not using a URL safe base64 standard, which means that technically this is
@@ -229,6 +265,57 @@ fn build_jsonrpc(method: &str, params: serde_json::Value) -> serde_json::Value {
     })
 }
 
+fn skip_from_path(path: &ResourcePath) -> u32 {
+    path.get_extra_first_value(SKIP_EXTRA_NAME)
+        .and_then(|skip| skip.parse::<u32>().ok())
+        .unwrap_or(0)
+}
+
+// Legacy catalogs are the sorts from the manifest, each of them is a property of the
+// meta item. A catalog only contains the items which have its property and they are ordered
+// by it descending, with popularity as a tiebreaker.
+// The top catalog falls back to the default order of the addon, which is by popularity
+// Just follows the convention set out by stremboard
+// L287 cffb94e4a9c57f5872e768eff25164b53f004a2b
+fn query_from_catalog(path: &ResourcePath) -> serde_json::Value {
+    let mut query = serde_json::Map::new();
+    query.insert("type".into(), json!(path.r#type));
+    if path.id != TOP_CATALOG_ID {
+        query.insert(path.id.to_owned(), json!({ "$exists": true }));
+    }
+    if let Some(genre) = path.get_extra_first_value(GENRE_EXTRA_NAME) {
+        query.insert("genre".into(), json!(genre));
+    }
+    serde_json::Value::Object(query)
+}
+
+fn sort_from_catalog_id(id: &str) -> serde_json::Value {
+    match id {
+        TOP_CATALOG_ID => serde_json::Value::Null,
+        POPULARITY_SORT_PROP => json!({ POPULARITY_SORT_PROP: -1 }),
+        _ => json!({ id.to_owned(): -1, POPULARITY_SORT_PROP: -1 }),
+    }
+}
+
+// The item hash used by the legacy subtitles addons is the imdb id followed by the
+// season and the episode, separated by spaces
+fn item_hash_from_id(id: &str) -> String {
+    match query_from_id(id) {
+        serde_json::Value::Object(query) if query.contains_key("imdb_id") => {
+            ["imdb_id", "season", "episode"]
+                .iter()
+                .filter_map(|key| query.get(*key))
+                .map(|value| match value {
+                    serde_json::Value::String(value) => value.to_owned(),
+                    value => value.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        _ => id.replace(':', " "),
+    }
+}
+
 fn query_from_id(id: &str) -> serde_json::Value {
     let parts: Vec<&str> = id.split(':').collect();
     // IMDb format: tt...:(season:episode)?
@@ -267,7 +354,265 @@ fn query_from_id(id: &str) -> serde_json::Value {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::types::addon::ResourcePath;
+    use crate::types::addon::{ExtraValue, ManifestExtra, ManifestResource, ResourcePath};
+    use crate::types::resource::StreamSource;
+    use futures::executor::block_on;
+    use serde::de::DeserializeOwned;
+
+    const TRANSPORT_URL: &str = "https://legacy.strem.io/stremio/v1";
+
+    fn fixture<T: DeserializeOwned + Send + 'static>(fixture: &str) -> T {
+        let resp = serde_json::from_str::<JsonRPCResp<T>>(fixture).expect("fixture parse failed");
+        block_on(map_response(resp)).expect("fixture is not a result")
+    }
+
+    fn request_params(path: &ResourcePath) -> serde_json::Value {
+        let transport_url = Url::parse(TRANSPORT_URL).unwrap();
+        let url = build_legacy_req(&transport_url, path)
+            .expect("build_legacy_req failed")
+            .uri()
+            .to_string();
+        let (_, param) = url.split_once("/q.json?b=").expect("legacy request url");
+        let param = base64::decode(param).expect("legacy request param decode failed");
+        serde_json::from_slice(&param).expect("legacy request param parse failed")
+    }
+
+    fn extra_value(name: &str, value: &str) -> ExtraValue {
+        ExtraValue {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn manifest_from_fixture() {
+        let manifest: Manifest =
+            fixture::<LegacyManifestResp>(include_str!("fixtures/manifest.json")).into();
+        assert_eq!(
+            manifest
+                .catalogs
+                .iter()
+                .map(|catalog| (catalog.r#type.as_str(), catalog.id.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("movie", "popularities.legacy"),
+                ("movie", "year"),
+                ("series", "year"),
+                ("movie", "search"),
+                ("series", "search"),
+            ]
+        );
+        assert_eq!(
+            manifest.catalogs[0].extra,
+            ManifestExtra::Short {
+                required: vec![],
+                supported: vec!["genre".to_owned(), "skip".to_owned()],
+            }
+        );
+        assert_eq!(
+            manifest.catalogs[3].extra,
+            ManifestExtra::Short {
+                required: vec!["search".to_owned()],
+                supported: vec!["search".to_owned(), "skip".to_owned()],
+            }
+        );
+        assert_eq!(
+            manifest.resources,
+            vec![
+                ManifestResource::Short("meta".to_owned()),
+                ManifestResource::Short("stream".to_owned()),
+                ManifestResource::Short("subtitles".to_owned()),
+            ]
+        );
+        assert_eq!(
+            manifest.id_prefixes,
+            Some(vec!["tt".to_owned(), "legacy_id:".to_owned()])
+        );
+    }
+
+    #[test]
+    fn catalog_from_fixture() {
+        let path = ResourcePath {
+            resource: "catalog".to_owned(),
+            r#type: "movie".to_owned(),
+            id: "popularities.legacy".to_owned(),
+            extra: vec![extra_value("genre", "Horror"), extra_value("skip", "100")],
+        };
+        assert_eq!(
+            request_params(&path),
+            json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": "meta.find",
+                "params": [null, {
+                    "query": {
+                        "type": "movie",
+                        "popularities.legacy": { "$exists": true },
+                        "genre": "Horror",
+                    },
+                    "limit": CATALOG_PAGE_SIZE,
+                    "sort": { "popularities.legacy": -1, "popularity": -1 },
+                    "skip": 100,
+                }],
+            })
+        );
+        let path = ResourcePath::without_extra("catalog", "movie", "top");
+        assert_eq!(
+            request_params(&path)["params"][1]["query"],
+            json!({ "type": "movie" })
+        );
+        assert_eq!(
+            request_params(&path)["params"][1]["sort"],
+            serde_json::Value::Null
+        );
+        let path = ResourcePath::without_extra("catalog", "series", "year");
+        assert_eq!(
+            request_params(&path)["params"][1]["query"],
+            json!({ "type": "series", "year": { "$exists": true } })
+        );
+        assert_eq!(
+            request_params(&path)["params"][1]["sort"],
+            json!({ "year": -1, "popularity": -1 })
+        );
+        let path = ResourcePath::without_extra("catalog", "movie", "popularity");
+        assert_eq!(
+            request_params(&path)["params"][1]["sort"],
+            json!({ "popularity": -1 })
+        );
+        match ResourceResponse::from(fixture::<Vec<MetaItemPreview>>(include_str!(
+            "fixtures/catalog.json"
+        ))) {
+            ResourceResponse::Metas { metas } => {
+                assert_eq!(metas.len(), 2);
+                assert_eq!(metas[0].id, "tt0063350");
+                assert_eq!(metas[0].name, "Night of the Living Dead");
+                assert_eq!(metas[1].poster, None);
+            }
+            _ => panic!("expected metas"),
+        }
+    }
+
+    #[test]
+    fn search_from_fixture() {
+        let path = ResourcePath::with_extra(
+            "catalog",
+            "movie",
+            "search",
+            &[extra_value("search", "living dead")],
+        );
+        assert_eq!(
+            request_params(&path),
+            json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": "meta.search",
+                "params": [null, {
+                    "query": "living dead",
+                    "limit": CATALOG_PAGE_SIZE,
+                    "skip": 0,
+                }],
+            })
+        );
+        match ResourceResponse::from(fixture::<SearchResult>(include_str!(
+            "fixtures/search.json"
+        ))) {
+            ResourceResponse::Metas { metas } => {
+                assert_eq!(
+                    metas
+                        .iter()
+                        .map(|meta| meta.id.as_str())
+                        .collect::<Vec<_>>(),
+                    vec!["tt0063350"]
+                );
+            }
+            _ => panic!("expected metas"),
+        }
+    }
+
+    #[test]
+    fn meta_from_fixture() {
+        match ResourceResponse::from(fixture::<MetaItem>(include_str!("fixtures/meta.json"))) {
+            ResourceResponse::Meta { meta } => {
+                assert_eq!(meta.preview.name, "Night of the Living Dead");
+                assert_eq!(meta.preview.release_info, Some("1968".to_owned()));
+            }
+            _ => panic!("expected meta"),
+        }
+    }
+
+    #[test]
+    fn stream_from_fixture() {
+        match ResourceResponse::from(fixture::<Vec<Stream>>(include_str!("fixtures/stream.json"))) {
+            ResourceResponse::Streams { streams } => {
+                assert_eq!(streams.len(), 2);
+                assert_eq!(streams[0].description, Some("Archive.org 480p".to_owned()));
+                assert!(matches!(
+                    streams[1].source,
+                    StreamSource::Torrent {
+                        file_idx: Some(1),
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("expected streams"),
+        }
+    }
+
+    #[test]
+    fn subtitles_from_fixture() {
+        let path = ResourcePath::with_extra(
+            "subtitles",
+            "series",
+            "tt0386676:5:1",
+            &[
+                extra_value("videoHash", "8e245d9679d31e12"),
+                extra_value("videoSize", "1073741824"),
+            ],
+        );
+        assert_eq!(
+            request_params(&path),
+            json!({
+                "id": 1,
+                "jsonrpc": "2.0",
+                "method": "subtitles.find",
+                "params": [null, {
+                    "query": {
+                        "itemHash": "tt0386676 5 1",
+                        "videoHash": "8e245d9679d31e12",
+                        "videoSize": 1073741824_u64,
+                    },
+                }],
+            })
+        );
+        match ResourceResponse::from(fixture::<Option<SubtitlesResult>>(include_str!(
+            "fixtures/subtitles.json"
+        ))) {
+            ResourceResponse::Subtitles { subtitles } => {
+                assert_eq!(
+                    subtitles
+                        .iter()
+                        .map(|subtitles| subtitles.lang.as_str())
+                        .collect::<Vec<_>>(),
+                    vec!["eng", "bul"]
+                );
+            }
+            _ => panic!("expected subtitles"),
+        }
+        match ResourceResponse::from(fixture::<Option<SubtitlesResult>>(include_str!(
+            "fixtures/subtitles_empty.json"
+        ))) {
+            ResourceResponse::Subtitles { subtitles } => assert!(subtitles.is_empty()),
+            _ => panic!("expected subtitles"),
+        }
+    }
+
+    #[test]
+    fn item_hash() {
+        assert_eq!(item_hash_from_id("tt0386676:5:1"), "tt0386676 5 1");
+        assert_eq!(item_hash_from_id("tt0386676:05:01"), "tt0386676 5 1");
+        assert_eq!(item_hash_from_id("tt0063350"), "tt0063350");
+        assert_eq!(item_hash_from_id("custom:test"), "custom test");
+    }
 
     // Those are a bit sensitive for now, but that's a good thing, since it will force us
     // to pay attention to minor details that might matter with the legacy system
@@ -279,7 +624,7 @@ mod test {
         let path = ResourcePath::without_extra("catalog", "tv", "popularities.mixer");
         assert_eq!(
             &build_legacy_req(&transport_url, &path).unwrap().uri().to_string(),
-            "https://stremio-mixer.schneider.ax/stremioget/stremio/v1/q.json?b=eyJpZCI6MSwianNvbnJwYyI6IjIuMCIsIm1ldGhvZCI6Im1ldGEuZmluZCIsInBhcmFtcyI6W251bGwseyJsaW1pdCI6MTAwLCJxdWVyeSI6eyJ0eXBlIjoidHYifSwic2tpcCI6MCwic29ydCI6eyJwb3B1bGFyaXRpZXMubWl4ZXIiOi0xLCJwb3B1bGFyaXR5IjotMX19XX0=",
+            "https://stremio-mixer.schneider.ax/stremioget/stremio/v1/q.json?b=eyJpZCI6MSwianNvbnJwYyI6IjIuMCIsIm1ldGhvZCI6Im1ldGEuZmluZCIsInBhcmFtcyI6W251bGwseyJsaW1pdCI6MTAwLCJxdWVyeSI6eyJwb3B1bGFyaXRpZXMubWl4ZXIiOnsiJGV4aXN0cyI6dHJ1ZX0sInR5cGUiOiJ0diJ9LCJza2lwIjowLCJzb3J0Ijp7InBvcHVsYXJpdGllcy5taXhlciI6LTEsInBvcHVsYXJpdHkiOi0xfX1dfQ==",
         );
     }
 
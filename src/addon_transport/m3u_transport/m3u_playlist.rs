@@ -0,0 +1,313 @@
+use crate::constants::{
+    GENRE_EXTRA_NAME, M3U_CATALOG_ID, M3U_ID_PREFIX, M3U_TRANSPORT_SCHEME, SKIP_EXTRA_PROP,
+    URI_COMPONENT_ENCODE_SET,
+};
+use crate::types::addon::{
+    ExtraProp, Manifest, ManifestBehaviorHints, ManifestCatalog, ManifestExtra, ManifestResource,
+    OptionsLimit,
+};
+use crate::types::resource::{
+    MetaItem, MetaItemBehaviorHints, MetaItemPreview, PosterShape, Stream, StreamSource,
+};
+use itertools::Itertools;
+use percent_encoding::utf8_percent_encode;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use url::Url;
+
+const EXTINF_DIRECTIVE: &str = "#EXTINF:";
+const EXTGRP_DIRECTIVE: &str = "#EXTGRP:";
+const TVG_ID_ATTRIBUTE: &str = "tvg-id";
+const TVG_LOGO_ATTRIBUTE: &str = "tvg-logo";
+const GROUP_TITLE_ATTRIBUTE: &str = "group-title";
+const TV_TYPE: &str = "tv";
+
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum M3UError {
+    NoEntries,
+}
+
+impl fmt::Display for M3UError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            M3UError::NoEntries => write!(f, "Playlist has no entries"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct M3UEntry {
+    pub title: String,
+    pub url: Url,
+    pub tvg_id: Option<String>,
+    pub tvg_logo: Option<Url>,
+    pub group_title: Option<String>,
+}
+
+impl M3UEntry {
+    /// Entries with the same `tvg-id`, or the same title when it is missing, are one channel
+    fn channel_key(&self) -> &str {
+        self.tvg_id.as_deref().unwrap_or(&self.title)
+    }
+}
+
+/// An extended M3U playlist exposed as an in-process addon with a single `tv` catalog.
+///
+/// Entries are grouped into channels by their `tvg-id`, each entry becoming one of the streams
+/// of its channel. The `group-title` attributes are offered as `genre` options of the catalog.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct M3UPlaylist {
+    pub id: String,
+    pub name: String,
+    pub entries: Vec<M3UEntry>,
+}
+
+impl M3UPlaylist {
+    /// Parses the content of a playlist, skipping entries without a valid url.
+    /// The id is derived from the name, so importing a playlist with the same name replaces it,
+    /// while names that differ only in punctuation or case get distinct ids.
+    pub fn parse(name: &str, content: &str) -> Result<Self, M3UError> {
+        let mut entries = vec![];
+        let mut info: Option<(String, Vec<(String, String)>)> = None;
+        let mut group: Option<String> = None;
+        for line in content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            if let Some(extinf) = line.strip_prefix(EXTINF_DIRECTIVE) {
+                info = Some(parse_extinf(extinf));
+                group = None;
+            } else if let Some(extgrp) = line.strip_prefix(EXTGRP_DIRECTIVE) {
+                group = Some(extgrp.trim().to_owned()).filter(|group| !group.is_empty());
+            } else if line.starts_with('#') {
+                continue;
+            } else {
+                let (title, attributes) = info.take().unwrap_or_default();
+                let extgrp = group.take();
+                let url = match Url::parse(line) {
+                    Ok(url) => url,
+                    _ => continue,
+                };
+                let attribute = |name: &str| {
+                    attributes
+                        .iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                        .map(|(_, value)| value.to_owned())
+                        .filter(|value| !value.is_empty())
+                };
+                let title = Some(title)
+                    .filter(|title| !title.is_empty())
+                    .or_else(|| {
+                        url.path_segments()
+                            .and_then(|mut segments| segments.next_back())
+                            .filter(|segment| !segment.is_empty())
+                            .map(|segment| segment.to_owned())
+                    })
+                    .unwrap_or_else(|| url.to_string());
+                entries.push(M3UEntry {
+                    title,
+                    tvg_id: attribute(TVG_ID_ATTRIBUTE),
+                    tvg_logo: attribute(TVG_LOGO_ATTRIBUTE).and_then(|logo| Url::parse(&logo).ok()),
+                    group_title: attribute(GROUP_TITLE_ATTRIBUTE).or(extgrp),
+                    url,
+                });
+            };
+        }
+        if entries.is_empty() {
+            return Err(M3UError::NoEntries);
+        }
+        Ok(M3UPlaylist {
+            id: playlist_id(name),
+            name: name.to_owned(),
+            entries,
+        })
+    }
+    pub fn transport_url(&self) -> Url {
+        Url::parse(&format!("{}://{}", M3U_TRANSPORT_SCHEME, self.id))
+            .expect("M3U transport url build failed")
+    }
+    pub fn groups(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.group_title.to_owned())
+            .unique()
+            .collect()
+    }
+    pub fn manifest(&self) -> Manifest {
+        Manifest {
+            id: format!("local.m3u.{}", self.id),
+            version: Version::new(1, 0, 0),
+            name: self.name.to_owned(),
+            contact_email: None,
+            description: None,
+            logo: None,
+            background: None,
+            types: vec![TV_TYPE.to_owned()],
+            resources: vec![
+                ManifestResource::Short("catalog".to_owned()),
+                ManifestResource::Short("meta".to_owned()),
+                ManifestResource::Short("stream".to_owned()),
+            ],
+            id_prefixes: Some(vec![self.id_prefix()]),
+            catalogs: vec![ManifestCatalog {
+                id: M3U_CATALOG_ID.to_owned(),
+                r#type: TV_TYPE.to_owned(),
+                name: Some(self.name.to_owned()),
+                extra: ManifestExtra::Full {
+                    props: vec![
+                        ExtraProp {
+                            name: GENRE_EXTRA_NAME.to_owned(),
+                            is_required: false,
+                            options: self.groups(),
+                            options_limit: OptionsLimit::default(),
+                        },
+                        SKIP_EXTRA_PROP.to_owned(),
+                    ],
+                },
+            }],
+            addon_catalogs: vec![],
+            behavior_hints: ManifestBehaviorHints::default(),
+        }
+    }
+    /// The channels of a group, or of the whole playlist, in the order of their first entry
+    pub fn metas(&self, group: Option<&str>) -> Vec<MetaItemPreview> {
+        self.entries
+            .iter()
+            .filter(|entry| group.is_none() || entry.group_title.as_deref() == group)
+            .unique_by(|entry| entry.channel_key())
+            .map(|entry| self.meta_item_preview(entry))
+            .collect()
+    }
+    pub fn meta(&self, id: &str) -> Option<MetaItem> {
+        self.entries
+            .iter()
+            .find(|entry| self.channel_id(entry) == id)
+            .map(|entry| MetaItem {
+                preview: self.meta_item_preview(entry),
+                videos: vec![],
+            })
+    }
+    pub fn streams(&self, id: &str) -> Vec<Stream> {
+        self.entries
+            .iter()
+            .filter(|entry| self.channel_id(entry) == id)
+            .map(|entry| Stream {
+                source: StreamSource::Url {
+                    url: entry.url.to_owned(),
+                },
+                name: Some(self.name.to_owned()),
+                description: Some(entry.title.to_owned()),
+                thumbnail: None,
+                subtitles: vec![],
+                behavior_hints: Default::default(),
+            })
+            .collect()
+    }
+    fn id_prefix(&self) -> String {
+        format!("{}{}:", M3U_ID_PREFIX, self.id)
+    }
+    fn channel_id(&self, entry: &M3UEntry) -> String {
+        format!(
+            "{}{}",
+            self.id_prefix(),
+            utf8_percent_encode(entry.channel_key(), URI_COMPONENT_ENCODE_SET)
+        )
+    }
+    fn meta_item_preview(&self, entry: &M3UEntry) -> MetaItemPreview {
+        MetaItemPreview {
+            id: self.channel_id(entry),
+            r#type: TV_TYPE.to_owned(),
+            name: entry.title.to_owned(),
+            poster: entry.tvg_logo.to_owned(),
+            background: None,
+            logo: entry.tvg_logo.to_owned(),
+            description: entry.group_title.to_owned(),
+            release_info: None,
+            runtime: None,
+            released: None,
+            poster_shape: PosterShape::Square,
+            links: vec![],
+            trailer_streams: vec![],
+            behavior_hints: MetaItemBehaviorHints::default(),
+        }
+    }
+}
+
+/// Splits the content of an `#EXTINF` line into the title and the attributes.
+/// The title follows the first comma which is not within a quoted attribute value.
+fn parse_extinf(extinf: &str) -> (String, Vec<(String, String)>) {
+    let mut attributes = vec![];
+    let mut chars = extinf.char_indices().peekable();
+    // Skip the duration
+    while let Some((_, char)) = chars.peek() {
+        if char.is_whitespace() || *char == ',' {
+            break;
+        }
+        chars.next();
+    }
+    loop {
+        while chars.next_if(|(_, char)| char.is_whitespace()).is_some() {}
+        match chars.peek() {
+            Some((index, ',')) => {
+                return (extinf[index + 1..].trim().to_owned(), attributes);
+            }
+            Some(_) => {}
+            None => return (String::new(), attributes),
+        };
+        let mut key = String::new();
+        while let Some((_, char)) =
+            chars.next_if(|(_, char)| *char != '=' && *char != ',' && !char.is_whitespace())
+        {
+            key.push(char);
+        }
+        if chars.next_if(|(_, char)| *char == '=').is_none() {
+            continue;
+        }
+        let mut value = String::new();
+        if chars.next_if(|(_, char)| *char == '"').is_some() {
+            for (_, char) in chars.by_ref() {
+                if char == '"' {
+                    break;
+                }
+                value.push(char);
+            }
+        } else {
+            while let Some((_, char)) =
+                chars.next_if(|(_, char)| *char != ',' && !char.is_whitespace())
+            {
+                value.push(char);
+            }
+        }
+        attributes.push((key, value));
+    }
+}
+
+fn playlist_id(name: &str) -> String {
+    let slug = name
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() {
+                char.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .join("-");
+    let slug = if slug.is_empty() {
+        "playlist".to_owned()
+    } else {
+        slug
+    };
+    let hash = sha1_smol::Sha1::from(name).digest().to_string();
+    format!("{}-{}", slug, &hash[..8])
+}
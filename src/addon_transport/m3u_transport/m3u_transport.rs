@@ -0,0 +1,102 @@
+use crate::addon_transport::{AddonTransport, M3UPlaylist};
+use crate::constants::{
+    CATALOG_PAGE_SIZE, CATALOG_RESOURCE_NAME, GENRE_EXTRA_NAME, M3U_CATALOG_ID,
+    M3U_PLAYLIST_STORAGE_KEY_PREFIX, M3U_TRANSPORT_SCHEME, META_RESOURCE_NAME, SKIP_EXTRA_NAME,
+    STREAM_RESOURCE_NAME,
+};
+use crate::runtime::{Env, EnvError, EnvFutureExt, TryEnvFuture};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponse, ResourceResponseWithMetadata};
+use futures::{future, TryFutureExt};
+use std::marker::PhantomData;
+use url::Url;
+
+/// Serves an imported M3U playlist from the storage of the environment.
+///
+/// The transport url has the form `m3u://<playlist id>`.
+pub struct AddonM3UTransport<E: Env> {
+    transport_url: Url,
+    env: PhantomData<E>,
+}
+
+impl<E: Env> AddonM3UTransport<E> {
+    pub fn new(transport_url: Url) -> Self {
+        AddonM3UTransport {
+            transport_url,
+            env: PhantomData,
+        }
+    }
+    fn playlist(&self) -> TryEnvFuture<M3UPlaylist> {
+        let playlist_id = m3u_playlist_id(&self.transport_url)
+            .unwrap_or_default()
+            .to_owned();
+        E::get_storage::<M3UPlaylist>(&m3u_playlist_storage_key(&playlist_id))
+            .and_then(move |playlist| match playlist {
+                Some(playlist) => future::ok(playlist),
+                _ => future::err(EnvError::AddonTransport(format!(
+                    "m3u transport: playlist {} not found",
+                    playlist_id
+                ))),
+            })
+            .boxed_env()
+    }
+}
+
+impl<E: Env> AddonTransport for AddonM3UTransport<E> {
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
+        let path = path.to_owned();
+        self.playlist()
+            .and_then(move |playlist| {
+                let response = match path.resource.as_str() {
+                    CATALOG_RESOURCE_NAME if path.id == M3U_CATALOG_ID => {
+                        let skip = path
+                            .get_extra_first_value(SKIP_EXTRA_NAME)
+                            .and_then(|skip| skip.parse::<usize>().ok())
+                            .unwrap_or_default();
+                        let metas = playlist
+                            .metas(
+                                path.get_extra_first_value(GENRE_EXTRA_NAME)
+                                    .map(String::as_str),
+                            )
+                            .into_iter()
+                            .skip(skip)
+                            .take(CATALOG_PAGE_SIZE)
+                            .collect();
+                        Some(ResourceResponse::Metas { metas })
+                    }
+                    META_RESOURCE_NAME => playlist
+                        .meta(&path.id)
+                        .map(|meta| ResourceResponse::Meta { meta }),
+                    STREAM_RESOURCE_NAME => Some(ResourceResponse::Streams {
+                        streams: playlist.streams(&path.id),
+                    }),
+                    _ => None,
+                };
+                match response {
+                    Some(response) => future::ok(ResourceResponseWithMetadata::from(response)),
+                    _ => future::err(EnvError::AddonTransport(format!(
+                        "m3u transport: {} {} not found",
+                        path.resource, path.id
+                    ))),
+                }
+            })
+            .boxed_env()
+    }
+    fn manifest(&self) -> TryEnvFuture<Manifest> {
+        self.playlist()
+            .map_ok(|playlist| playlist.manifest())
+            .boxed_env()
+    }
+}
+
+pub fn m3u_playlist_storage_key(playlist_id: &str) -> String {
+    format!("{}{}", M3U_PLAYLIST_STORAGE_KEY_PREFIX, playlist_id)
+}
+
+/// The id of the imported playlist behind a transport url, if it is an `m3u://` one.
+pub fn m3u_playlist_id(transport_url: &Url) -> Option<&str> {
+    if transport_url.scheme() == M3U_TRANSPORT_SCHEME {
+        transport_url.host_str()
+    } else {
+        None
+    }
+}
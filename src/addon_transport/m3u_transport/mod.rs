@@ -0,0 +1,5 @@
+mod m3u_playlist;
+pub use m3u_playlist::*;
+
+mod m3u_transport;
+pub use m3u_transport::*;
@@ -1,8 +1,19 @@
 mod http_transport;
 pub use http_transport::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod process_transport;
+#[cfg(not(target_arch = "wasm32"))]
+pub use process_transport::*;
+
+mod m3u_transport;
+pub use m3u_transport::*;
+
 mod addon_transport;
 pub use addon_transport::*;
 
+mod scheduled_transport;
+pub use scheduled_transport::*;
+
 mod unsupported_transport;
 pub use unsupported_transport::*;
@@ -0,0 +1,2 @@
+mod process_transport;
+pub use process_transport::*;
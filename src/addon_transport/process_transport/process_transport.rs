@@ -0,0 +1,370 @@
+use crate::addon_transport::AddonTransport;
+use crate::constants::ADDON_PROCESS_TIMEOUT;
+use crate::runtime::{EnvError, EnvFutureExt, TryEnvFuture};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponseWithMetadata};
+use futures::channel::oneshot;
+use futures::{future, FutureExt};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+const JSONRPC_VERSION: &str = "2.0";
+const REQUEST_ID: u64 = 1;
+const ARG_QUERY_PARAM: &str = "arg";
+const TIMEOUT_QUERY_PARAM: &str = "timeout";
+const STDERR_TAIL_SIZE: usize = 512;
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Serialize)]
+struct JsonRPCRequest<'a, T> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct JsonRPCError {
+    message: String,
+    #[serde(default)]
+    code: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonRPCResponse<T> {
+    Result { result: T },
+    Error { error: JsonRPCError },
+}
+
+/// Runs an addon as a local executable and talks to it with JSON-RPC over stdio.
+///
+/// The transport url has the form `stdio:///path/to/executable?arg=--flag&arg=value&timeout=5000`.
+/// A new process is spawned for every call: the request is written as a single line to stdin,
+/// stdin is closed and the first line written to stdout is treated as the response.
+/// The process is killed once the response is read or when the timeout (in milliseconds) elapses,
+/// also when it keeps running after closing its stdout.
+pub struct AddonProcessTransport {
+    transport_url: Url,
+}
+
+impl AddonProcessTransport {
+    pub fn new(transport_url: Url) -> Self {
+        AddonProcessTransport { transport_url }
+    }
+    fn program(&self) -> String {
+        percent_decode_str(self.transport_url.path())
+            .decode_utf8_lossy()
+            .into_owned()
+    }
+    fn args(&self) -> Vec<String> {
+        self.transport_url
+            .query_pairs()
+            .filter(|(name, _)| name == ARG_QUERY_PARAM)
+            .map(|(_, value)| value.into_owned())
+            .collect()
+    }
+    fn timeout(&self) -> Duration {
+        self.transport_url
+            .query_pairs()
+            .find(|(name, _)| name == TIMEOUT_QUERY_PARAM)
+            .and_then(|(_, value)| value.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(ADDON_PROCESS_TIMEOUT)
+    }
+    fn call<
+        P: Serialize,
+        #[cfg(not(feature = "env-future-send"))] T: for<'de> Deserialize<'de> + 'static,
+        #[cfg(feature = "env-future-send")] T: for<'de> Deserialize<'de> + Send + 'static,
+    >(
+        &self,
+        method: &str,
+        params: P,
+    ) -> TryEnvFuture<T> {
+        let request = match serde_json::to_string(&JsonRPCRequest {
+            jsonrpc: JSONRPC_VERSION,
+            id: REQUEST_ID,
+            method,
+            params,
+        }) {
+            Ok(request) => request,
+            Err(error) => return future::err(EnvError::from(error)).boxed_env(),
+        };
+        let program = self.program();
+        let args = self.args();
+        let timeout = self.timeout();
+        let (tx, rx) = oneshot::channel();
+        thread::spawn(move || {
+            let _ = tx.send(execute(&program, &args, &request, timeout));
+        });
+        rx.map(|result| {
+            result
+                .unwrap_or_else(|_| {
+                    Err(EnvError::AddonTransport(
+                        "process transport: worker thread terminated".to_owned(),
+                    ))
+                })
+                .and_then(
+                    |response| match serde_json::from_str::<JsonRPCResponse<T>>(&response)? {
+                        JsonRPCResponse::Result { result } => Ok(result),
+                        JsonRPCResponse::Error { error } => Err(EnvError::AddonTransport(format!(
+                            "rpc error {}: {}",
+                            error.code, error.message
+                        ))),
+                    },
+                )
+        })
+        .boxed_env()
+    }
+}
+
+impl AddonTransport for AddonProcessTransport {
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
+        self.call("resource", path)
+    }
+    fn manifest(&self) -> TryEnvFuture<Manifest> {
+        self.call("manifest", Vec::<()>::new())
+    }
+}
+
+fn execute(
+    program: &str,
+    args: &[String],
+    request: &str,
+    timeout: Duration,
+) -> Result<String, EnvError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| {
+            EnvError::AddonTransport(format!(
+                "process transport: failed to spawn {}: {}",
+                program, error
+            ))
+        })?;
+    // Every pipe is served by its own thread so that a child which does not read its stdin
+    // or fills up one of its output pipes cannot block the others
+    let mut stdin = child.stdin.take().expect("child stdin is piped");
+    let stdout = child.stdout.take().expect("child stdout is piped");
+    let mut stderr = child.stderr.take().expect("child stderr is piped");
+    let (stdout_tx, stdout_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        let result = BufReader::new(stdout).read_line(&mut line).map(|_| line);
+        let _ = stdout_tx.send(result);
+    });
+    let stderr_reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+    let request = format!("{}\n", request);
+    let stdin_writer = thread::spawn(move || stdin.write_all(request.as_bytes()));
+    let deadline = Instant::now() + timeout;
+    let timed_out = || {
+        EnvError::AddonTransport(format!(
+            "process transport: {} timed out after {}ms",
+            program,
+            timeout.as_millis()
+        ))
+    };
+    match stdout_rx.recv_timeout(timeout) {
+        Ok(Ok(line)) if !line.trim().is_empty() => {
+            terminate(&mut child);
+            Ok(line)
+        }
+        Ok(result) => {
+            let status = match wait_until(&mut child, deadline) {
+                Ok(Some(status)) => status,
+                Ok(None) => {
+                    terminate(&mut child);
+                    return Err(timed_out());
+                }
+                Err(error) => {
+                    terminate(&mut child);
+                    return Err(EnvError::AddonTransport(format!(
+                        "process transport: {}",
+                        error
+                    )));
+                }
+            };
+            let stderr = stderr_reader.join().unwrap_or_default();
+            let stdin_result = stdin_writer.join().unwrap_or(Ok(()));
+            let reason = match (result, stdin_result) {
+                (Err(error), _) | (_, Err(error)) => error.to_string(),
+                _ => "no response".to_owned(),
+            };
+            Err(EnvError::AddonTransport(format!(
+                "process transport: {} exited with {} ({}){}",
+                program,
+                status,
+                reason,
+                stderr_tail(&stderr)
+                    .map(|stderr| format!(": {}", stderr))
+                    .unwrap_or_default()
+            )))
+        }
+        Err(_) => {
+            terminate(&mut child);
+            Err(timed_out())
+        }
+    }
+}
+
+/// Waits for the child to exit, returning `None` if it is still running at the deadline
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        };
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        };
+        thread::sleep(cmp::min(WAIT_POLL_INTERVAL, deadline - now));
+    }
+}
+
+fn terminate(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn stderr_tail(stderr: &str) -> Option<&str> {
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return None;
+    }
+    let start = stderr
+        .char_indices()
+        .map(|(index, _)| index)
+        .find(|index| stderr.len() - index <= STDERR_TAIL_SIZE)
+        .unwrap_or_default();
+    Some(&stderr[start..])
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::types::addon::ResourceResponse;
+    use futures::executor::block_on;
+    use url::form_urlencoded;
+
+    fn transport(script: &str, timeout: Option<u64>) -> AddonProcessTransport {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query.append_pair(ARG_QUERY_PARAM, "-c");
+        query.append_pair(ARG_QUERY_PARAM, script);
+        if let Some(timeout) = timeout {
+            query.append_pair(TIMEOUT_QUERY_PARAM, &timeout.to_string());
+        }
+        AddonProcessTransport::new(
+            Url::parse(&format!("stdio:///bin/sh?{}", query.finish())).expect("url parse failed"),
+        )
+    }
+
+    #[test]
+    fn transport_url() {
+        let transport = AddonProcessTransport::new(
+            Url::parse("stdio:///usr/local/bin/my%20addon?arg=--port&arg=0&timeout=250")
+                .expect("url parse failed"),
+        );
+        assert_eq!(transport.program(), "/usr/local/bin/my addon");
+        assert_eq!(transport.args(), vec!["--port".to_owned(), "0".to_owned()]);
+        assert_eq!(transport.timeout(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn manifest() {
+        let transport = transport(
+            r#"read request; case "$request" in *'"method":"manifest"'*) echo '{"jsonrpc":"2.0","id":1,"result":{"id":"local","version":"0.0.1","name":"Local","types":["movie"],"resources":["stream"]}}';; esac"#,
+            None,
+        );
+        let manifest = block_on(transport.manifest()).expect("manifest failed");
+        assert_eq!(manifest.id, "local");
+        assert_eq!(manifest.types, vec!["movie".to_owned()]);
+    }
+
+    #[test]
+    fn resource() {
+        let transport = transport(
+            r#"read request; case "$request" in *'"params":{"resource":"stream","type":"movie","id":"tt1","extra":[]}'*) echo '{"jsonrpc":"2.0","id":1,"result":{"streams":[{"url":"http://127.0.0.1/video.mp4"}]}}';; esac"#,
+            None,
+        );
+        let response =
+            block_on(transport.resource(&ResourcePath::without_extra("stream", "movie", "tt1")))
+                .expect("resource failed");
+        assert!(matches!(
+            response.content,
+            Some(ResourceResponse::Streams { streams }) if streams.len() == 1
+        ));
+    }
+
+    #[test]
+    fn rpc_error() {
+        let transport = transport(
+            r#"read request; echo '{"jsonrpc":"2.0","id":1,"error":{"code":404,"message":"not found"}}'"#,
+            None,
+        );
+        assert_eq!(
+            block_on(transport.manifest()).err(),
+            Some(EnvError::AddonTransport(
+                "rpc error 404: not found".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn crash() {
+        let transport = transport("read request; echo 'boom' >&2; exit 3", None);
+        match block_on(transport.manifest()) {
+            Err(EnvError::AddonTransport(message)) => {
+                assert!(message.contains("exit status: 3"), "{}", message);
+                assert!(message.ends_with(": boom"), "{}", message);
+            }
+            _ => panic!("expected crash to be reported"),
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let transport = transport("exec sleep 5", Some(100));
+        assert_eq!(
+            block_on(transport.manifest()).err(),
+            Some(EnvError::AddonTransport(
+                "process transport: /bin/sh timed out after 100ms".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn timeout_after_stdout_closed() {
+        let transport = transport("exec >&-; sleep 5", Some(100));
+        assert_eq!(
+            block_on(transport.manifest()).err(),
+            Some(EnvError::AddonTransport(
+                "process transport: /bin/sh timed out after 100ms".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn spawn_error() {
+        let transport = AddonProcessTransport::new(
+            Url::parse("stdio:///nonexistent/addon").expect("url parse failed"),
+        );
+        assert!(matches!(
+            block_on(transport.manifest()),
+            Err(EnvError::AddonTransport(message)) if message.starts_with("process transport: failed to spawn /nonexistent/addon")
+        ));
+    }
+}
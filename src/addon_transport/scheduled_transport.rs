@@ -0,0 +1,286 @@
+use crate::addon_transport::AddonTransport;
+use crate::constants::{ADDON_CONCURRENCY, ADDON_HOST_CONCURRENCY, ADDON_RATE_LIMIT_COOLDOWN};
+use crate::runtime::{Env, EnvError, EnvFutureExt, TryEnvFuture};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponseWithMetadata};
+use chrono::{DateTime, Duration, Utc};
+use derivative::Derivative;
+use futures::channel::oneshot;
+use futures::{future, FutureExt, TryFutureExt};
+use lazy_static::lazy_static;
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use url::Url;
+
+lazy_static! {
+    // Every Env type gets its own scheduler
+    static ref SCHEDULERS: Mutex<HashMap<TypeId, Scheduler>> = Default::default();
+}
+
+#[derive(Derivative, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derivative(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum AddonRequestPriority {
+    Low,
+    #[derivative(Default)]
+    Normal,
+    High,
+}
+
+struct Waiter {
+    env: TypeId,
+    priority: AddonRequestPriority,
+    sequence: u64,
+    host: String,
+    transport_url: Url,
+    sender: oneshot::Sender<Result<AddonRequestPermit, EnvError>>,
+}
+
+#[derive(Default)]
+struct Scheduler {
+    sequence: u64,
+    hosts: HashMap<String, usize>,
+    addons: HashMap<Url, usize>,
+    rate_limits: HashMap<String, DateTime<Utc>>,
+    waiters: Vec<Waiter>,
+}
+
+impl Scheduler {
+    fn dispatch(&mut self) -> Vec<Result<AddonRequestPermit, EnvError>> {
+        let mut undelivered = vec![];
+        self.waiters
+            .sort_by_key(|waiter| (Reverse(waiter.priority), waiter.sequence));
+        let mut waiters = vec![];
+        for waiter in self.waiters.drain(..) {
+            let host_count = self.hosts.get(&waiter.host).copied().unwrap_or_default();
+            let addon_count = self
+                .addons
+                .get(&waiter.transport_url)
+                .copied()
+                .unwrap_or_default();
+            if host_count < ADDON_HOST_CONCURRENCY && addon_count < ADDON_CONCURRENCY {
+                *self.hosts.entry(waiter.host.to_owned()).or_default() += 1;
+                *self
+                    .addons
+                    .entry(waiter.transport_url.to_owned())
+                    .or_default() += 1;
+                let permit = AddonRequestPermit {
+                    env: waiter.env,
+                    host: waiter.host,
+                    transport_url: waiter.transport_url,
+                };
+                if let Err(permit) = waiter.sender.send(Ok(permit)) {
+                    undelivered.push(permit);
+                };
+            } else {
+                waiters.push(waiter);
+            };
+        }
+        self.waiters = waiters;
+        undelivered
+    }
+    fn release(&mut self, host: &str, transport_url: &Url) {
+        if let Some(count) = self.hosts.get_mut(host) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.hosts.remove(host);
+            };
+        };
+        if let Some(count) = self.addons.get_mut(transport_url) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.addons.remove(transport_url);
+            };
+        };
+    }
+    fn rate_limit(
+        &mut self,
+        host: &str,
+        until: DateTime<Utc>,
+    ) -> Vec<Result<AddonRequestPermit, EnvError>> {
+        self.rate_limits.insert(host.to_owned(), until);
+        let (rejected, waiters) = self
+            .waiters
+            .drain(..)
+            .partition::<Vec<_>, _>(|waiter| waiter.host == host);
+        self.waiters = waiters;
+        rejected
+            .into_iter()
+            .filter_map(|waiter| {
+                waiter
+                    .sender
+                    .send(Err(EnvError::TooManyRequests(None)))
+                    .err()
+            })
+            .collect()
+    }
+}
+
+fn with_scheduler<T>(env: TypeId, f: impl FnOnce(&mut Scheduler) -> T) -> T {
+    let mut schedulers = SCHEDULERS.lock().expect("scheduler lock failed");
+    f(schedulers.entry(env).or_default())
+}
+
+/// Drops the queued requests, the running request counts and the rate limits of the given env.
+///
+/// The queued requests fail as cancelled.
+pub fn reset_addon_scheduler<E: Env + 'static>() {
+    let scheduler = SCHEDULERS
+        .lock()
+        .expect("scheduler lock failed")
+        .remove(&TypeId::of::<E>());
+    drop(scheduler);
+}
+
+/// A slot in the addon request scheduler.
+///
+/// The slot is returned to the scheduler when the permit is dropped,
+/// which lets the next queued request for the same host or addon proceed.
+pub struct AddonRequestPermit {
+    env: TypeId,
+    host: String,
+    transport_url: Url,
+}
+
+impl AddonRequestPermit {
+    /// Waits for a free slot for the given addon.
+    ///
+    /// Requests are served by priority and then in the order they were made.
+    /// Fails right away while the addon host is rate limited.
+    pub fn acquire<E: Env + 'static>(
+        transport_url: &Url,
+        priority: AddonRequestPriority,
+    ) -> TryEnvFuture<AddonRequestPermit> {
+        let host = scheduler_host(transport_url);
+        let (sender, receiver) = oneshot::channel();
+        let env = TypeId::of::<E>();
+        let undelivered = with_scheduler(env, |scheduler| {
+            match scheduler.rate_limits.get(&host) {
+                Some(until) if *until > E::now() => {
+                    return Err(EnvError::TooManyRequests(Some(
+                        (*until - E::now()).num_seconds().max(0) as u64,
+                    )));
+                }
+                Some(_) => {
+                    scheduler.rate_limits.remove(&host);
+                }
+                _ => {}
+            };
+            scheduler.sequence += 1;
+            let sequence = scheduler.sequence;
+            scheduler.waiters.push(Waiter {
+                env,
+                priority,
+                sequence,
+                host,
+                transport_url: transport_url.to_owned(),
+                sender,
+            });
+            Ok(scheduler.dispatch())
+        });
+        match undelivered {
+            Ok(undelivered) => drop(undelivered),
+            Err(error) => return future::err(error).boxed_env(),
+        };
+        receiver
+            .map(|result| {
+                result.unwrap_or_else(|_| {
+                    Err(EnvError::AddonTransport(
+                        "addon request was cancelled by the scheduler".to_owned(),
+                    ))
+                })
+            })
+            .boxed_env()
+    }
+    /// Stops scheduling requests to the host of this permit until the given time.
+    ///
+    /// Requests which are already queued for the host are rejected.
+    pub fn rate_limit(&self, until: DateTime<Utc>) {
+        let rejected = with_scheduler(self.env, |scheduler| {
+            scheduler.rate_limit(&self.host, until)
+        });
+        drop(rejected);
+    }
+}
+
+impl Drop for AddonRequestPermit {
+    fn drop(&mut self) {
+        let undelivered = with_scheduler(self.env, |scheduler| {
+            scheduler.release(&self.host, &self.transport_url);
+            scheduler.dispatch()
+        });
+        drop(undelivered);
+    }
+}
+
+/// Runs the requests of an addon transport through the addon request scheduler.
+///
+/// The underlying transport is created with `Env::unscheduled_addon_transport`
+/// only after a slot is acquired, so no request is started while queued.
+/// A `TooManyRequests` error rate limits the whole host,
+/// so `Env::fetch` has to map 429 responses to it.
+pub struct AddonScheduledTransport<E: Env> {
+    transport_url: Url,
+    priority: AddonRequestPriority,
+    env: PhantomData<E>,
+}
+
+impl<E: Env + 'static> AddonScheduledTransport<E> {
+    pub fn new(transport_url: Url, priority: AddonRequestPriority) -> Self {
+        AddonScheduledTransport {
+            transport_url,
+            priority,
+            env: PhantomData,
+        }
+    }
+    fn schedule<
+        #[cfg(not(feature = "env-future-send"))] T: 'static,
+        #[cfg(feature = "env-future-send")] T: Send + 'static,
+        #[cfg(not(feature = "env-future-send"))] F: FnOnce(&dyn AddonTransport) -> TryEnvFuture<T> + 'static,
+        #[cfg(feature = "env-future-send")] F: FnOnce(&dyn AddonTransport) -> TryEnvFuture<T> + Send + 'static,
+    >(
+        &self,
+        request: F,
+    ) -> TryEnvFuture<T> {
+        let transport_url = self.transport_url.to_owned();
+        AddonRequestPermit::acquire::<E>(&self.transport_url, self.priority)
+            .and_then(move |permit| {
+                request(E::unscheduled_addon_transport(&transport_url).as_ref()).map(
+                    move |result| {
+                        if let Err(EnvError::TooManyRequests(retry_after)) = &result {
+                            let cooldown = retry_after
+                                .map(|retry_after| Duration::seconds(retry_after as i64))
+                                .unwrap_or_else(|| Duration::seconds(ADDON_RATE_LIMIT_COOLDOWN));
+                            permit.rate_limit(E::now() + cooldown);
+                        };
+                        drop(permit);
+                        result
+                    },
+                )
+            })
+            .boxed_env()
+    }
+}
+
+impl<E: Env + 'static> AddonTransport for AddonScheduledTransport<E> {
+    fn resource(&self, path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
+        let path = path.to_owned();
+        self.schedule(move |transport| transport.resource(&path))
+    }
+    fn manifest(&self) -> TryEnvFuture<Manifest> {
+        self.schedule(|transport| transport.manifest())
+    }
+}
+
+fn scheduler_host(transport_url: &Url) -> String {
+    transport_url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .map(|host| match transport_url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_owned(),
+        })
+        .unwrap_or_else(|| transport_url.as_str().to_owned())
+}
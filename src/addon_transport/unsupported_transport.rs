@@ -1,6 +1,6 @@
 use crate::addon_transport::AddonTransport;
 use crate::runtime::{EnvError, EnvFutureExt, TryEnvFuture};
-use crate::types::addon::{Manifest, ResourcePath, ResourceResponse};
+use crate::types::addon::{Manifest, ResourcePath, ResourceResponseWithMetadata};
 use futures::future;
 use url::Url;
 
@@ -27,8 +27,8 @@ impl UnsupportedTransport {
 }
 
 impl AddonTransport for UnsupportedTransport {
-    fn resource(&self, _path: &ResourcePath) -> TryEnvFuture<ResourceResponse> {
-        self.result::<ResourceResponse>()
+    fn resource(&self, _path: &ResourcePath) -> TryEnvFuture<ResourceResponseWithMetadata> {
+        self.result::<ResourceResponseWithMetadata>()
     }
     fn manifest(&self) -> TryEnvFuture<Manifest> {
         self.result::<Manifest>()
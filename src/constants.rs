@@ -2,30 +2,55 @@ use crate::types::addon::{Descriptor, ExtraProp, OptionsLimit};
 use lazy_static::lazy_static;
 use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
 use std::collections::HashMap;
+use std::time::Duration;
 use url::Url;
 
 pub const SCHEMA_VERSION_STORAGE_KEY: &str = "schema_version";
 pub const PROFILE_STORAGE_KEY: &str = "profile";
 pub const LIBRARY_STORAGE_KEY: &str = "library";
 pub const LIBRARY_RECENT_STORAGE_KEY: &str = "library_recent";
+pub const LISTS_STORAGE_KEY: &str = "lists";
+pub const HISTORY_STORAGE_KEY: &str = "history";
+pub const LIBRARY_VIDEOS_STORAGE_KEY: &str = "library_videos";
+pub const M3U_PLAYLIST_STORAGE_KEY_PREFIX: &str = "m3u_playlist_";
 pub const LIBRARY_COLLECTION_NAME: &str = "libraryItem";
+pub const LISTS_COLLECTION_NAME: &str = "userList";
 pub const SEARCH_EXTRA_NAME: &str = "search";
+pub const GENRE_EXTRA_NAME: &str = "genre";
+pub const SKIP_EXTRA_NAME: &str = "skip";
+pub const VIDEO_HASH_EXTRA_NAME: &str = "videoHash";
+pub const VIDEO_SIZE_EXTRA_NAME: &str = "videoSize";
+pub const VIDEO_FILENAME_EXTRA_NAME: &str = "filename";
+pub const LAST_VIDEOS_IDS_EXTRA_NAME: &str = "lastVideosIds";
 pub const META_RESOURCE_NAME: &str = "meta";
 pub const STREAM_RESOURCE_NAME: &str = "stream";
 pub const CATALOG_RESOURCE_NAME: &str = "catalog";
 pub const SUBTITLES_RESOURCE_NAME: &str = "subtitles";
 pub const ADDON_MANIFEST_PATH: &str = "/manifest.json";
 pub const ADDON_LEGACY_PATH: &str = "/stremio/v1";
+pub const ADDON_HOST_CONCURRENCY: usize = 6;
+pub const ADDON_CONCURRENCY: usize = 4;
+pub const ADDON_RATE_LIMIT_COOLDOWN: i64 = 60;
+pub const ADDON_PROCESS_TIMEOUT: Duration = Duration::from_secs(30);
 pub const CATALOG_PAGE_SIZE: usize = 100;
 pub const CATALOG_PREVIEW_SIZE: usize = 10;
 pub const LIBRARY_RECENT_COUNT: usize = 200;
+pub const LIBRARY_SYNC_BATCH_SIZE: usize = 500;
+pub const LIBRARY_STATISTICS_TOP_GENRES_COUNT: usize = 5;
+pub const HISTORY_MAX_ENTRIES: usize = 1000;
+pub const UNDO_STACK_SIZE: usize = 10;
+pub const UNDO_EXPIRY: i64 = 60;
 pub const WATCHED_THRESHOLD_COEF: f64 = 0.7;
-pub const SCHEMA_VERSION: u32 = 5;
+pub const VIDEO_HASH_CHUNK_SIZE: u64 = 65536;
+pub const SCHEMA_VERSION: u32 = 6;
 pub const IMDB_LINK_CATEGORY: &str = "imdb";
 pub const GENRES_LINK_CATEGORY: &str = "Genres";
 pub const CINEMETA_TOP_CATALOG_ID: &str = "top";
 pub const IMDB_TITLE_PATH: &str = "title";
 pub const YOUTUBE_ADDON_ID_PREFIX: &str = "yt_id:";
+pub const M3U_TRANSPORT_SCHEME: &str = "m3u";
+pub const M3U_ID_PREFIX: &str = "m3u:";
+pub const M3U_CATALOG_ID: &str = "m3u";
 pub const URI_COMPONENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
     .remove(b'-')
     .remove(b'_')
@@ -6,11 +6,15 @@ use crate::deep_links::error_link::ErrorLink;
 use crate::deep_links::query_params_encode::query_params_encode;
 use crate::models::installed_addons_with_filters::InstalledAddonsRequest;
 use crate::models::library_with_filters::LibraryRequest;
+use crate::models::list_with_filters::ListRequest;
 use crate::types::addon::{ExtraValue, ResourceRequest};
 use crate::types::library::LibraryItem;
+use crate::types::lists::UserList;
 use crate::types::resource::{MetaItem, MetaItemPreview, Stream, StreamSource, Video};
 use percent_encoding::utf8_percent_encode;
 use serde::Serialize;
+use std::convert::TryFrom;
+use url::Url;
 
 #[derive(Serialize)]
 #[cfg_attr(debug_assertions, derive(Debug, PartialEq))]
@@ -19,9 +23,20 @@ pub struct ExternalPlayerLink {
     pub download: Option<String>,
 }
 
-impl From<&Stream> for ExternalPlayerLink {
-    fn from(stream: &Stream) -> Self {
-        match &stream.source {
+impl From<anyhow::Error> for ExternalPlayerLink {
+    fn from(error: anyhow::Error) -> Self {
+        ExternalPlayerLink {
+            href: ErrorLink::from(error).into(),
+            download: None,
+        }
+    }
+}
+
+/// Archives and NZB streams are opened through the streaming server with the given url
+impl TryFrom<(&Stream, &Url)> for ExternalPlayerLink {
+    type Error = anyhow::Error;
+    fn try_from((stream, streaming_server_url): (&Stream, &Url)) -> Result<Self, Self::Error> {
+        let link = match &stream.source {
             StreamSource::Url { url } if url.scheme() == "magnet" => ExternalPlayerLink {
                 href: url.as_str().to_owned(),
                 download: None,
@@ -37,7 +52,7 @@ impl From<&Stream> for ExternalPlayerLink {
                 href: stream
                     .magnet_url()
                     .map(|magnet_url| magnet_url.to_string())
-                    .expect("Failed to build magnet url for torrent"),
+                    .ok_or_else(|| anyhow::Error::msg("Failed to build magnet url for torrent"))?,
                 download: None,
             },
             StreamSource::External { external_url } => ExternalPlayerLink {
@@ -55,7 +70,24 @@ impl From<&Stream> for ExternalPlayerLink {
                 href: player_frame_url.as_str().to_owned(),
                 download: None,
             },
-        }
+            StreamSource::Rar { .. } | StreamSource::Zip { .. } | StreamSource::Nzb { .. } => {
+                ExternalPlayerLink {
+                    href: format!(
+                        "data:application/octet-stream;charset=utf-8;base64,{}",
+                        base64::encode(format!(
+                            "#EXTM3U\n#EXTINF:0\n{}",
+                            stream
+                                .streaming_server_url(streaming_server_url)
+                                .ok_or_else(|| anyhow::Error::msg(
+                                    "Failed to build streaming server url for stream"
+                                ))?
+                        ))
+                    ),
+                    download: Some("playlist.m3u".to_owned()),
+                }
+            }
+        };
+        Ok(link)
     }
 }
 
@@ -70,6 +102,13 @@ pub struct LibraryItemDeepLinks {
 
 impl From<&LibraryItem> for LibraryItemDeepLinks {
     fn from(item: &LibraryItem) -> Self {
+        LibraryItemDeepLinks::from((item, None))
+    }
+}
+
+/// The streams link targets the up next video when there is one.
+impl From<(&LibraryItem, Option<&Video>)> for LibraryItemDeepLinks {
+    fn from((item, up_next_video): (&LibraryItem, Option<&Video>)) -> Self {
         LibraryItemDeepLinks {
             meta_details_videos: item
                 .behavior_hints
@@ -81,10 +120,9 @@ impl From<&LibraryItem> for LibraryItemDeepLinks {
                     utf8_percent_encode(&item.r#type, URI_COMPONENT_ENCODE_SET),
                     utf8_percent_encode(&item.id, URI_COMPONENT_ENCODE_SET)
                 ))),
-            meta_details_streams: item
-                .state
-                .video_id
-                .as_ref()
+            meta_details_streams: up_next_video
+                .map(|video| &video.id)
+                .or(item.state.video_id.as_ref())
                 .or(item.behavior_hints.default_video_id.as_ref())
                 .map(|video_id| {
                     format!(
@@ -172,8 +210,8 @@ pub struct VideoDeepLinks {
     pub external_player: Option<ExternalPlayerLink>,
 }
 
-impl From<(&Video, &ResourceRequest)> for VideoDeepLinks {
-    fn from((video, request): (&Video, &ResourceRequest)) -> Self {
+impl From<(&Video, &ResourceRequest, &Url)> for VideoDeepLinks {
+    fn from((video, request, streaming_server_url): (&Video, &ResourceRequest, &Url)) -> Self {
         let stream = video.stream();
         VideoDeepLinks {
             meta_details_streams: format!(
@@ -197,9 +235,10 @@ impl From<(&Video, &ResourceRequest)> for VideoDeepLinks {
                 })
                 .transpose()
                 .unwrap_or_else(|error| Some(ErrorLink::from(error).into())),
-            external_player: stream
-                .as_ref()
-                .map(|stream| ExternalPlayerLink::from(stream.as_ref())),
+            external_player: stream.as_ref().map(|stream| {
+                ExternalPlayerLink::try_from((stream.as_ref(), streaming_server_url))
+                    .unwrap_or_else(ExternalPlayerLink::from)
+            }),
         }
     }
 }
@@ -211,8 +250,8 @@ pub struct StreamDeepLinks {
     pub external_player: ExternalPlayerLink,
 }
 
-impl From<&Stream> for StreamDeepLinks {
-    fn from(stream: &Stream) -> Self {
+impl From<(&Stream, &Url)> for StreamDeepLinks {
+    fn from((stream, streaming_server_url): (&Stream, &Url)) -> Self {
         StreamDeepLinks {
             player: stream
                 .encode()
@@ -223,14 +262,20 @@ impl From<&Stream> for StreamDeepLinks {
                     )
                 })
                 .unwrap_or_else(|error| ErrorLink::from(error).into()),
-            external_player: ExternalPlayerLink::from(stream),
+            external_player: ExternalPlayerLink::try_from((stream, streaming_server_url))
+                .unwrap_or_else(ExternalPlayerLink::from),
         }
     }
 }
 
-impl From<(&Stream, &ResourceRequest, &ResourceRequest)> for StreamDeepLinks {
+impl From<(&Stream, &ResourceRequest, &ResourceRequest, &Url)> for StreamDeepLinks {
     fn from(
-        (stream, stream_request, meta_request): (&Stream, &ResourceRequest, &ResourceRequest),
+        (stream, stream_request, meta_request, streaming_server_url): (
+            &Stream,
+            &ResourceRequest,
+            &ResourceRequest,
+            &Url,
+        ),
     ) -> Self {
         StreamDeepLinks {
             player: stream
@@ -247,7 +292,8 @@ impl From<(&Stream, &ResourceRequest, &ResourceRequest)> for StreamDeepLinks {
                     )
                 })
                 .unwrap_or_else(|error| ErrorLink::from(error).into()),
-            external_player: ExternalPlayerLink::from(stream),
+            external_player: ExternalPlayerLink::try_from((stream, streaming_server_url))
+                .unwrap_or_else(ExternalPlayerLink::from),
         }
     }
 }
@@ -330,36 +376,91 @@ impl From<&String> for LibraryDeepLinks {
 
 impl From<(&String, &LibraryRequest)> for LibraryDeepLinks {
     fn from((root, request): (&String, &LibraryRequest)) -> Self {
+        let query_params = vec![
+            (
+                "sort",
+                serde_json::to_value(&request.sort)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+            ),
+            ("page", request.page.to_string()),
+        ]
+        .into_iter()
+        .chain(
+            request
+                .search
+                .as_ref()
+                .map(|search| ("search", search.to_owned())),
+        )
+        .chain(request.status.as_ref().map(|status| {
+            (
+                "status",
+                serde_json::to_value(status)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+            )
+        }))
+        .collect::<Vec<_>>();
         LibraryDeepLinks {
             library: match &request.r#type {
                 Some(r#type) => format!(
                     "stremio:///{}/{}?{}",
                     root,
                     utf8_percent_encode(r#type, URI_COMPONENT_ENCODE_SET),
-                    query_params_encode(&[
-                        (
-                            "sort",
-                            serde_json::to_value(&request.sort)
-                                .unwrap()
-                                .as_str()
-                                .unwrap()
-                        ),
-                        ("page", &request.page.to_string())
-                    ]),
+                    query_params_encode(query_params),
+                ),
+                _ => format!("stremio:///{}?{}", root, query_params_encode(query_params)),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeepLinks {
+    pub list: String,
+}
+
+impl From<&UserList> for ListDeepLinks {
+    fn from(list: &UserList) -> Self {
+        ListDeepLinks {
+            list: format!(
+                "stremio:///list/{}",
+                utf8_percent_encode(&list.id, URI_COMPONENT_ENCODE_SET)
+            ),
+        }
+    }
+}
+
+impl From<&ListRequest> for ListDeepLinks {
+    fn from(request: &ListRequest) -> Self {
+        let query_params = vec![
+            (
+                "sort",
+                serde_json::to_value(&request.sort)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_owned(),
+            ),
+            ("page", request.page.to_string()),
+        ];
+        ListDeepLinks {
+            list: match &request.r#type {
+                Some(r#type) => format!(
+                    "stremio:///list/{}/{}?{}",
+                    utf8_percent_encode(&request.id, URI_COMPONENT_ENCODE_SET),
+                    utf8_percent_encode(r#type, URI_COMPONENT_ENCODE_SET),
+                    query_params_encode(query_params),
                 ),
                 _ => format!(
-                    "stremio:///{}?{}",
-                    root,
-                    query_params_encode(&[
-                        (
-                            "sort",
-                            serde_json::to_value(&request.sort)
-                                .unwrap()
-                                .as_str()
-                                .unwrap()
-                        ),
-                        ("page", &request.page.to_string())
-                    ]),
+                    "stremio:///list/{}?{}",
+                    utf8_percent_encode(&request.id, URI_COMPONENT_ENCODE_SET),
+                    query_params_encode(query_params)
                 ),
             },
         }
@@ -4,6 +4,7 @@ pub mod addon_transport;
 pub mod deep_links;
 pub mod models;
 pub mod runtime;
+pub mod subtitles;
 pub mod types;
 
 pub mod constants;
@@ -1,3 +1,4 @@
+use crate::addon_transport::AddonRequestPriority;
 use crate::constants::{SKIP_EXTRA_PROP, TYPE_PRIORITIES};
 use crate::models::common::{
     compare_with_priorities, eq_update, resource_update_with_vector_content, ResourceAction,
@@ -280,10 +281,14 @@ where
     let mut page = ResourceLoadable {
         request: request.to_owned(),
         content: None,
+        metadata: None,
     };
     let effects = resource_update_with_vector_content::<E, _>(
         &mut page,
-        ResourceAction::ResourceRequested { request },
+        ResourceAction::ResourceRequested {
+            request,
+            priority: AddonRequestPriority::Normal,
+        },
     );
     match page_request {
         CatalogPageRequest::First => *catalog = vec![page],
@@ -58,6 +58,7 @@ impl<E: Env + 'static> UpdateWithCtx<E> for CatalogsWithExtra {
                     Some(ResourceLoadable {
                         content: Some(Loadable::Ready(items)),
                         request,
+                        ..
                     }) => {
                         let skip = request
                             .path
@@ -83,6 +84,7 @@ impl<E: Env + 'static> UpdateWithCtx<E> for CatalogsWithExtra {
                         catalog.push(ResourceLoadable {
                             request: request.to_owned(),
                             content: Some(Loadable::Loading),
+                            metadata: None,
                         });
                         Effects::one(
                             EffectFuture::Concurrent(
@@ -157,6 +159,7 @@ fn catalogs_update<E: Env + 'static>(
                                 vec![ResourceLoadable {
                                     request: request.to_owned(),
                                     content: Some(Loadable::Loading),
+                                    metadata: None,
                                 }],
                                 Some(
                                     EffectFuture::Concurrent(
@@ -177,6 +180,7 @@ fn catalogs_update<E: Env + 'static>(
                                 vec![ResourceLoadable {
                                     request,
                                     content: None,
+                                    metadata: None,
                                 }],
                                 None,
                             ),
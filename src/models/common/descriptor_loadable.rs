@@ -1,3 +1,4 @@
+use crate::addon_transport::AddonRequestPriority;
 use crate::constants::OFFICIAL_ADDONS;
 use crate::models::common::Loadable;
 use crate::runtime::msg::{Internal, Msg};
@@ -40,7 +41,7 @@ pub fn descriptor_update<E: Env + 'static>(
                     content: Loadable::Loading,
                 });
                 Effects::future(EffectFuture::Concurrent(
-                    E::addon_transport(&transport_url)
+                    E::addon_transport_with_priority(&transport_url, AddonRequestPriority::High)
                         .manifest()
                         .map(move |result| {
                             Msg::Internal(Internal::ManifestRequestResult(transport_url, result))
@@ -1,7 +1,11 @@
+use crate::addon_transport::AddonRequestPriority;
 use crate::models::common::{eq_update, Loadable};
 use crate::runtime::msg::{Internal, Msg};
 use crate::runtime::{EffectFuture, Effects, Env, EnvError, EnvFutureExt};
-use crate::types::addon::{AggrRequest, Descriptor, ResourceRequest, ResourceResponse};
+use crate::types::addon::{
+    AggrRequest, Descriptor, ResourceRequest, ResourceResponse, ResourceResponseMetadata,
+    ResourceResponseWithMetadata,
+};
 use enclose::enclose;
 use futures::FutureExt;
 use serde::Serialize;
@@ -34,15 +38,18 @@ impl fmt::Display for ResourceError {
 pub struct ResourceLoadable<T> {
     pub request: ResourceRequest,
     pub content: Option<Loadable<T, ResourceError>>,
+    /// Caching and redirect hints of the last response
+    pub metadata: Option<ResourceResponseMetadata>,
 }
 
 pub enum ResourceAction<'a> {
     ResourceRequested {
         request: &'a ResourceRequest,
+        priority: AddonRequestPriority,
     },
     ResourceRequestResult {
         request: &'a ResourceRequest,
-        result: &'a Result<ResourceResponse, EnvError>,
+        result: &'a Result<ResourceResponseWithMetadata, EnvError>,
     },
 }
 
@@ -50,10 +57,11 @@ pub enum ResourcesAction<'a> {
     ResourcesRequested {
         request: &'a AggrRequest<'a>,
         addons: &'a [Descriptor],
+        priority: AddonRequestPriority,
     },
     ResourceRequestResult {
         request: &'a ResourceRequest,
-        result: &'a Result<ResourceResponse, EnvError>,
+        result: &'a Result<ResourceResponseWithMetadata, EnvError>,
     },
 }
 
@@ -63,13 +71,14 @@ where
     T: TryFrom<ResourceResponse, Error = &'static str>,
 {
     match action {
-        ResourceAction::ResourceRequested { request }
+        ResourceAction::ResourceRequested { request, priority }
             if resource.request != *request || resource.content.is_none() =>
         {
             resource.request = request.to_owned();
             resource.content = Some(Loadable::Loading);
+            resource.metadata = None;
             Effects::future(EffectFuture::Concurrent(
-                E::addon_transport(&request.base)
+                E::addon_transport_with_priority(&request.base, priority)
                     .resource(&request.path)
                     .map(enclose!((request) move |result| {
                         Msg::Internal(Internal::ResourceRequestResult(request, Box::new(result)))
@@ -83,6 +92,7 @@ where
             && matches!(resource.content, Some(Loadable::Loading)) =>
         {
             resource.content = Some(resource_content_from_result(result));
+            resource.metadata = resource_metadata_from_result(result);
             Effects::none()
         }
         _ => Effects::none().unchanged(),
@@ -103,6 +113,7 @@ where
                 && matches!(resource.content, Some(Loadable::Loading)) =>
         {
             resource.content = Some(resource_vector_content_from_result(result));
+            resource.metadata = resource_metadata_from_result(result);
             Effects::none()
         }
         _ => resource_update::<E, _>(resource, action),
@@ -118,7 +129,11 @@ where
     T: TryFrom<ResourceResponse, Error = &'static str> + Clone + PartialEq,
 {
     match action {
-        ResourcesAction::ResourcesRequested { request, addons } => {
+        ResourcesAction::ResourcesRequested {
+            request,
+            addons,
+            priority,
+        } => {
             let (next_resources, effects) = request
                 .plan(addons)
                 .into_iter()
@@ -132,10 +147,11 @@ where
                                 ResourceLoadable {
                                     request: request.to_owned(),
                                     content: Some(Loadable::Loading),
+                                    metadata: None,
                                 },
                                 Some(
                                     EffectFuture::Concurrent(
-                                        E::addon_transport(&request.base)
+                                        E::addon_transport_with_priority(&request.base, priority)
                                             .resource(&request.path)
                                             .map(|result| {
                                                 Msg::Internal(Internal::ResourceRequestResult(
@@ -163,6 +179,7 @@ where
             }) {
                 Some(resource) => {
                     resource.content = Some(resource_content_from_result(result));
+                    resource.metadata = resource_metadata_from_result(result);
                     Effects::none()
                 }
                 _ => Effects::none().unchanged(),
@@ -187,6 +204,7 @@ where
             }) {
                 Some(resource) => {
                     resource.content = Some(resource_vector_content_from_result(result));
+                    resource.metadata = resource_metadata_from_result(result);
                     Effects::none()
                 }
                 _ => Effects::none().unchanged(),
@@ -197,28 +215,35 @@ where
 }
 
 fn resource_content_from_result<T>(
-    result: &Result<ResourceResponse, EnvError>,
+    result: &Result<ResourceResponseWithMetadata, EnvError>,
 ) -> Loadable<T, ResourceError>
 where
     T: TryFrom<ResourceResponse, Error = &'static str>,
 {
     match result {
-        Ok(result) => match T::try_from(result.to_owned()) {
+        Ok(ResourceResponseWithMetadata {
+            content: Some(content),
+            ..
+        }) => match T::try_from(content.to_owned()) {
             Ok(content) => Loadable::Ready(content),
             Err(error) => Loadable::Err(ResourceError::UnexpectedResponse(error.to_owned())),
         },
+        Ok(_) => Loadable::Err(ResourceError::EmptyContent),
         Err(error) => Loadable::Err(ResourceError::Env(error.to_owned())),
     }
 }
 
 fn resource_vector_content_from_result<T>(
-    result: &Result<ResourceResponse, EnvError>,
+    result: &Result<ResourceResponseWithMetadata, EnvError>,
 ) -> Loadable<Vec<T>, ResourceError>
 where
     Vec<T>: TryFrom<ResourceResponse, Error = &'static str>,
 {
     match result {
-        Ok(result) => match <Vec<T>>::try_from(result.to_owned()) {
+        Ok(ResourceResponseWithMetadata {
+            content: Some(content),
+            ..
+        }) => match <Vec<T>>::try_from(content.to_owned()) {
             Ok(content) => {
                 if content.is_empty() {
                     Loadable::Err(ResourceError::EmptyContent)
@@ -228,6 +253,16 @@ where
             }
             Err(error) => Loadable::Err(ResourceError::UnexpectedResponse(error.to_owned())),
         },
+        Ok(_) => Loadable::Err(ResourceError::EmptyContent),
         Err(error) => Loadable::Err(ResourceError::Env(error.to_owned())),
     }
 }
+
+fn resource_metadata_from_result(
+    result: &Result<ResourceResponseWithMetadata, EnvError>,
+) -> Option<ResourceResponseMetadata> {
+    result
+        .as_ref()
+        .ok()
+        .map(|response| response.metadata.to_owned())
+}
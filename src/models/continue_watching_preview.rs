@@ -3,43 +3,104 @@ use crate::models::common::eq_update;
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::{Internal, Msg};
 use crate::runtime::{Effects, Env, UpdateWithCtx};
-use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryVideosBucket};
+use crate::types::resource::Video;
+use boolinator::Boolinator;
+use chrono::{DateTime, Utc};
 use lazysort::SortedBy;
 use serde::Serialize;
+use std::collections::HashMap;
 
 #[derive(Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContinueWatchingPreview {
     pub library_items: Vec<LibraryItem>,
+    /// The next episode to watch of the series whose current episode is watched, by library item id.
+    /// Only its id, season, episode and release date are set
+    pub up_next_videos: HashMap<String, Video>,
 }
 
 impl ContinueWatchingPreview {
-    pub fn new(library: &LibraryBucket) -> (Self, Effects) {
+    pub fn new<E: Env + 'static>(
+        library: &LibraryBucket,
+        library_videos: &LibraryVideosBucket,
+    ) -> (Self, Effects) {
         let mut library_items = vec![];
-        let effects = library_items_update(&mut library_items, library);
-        (Self { library_items }, effects.unchanged())
+        let mut up_next_videos = HashMap::new();
+        let effects = library_items_update(
+            &mut library_items,
+            &mut up_next_videos,
+            library,
+            library_videos,
+            E::now(),
+        );
+        (
+            Self {
+                library_items,
+                up_next_videos,
+            },
+            effects.unchanged(),
+        )
     }
 }
 
 impl<E: Env + 'static> UpdateWithCtx<E> for ContinueWatchingPreview {
     fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
         match msg {
-            Msg::Internal(Internal::LibraryChanged(_)) => {
-                library_items_update(&mut self.library_items, &ctx.library)
-            }
+            Msg::Internal(Internal::LibraryChanged(_))
+            | Msg::Internal(Internal::LibraryVideosChanged(_)) => library_items_update(
+                &mut self.library_items,
+                &mut self.up_next_videos,
+                &ctx.library,
+                &ctx.library_videos,
+                E::now(),
+            ),
             _ => Effects::none().unchanged(),
         }
     }
 }
 
-fn library_items_update(library_items: &mut Vec<LibraryItem>, library: &LibraryBucket) -> Effects {
+fn library_items_update(
+    library_items: &mut Vec<LibraryItem>,
+    up_next_videos: &mut HashMap<String, Video>,
+    library: &LibraryBucket,
+    library_videos: &LibraryVideosBucket,
+    now: DateTime<Utc>,
+) -> Effects {
     let next_library_items = library
         .items
         .values()
-        .filter(|library_item| library_item.is_in_continue_watching())
-        .sorted_by(|a, b| b.mtime.cmp(&a.mtime))
+        .filter_map(|library_item| {
+            let up_next_video = library_videos
+                .videos
+                .get(&library_item.id)
+                .and_then(|videos| library_item.up_next_video(videos, now));
+            let up_next_released = up_next_video.and_then(|video| video.released);
+            // A series which was already watched resurfaces when a new episode is released
+            let resurfaced = !library_item.removed
+                && matches!(up_next_released, Some(released) if released > library_item.mtime);
+            (library_item.is_in_continue_watching() || resurfaced)
+                .as_option()
+                .map(|_| {
+                    let sort_time = up_next_released
+                        .filter(|_| resurfaced)
+                        .unwrap_or(library_item.mtime);
+                    (library_item, up_next_video, sort_time)
+                })
+        })
+        .sorted_by(|(_, _, a), (_, _, b)| b.cmp(a))
         .take(CATALOG_PREVIEW_SIZE)
-        .cloned()
+        .collect::<Vec<_>>();
+    let next_up_next_videos = next_library_items
+        .iter()
+        .filter_map(|(library_item, up_next_video, _)| {
+            up_next_video.map(|video| (library_item.id.to_owned(), video.to_owned()))
+        })
+        .collect::<HashMap<_, _>>();
+    let next_library_items = next_library_items
+        .into_iter()
+        .map(|(library_item, _, _)| library_item.to_owned())
         .collect::<Vec<_>>();
     eq_update(library_items, next_library_items)
+        .join(eq_update(up_next_videos, next_up_next_videos))
 }
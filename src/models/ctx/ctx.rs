@@ -1,13 +1,19 @@
 use crate::constants::LIBRARY_COLLECTION_NAME;
-use crate::models::ctx::{update_library, update_profile, CtxError};
+use crate::models::ctx::{
+    update_history, update_library, update_library_videos, update_lists, update_profile,
+    update_undo, CtxError,
+};
 use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
 use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt, Update};
 use crate::types::api::{
     fetch_api, APIRequest, APIResult, AuthRequest, AuthResponse, CollectionResponse,
     DatastoreCommand, DatastoreRequest, SuccessResponse,
 };
-use crate::types::library::LibraryBucket;
+use crate::types::history::HistoryBucket;
+use crate::types::library::{LibraryBucket, LibraryItem, LibrarySync, LibraryVideosBucket};
+use crate::types::lists::ListsBucket;
 use crate::types::profile::{Auth, AuthKey, Profile};
+use crate::types::undo::UndoStack;
 use derivative::Derivative;
 use enclose::enclose;
 use futures::{future, FutureExt, TryFutureExt};
@@ -31,15 +37,33 @@ pub struct Ctx {
     #[serde(skip)]
     pub library: LibraryBucket,
     #[serde(skip)]
+    pub library_sync: LibrarySync,
+    #[serde(skip)]
+    pub library_videos: LibraryVideosBucket,
+    #[serde(skip)]
+    pub lists: ListsBucket,
+    #[serde(skip)]
+    pub history: HistoryBucket,
+    pub undo: UndoStack,
+    #[serde(skip)]
     #[derivative(Default(value = "CtxStatus::Ready"))]
     pub status: CtxStatus,
 }
 
 impl Ctx {
-    pub fn new(profile: Profile, library: LibraryBucket) -> Self {
+    pub fn new(
+        profile: Profile,
+        library: LibraryBucket,
+        library_videos: LibraryVideosBucket,
+        lists: ListsBucket,
+        history: HistoryBucket,
+    ) -> Self {
         Self {
             profile,
             library,
+            library_videos,
+            lists,
+            history,
             ..Self::default()
         }
     }
@@ -58,28 +82,66 @@ impl<E: Env + 'static> Update<E> for Ctx {
                     Some(auth_key) => Effects::one(delete_session::<E>(auth_key)).unchanged(),
                     _ => Effects::none().unchanged(),
                 };
+                let undo_effects = update_undo::<E>(
+                    &mut self.undo,
+                    &self.profile,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
                 let profile_effects = update_profile::<E>(&mut self.profile, &self.status, msg);
                 let library_effects = update_library::<E>(
                     &mut self.library,
+                    &mut self.library_sync,
                     self.profile.auth_key(),
                     &self.status,
                     msg,
                 );
+                let library_videos_effects = update_library_videos::<E>(
+                    &mut self.library_videos,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
+                let lists_effects =
+                    update_lists::<E>(&mut self.lists, self.profile.auth_key(), &self.status, msg);
+                let history_effects = update_history::<E>(&mut self.history, &self.status, msg);
                 self.status = CtxStatus::Ready;
                 Effects::msg(Msg::Event(Event::UserLoggedOut { uid }))
                     .unchanged()
                     .join(session_effects)
                     .join(profile_effects)
                     .join(library_effects)
+                    .join(library_videos_effects)
+                    .join(lists_effects)
+                    .join(history_effects)
+                    .join(undo_effects)
             }
             Msg::Internal(Internal::CtxAuthResult(auth_request, result)) => {
+                let undo_effects = update_undo::<E>(
+                    &mut self.undo,
+                    &self.profile,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
                 let profile_effects = update_profile::<E>(&mut self.profile, &self.status, msg);
                 let library_effects = update_library::<E>(
                     &mut self.library,
+                    &mut self.library_sync,
                     self.profile.auth_key(),
                     &self.status,
                     msg,
                 );
+                let library_videos_effects = update_library_videos::<E>(
+                    &mut self.library_videos,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
+                let lists_effects =
+                    update_lists::<E>(&mut self.lists, self.profile.auth_key(), &self.status, msg);
+                let history_effects = update_history::<E>(&mut self.history, &self.status, msg);
                 let ctx_effects = match &self.status {
                     CtxStatus::Loading(loading_auth_request)
                         if loading_auth_request == auth_request =>
@@ -101,17 +163,45 @@ impl<E: Env + 'static> Update<E> for Ctx {
                     }
                     _ => Effects::none().unchanged(),
                 };
-                profile_effects.join(library_effects).join(ctx_effects)
+                profile_effects
+                    .join(library_effects)
+                    .join(library_videos_effects)
+                    .join(lists_effects)
+                    .join(history_effects)
+                    .join(undo_effects)
+                    .join(ctx_effects)
             }
             _ => {
+                let undo_effects = update_undo::<E>(
+                    &mut self.undo,
+                    &self.profile,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
                 let profile_effects = update_profile::<E>(&mut self.profile, &self.status, msg);
                 let library_effects = update_library::<E>(
                     &mut self.library,
+                    &mut self.library_sync,
                     self.profile.auth_key(),
                     &self.status,
                     msg,
                 );
-                profile_effects.join(library_effects)
+                let library_videos_effects = update_library_videos::<E>(
+                    &mut self.library_videos,
+                    &self.library,
+                    &self.status,
+                    msg,
+                );
+                let lists_effects =
+                    update_lists::<E>(&mut self.lists, self.profile.auth_key(), &self.status, msg);
+                let history_effects = update_history::<E>(&mut self.history, &self.status, msg);
+                profile_effects
+                    .join(library_effects)
+                    .join(library_videos_effects)
+                    .join(lists_effects)
+                    .join(history_effects)
+                    .join(undo_effects)
             }
         }
     }
@@ -141,13 +231,10 @@ fn authenticate<E: Env + 'static>(auth_request: &AuthRequest) -> Effect {
                         APIResult::Err { error } => future::err(CtxError::from(error)),
                     })
                     .map_ok(|CollectionResponse { addons, .. }| addons),
-                    fetch_api::<E, _, _, _>(&DatastoreRequest {
+                    fetch_api::<E, _, _, _>(&DatastoreRequest::<LibraryItem> {
                         auth_key: auth.key.to_owned(),
                         collection: LIBRARY_COLLECTION_NAME.to_owned(),
-                        command: DatastoreCommand::Get {
-                            ids: vec![],
-                            all: true,
-                        },
+                        command: DatastoreCommand::Meta {},
                     })
                     .map_err(CtxError::from)
                     .and_then(|result| match result {
@@ -155,7 +242,7 @@ fn authenticate<E: Env + 'static>(auth_request: &AuthRequest) -> Effect {
                         APIResult::Err { error } => future::err(CtxError::from(error)),
                     }),
                 )
-                .map_ok(move |(addons, library_items)| (auth, addons, library_items))
+                .map_ok(move |(addons, library_mtimes)| (auth, addons, library_mtimes))
             })
             .map(enclose!((auth_request) move |result| {
                 Msg::Internal(Internal::CtxAuthResult(auth_request, result))
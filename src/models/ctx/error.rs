@@ -39,6 +39,10 @@ pub enum OtherError {
     AddonNotInstalled,
     AddonIsProtected,
     AddonConfigurationRequired,
+    M3UPlaylistInvalid,
+    LibraryImportInvalid,
+    ListNotFound,
+    HistoryEntryNotFound,
 }
 
 impl OtherError {
@@ -50,6 +54,10 @@ impl OtherError {
             OtherError::AddonNotInstalled => "Addon is not installed".to_owned(),
             OtherError::AddonIsProtected => "Addon is protected".to_owned(),
             OtherError::AddonConfigurationRequired => "Addon requires configuration".to_owned(),
+            OtherError::M3UPlaylistInvalid => "Playlist is invalid".to_owned(),
+            OtherError::LibraryImportInvalid => "Library import is invalid".to_owned(),
+            OtherError::ListNotFound => "List is not found".to_owned(),
+            OtherError::HistoryEntryNotFound => "History entry is not found".to_owned(),
         }
     }
     pub fn code(&self) -> u64 {
@@ -60,6 +68,10 @@ impl OtherError {
             OtherError::AddonNotInstalled => 4,
             OtherError::AddonIsProtected => 5,
             OtherError::AddonConfigurationRequired => 6,
+            OtherError::M3UPlaylistInvalid => 7,
+            OtherError::LibraryImportInvalid => 8,
+            OtherError::ListNotFound => 9,
+            OtherError::HistoryEntryNotFound => 10,
         }
     }
 }
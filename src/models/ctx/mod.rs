@@ -1,9 +1,21 @@
+mod update_history;
+use update_history::*;
+
 mod update_library;
 use update_library::*;
 
+mod update_library_videos;
+use update_library_videos::*;
+
+mod update_lists;
+use update_lists::*;
+
 mod update_profile;
 use update_profile::*;
 
+mod update_undo;
+use update_undo::*;
+
 mod error;
 pub use error::*;
 
@@ -0,0 +1,74 @@
+use crate::constants::HISTORY_STORAGE_KEY;
+use crate::models::ctx::{CtxError, CtxStatus, OtherError};
+use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
+use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt};
+use crate::types::history::HistoryBucket;
+use futures::FutureExt;
+
+pub fn update_history<E: Env + 'static>(
+    history: &mut HistoryBucket,
+    status: &CtxStatus,
+    msg: &Msg,
+) -> Effects {
+    match msg {
+        Msg::Action(Action::Ctx(ActionCtx::RemoveHistoryEntry { id, start })) => {
+            let event = Event::HistoryEntryRemoved {
+                id: id.to_owned(),
+                start: start.to_owned(),
+            };
+            match history.remove(id, start) {
+                Some(_) => Effects::msg(Msg::Event(event))
+                    .join(Effects::msg(Msg::Internal(Internal::HistoryChanged(false)))),
+                _ => Effects::msg(Msg::Event(Event::Error {
+                    error: CtxError::from(OtherError::HistoryEntryNotFound),
+                    source: Box::new(event),
+                }))
+                .unchanged(),
+            }
+        }
+        Msg::Internal(Internal::RecordHistoryEntry(entry)) => {
+            history.record(entry.to_owned());
+            Effects::msg(Msg::Internal(Internal::HistoryChanged(false)))
+        }
+        Msg::Internal(Internal::HistoryChanged(persisted)) if !persisted => {
+            Effects::one(push_history_to_storage::<E>(history)).unchanged()
+        }
+        Msg::Internal(Internal::CtxAuthResult(auth_request, result)) => match (status, result) {
+            (CtxStatus::Loading(loading_auth_request), Ok((auth, _, _)))
+                if loading_auth_request == auth_request =>
+            {
+                // The history is kept only on this device, so it is not dropped on logout and
+                // it is taken over by the user who logs in unless it belongs to another user
+                let entries = match &history.uid {
+                    Some(uid) if *uid != auth.user.id => vec![],
+                    _ => history.entries.to_owned(),
+                };
+                let next_history = HistoryBucket::new(Some(auth.user.id.to_owned()), entries);
+                if *history != next_history {
+                    *history = next_history;
+                    Effects::msg(Msg::Internal(Internal::HistoryChanged(false)))
+                } else {
+                    Effects::none().unchanged()
+                }
+            }
+            _ => Effects::none().unchanged(),
+        },
+        _ => Effects::none().unchanged(),
+    }
+}
+
+fn push_history_to_storage<E: Env + 'static>(history: &HistoryBucket) -> Effect {
+    let uid = history.uid.to_owned();
+    EffectFuture::Sequential(
+        E::set_storage(HISTORY_STORAGE_KEY, Some(history))
+            .map(move |result| match result {
+                Ok(_) => Msg::Event(Event::HistoryPushedToStorage { uid }),
+                Err(error) => Msg::Event(Event::Error {
+                    error: CtxError::from(error),
+                    source: Box::new(Event::HistoryPushedToStorage { uid }),
+                }),
+            })
+            .boxed_env(),
+    )
+    .into()
+}
@@ -7,7 +7,10 @@ use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt};
 use crate::types::api::{
     fetch_api, APIResult, DatastoreCommand, DatastoreRequest, LibraryItemModified, SuccessResponse,
 };
-use crate::types::library::{LibraryBucket, LibraryBucketRef, LibraryItem};
+use crate::types::library::{
+    LibraryBucket, LibraryBucketRef, LibraryExportRow, LibraryImportRowReport, LibraryImportStatus,
+    LibraryItem, LibraryItemState, LibrarySync,
+};
 use crate::types::profile::AuthKey;
 use futures::future::Either;
 use futures::{future, FutureExt, TryFutureExt};
@@ -16,12 +19,14 @@ use std::marker::PhantomData;
 
 pub fn update_library<E: Env + 'static>(
     library: &mut LibraryBucket,
+    sync: &mut LibrarySync,
     auth_key: Option<&AuthKey>,
     status: &CtxStatus,
     msg: &Msg,
 ) -> Effects {
     match msg {
         Msg::Action(Action::Ctx(ActionCtx::Logout)) => {
+            *sync = LibrarySync::default();
             let next_library = LibraryBucket::default();
             if *library != next_library {
                 *library = next_library;
@@ -76,7 +81,30 @@ pub fn update_library<E: Env + 'static>(
             }))
             .unchanged(),
         },
+        Msg::Action(Action::Ctx(ActionCtx::ImportLibrary { format, content })) => {
+            match LibraryExportRow::parse_export(*format, content) {
+                Ok(rows) => {
+                    let (library_items, report) = import_library_rows::<E>(library, rows);
+                    Effects::msgs(
+                        library_items
+                            .into_iter()
+                            .map(|library_item| {
+                                Msg::Internal(Internal::UpdateLibraryItem(library_item))
+                            })
+                            .collect(),
+                    )
+                    .join(Effects::msg(Msg::Event(Event::LibraryImported { report })))
+                    .unchanged()
+                }
+                Err(_) => Effects::msg(Msg::Event(Event::Error {
+                    error: CtxError::from(OtherError::LibraryImportInvalid),
+                    source: Box::new(Event::LibraryImported { report: vec![] }),
+                }))
+                .unchanged(),
+            }
+        }
         Msg::Action(Action::Ctx(ActionCtx::SyncLibraryWithAPI)) => match auth_key {
+            Some(_) if sync.is_running() => Effects::none().unchanged(),
             Some(auth_key) => Effects::one(plan_sync_with_api::<E>(library, auth_key)).unchanged(),
             _ => Effects::msg(Msg::Event(Event::Error {
                 error: CtxError::from(OtherError::UserNotLoggedIn),
@@ -109,17 +137,25 @@ pub fn update_library<E: Env + 'static>(
             Effects::one(push_library_to_storage::<E>(library)).unchanged()
         }
         Msg::Internal(Internal::CtxAuthResult(auth_request, result)) => match (status, result) {
-            (CtxStatus::Loading(loading_auth_request), Ok((auth, _, library_items)))
+            (CtxStatus::Loading(loading_auth_request), Ok((auth, _, library_mtimes)))
                 if loading_auth_request == auth_request =>
             {
-                let next_library =
-                    LibraryBucket::new(Some(auth.user.id.to_owned()), library_items.to_owned());
-                if *library != next_library {
+                // The library of the user is pulled incrementally after login
+                let next_library = LibraryBucket::new(Some(auth.user.id.to_owned()), vec![]);
+                let library_effects = if *library != next_library {
                     *library = next_library;
                     Effects::msg(Msg::Internal(Internal::LibraryChanged(false)))
                 } else {
                     Effects::none().unchanged()
-                }
+                };
+                *sync = LibrarySync::new(
+                    library_mtimes
+                        .iter()
+                        .map(|LibraryItemModified(id, _)| id.to_owned())
+                        .collect(),
+                    vec![],
+                );
+                library_effects.join(sync_batches_effects::<E>(library, sync, &auth.key))
             }
             _ => Effects::none().unchanged(),
         },
@@ -131,33 +167,16 @@ pub fn update_library<E: Env + 'static>(
             result,
         )) if Some(loading_auth_key) == auth_key => match result {
             Ok((pull_ids, push_ids)) => {
-                let push_items = library
-                    .items
-                    .iter()
-                    .filter(move |(id, _)| push_ids.iter().any(|push_id| push_id == *id))
-                    .map(|(_, item)| item)
-                    .cloned()
-                    .collect::<Vec<_>>();
-                let push_items_to_api_effects = if push_items.is_empty() {
-                    Effects::none().unchanged()
+                *sync = if sync.is_interrupted() {
+                    sync.resume(pull_ids.to_owned(), push_ids.to_owned())
                 } else {
-                    Effects::one(push_items_to_api::<E>(push_items, loading_auth_key)).unchanged()
-                };
-                let pull_items_from_api_effects = if pull_ids.is_empty() {
-                    Effects::none().unchanged()
-                } else {
-                    Effects::one(pull_items_from_api::<E>(
-                        pull_ids.to_owned(),
-                        loading_auth_key,
-                    ))
-                    .unchanged()
+                    LibrarySync::new(pull_ids.to_owned(), push_ids.to_owned())
                 };
                 Effects::msg(Msg::Event(Event::LibrarySyncWithAPIPlanned {
                     plan: (pull_ids.to_owned(), push_ids.to_owned()),
                 }))
-                .join(push_items_to_api_effects)
-                .join(pull_items_from_api_effects)
                 .unchanged()
+                .join(sync_batches_effects::<E>(library, sync, loading_auth_key))
             }
             Err(error) => Effects::msg(Msg::Event(Event::Error {
                 error: error.to_owned(),
@@ -175,26 +194,222 @@ pub fn update_library<E: Env + 'static>(
             },
             result,
         )) if Some(loading_auth_key) == auth_key => match result {
-            Ok(items) => Effects::msg(Msg::Event(Event::LibraryItemsPulledFromAPI {
-                ids: ids.to_owned(),
-            }))
-            .join(Effects::one(update_and_push_items_to_storage::<E>(
-                library,
-                items.to_owned(),
-            )))
-            .join(Effects::msg(Msg::Internal(Internal::LibraryChanged(true)))),
-            Err(error) => Effects::msg(Msg::Event(Event::Error {
-                error: error.to_owned(),
-                source: Box::new(Event::LibraryItemsPulledFromAPI {
+            Ok(items) => {
+                let sync_effects = if sync.pull.batch_succeeded(ids) {
+                    sync_batches_effects::<E>(library, sync, loading_auth_key)
+                } else {
+                    Effects::none().unchanged()
+                };
+                let mut conflicted_ids = vec![];
+                let merged_items = items
+                    .iter()
+                    .map(|remote_item| match library.items.get(&remote_item.id) {
+                        Some(local_item) => {
+                            let mut merged_item = local_item.merge(remote_item);
+                            if merged_item != *local_item && merged_item != *remote_item {
+                                merged_item.mtime = E::now();
+                                conflicted_ids.push(merged_item.id.to_owned());
+                            };
+                            merged_item
+                        }
+                        _ => remote_item.to_owned(),
+                    })
+                    .collect::<Vec<_>>();
+                let push_items = merged_items
+                    .iter()
+                    .filter(|merged_item| !items.contains(merged_item))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let push_items_to_api_effects = if push_items.is_empty() {
+                    Effects::none().unchanged()
+                } else {
+                    Effects::one(push_items_to_api::<E>(push_items, loading_auth_key)).unchanged()
+                };
+                let conflicts_effects = if conflicted_ids.is_empty() {
+                    Effects::none().unchanged()
+                } else {
+                    Effects::msg(Msg::Event(Event::LibraryItemsConflictsMerged {
+                        ids: conflicted_ids,
+                    }))
+                    .unchanged()
+                };
+                Effects::msg(Msg::Event(Event::LibraryItemsPulledFromAPI {
                     ids: ids.to_owned(),
-                }),
-            }))
-            .unchanged(),
+                }))
+                .join(conflicts_effects)
+                .join(push_items_to_api_effects)
+                .join(Effects::one(update_and_push_items_to_storage::<E>(
+                    library,
+                    merged_items,
+                )))
+                .join(Effects::msg(Msg::Internal(Internal::LibraryChanged(true))))
+                .join(sync_effects)
+            }
+            Err(error) => {
+                sync.pull.batch_failed(ids);
+                Effects::msg(Msg::Event(Event::Error {
+                    error: error.to_owned(),
+                    source: Box::new(Event::LibraryItemsPulledFromAPI {
+                        ids: ids.to_owned(),
+                    }),
+                }))
+                .unchanged()
+            }
         },
+        Msg::Internal(Internal::LibraryPushResult(
+            DatastoreRequest {
+                auth_key: loading_auth_key,
+                command: DatastoreCommand::Put { changes },
+                ..
+            },
+            result,
+        )) if Some(loading_auth_key) == auth_key => {
+            let ids = changes
+                .iter()
+                .map(|item| &item.id)
+                .cloned()
+                .collect::<Vec<_>>();
+            match result {
+                Ok(_) => {
+                    let sync_effects = if sync.push.batch_succeeded(&ids) {
+                        sync_batches_effects::<E>(library, sync, loading_auth_key)
+                    } else {
+                        Effects::none().unchanged()
+                    };
+                    Effects::msg(Msg::Event(Event::LibraryItemsPushedToAPI { ids }))
+                        .unchanged()
+                        .join(sync_effects)
+                }
+                Err(error) => {
+                    sync.push.batch_failed(&ids);
+                    Effects::msg(Msg::Event(Event::Error {
+                        error: error.to_owned(),
+                        source: Box::new(Event::LibraryItemsPushedToAPI { ids }),
+                    }))
+                    .unchanged()
+                }
+            }
+        }
         _ => Effects::none().unchanged(),
     }
 }
 
+// Maps the imported rows to library items merged with the existing ones.
+// Importing only adds to the library, so items are never removed or unflagged as watched.
+fn import_library_rows<E: Env + 'static>(
+    library: &LibraryBucket,
+    rows: Vec<Result<LibraryExportRow, String>>,
+) -> (Vec<LibraryItem>, Vec<LibraryImportRowReport>) {
+    let mut library_items = HashMap::<String, LibraryItem>::new();
+    let mut ids = vec![];
+    let report = rows
+        .into_iter()
+        .enumerate()
+        .map(|(row_index, row)| {
+            let row = match row {
+                Ok(row) => row,
+                Err(reason) => {
+                    return LibraryImportRowReport {
+                        row: row_index,
+                        id: None,
+                        status: LibraryImportStatus::Invalid(reason),
+                    }
+                }
+            };
+            let existing = library_items
+                .get(&row.id)
+                .or_else(|| library.items.get(&row.id));
+            let removed =
+                !row.in_library && !matches!(existing, Some(existing) if !existing.removed);
+            let watched = row.watched
+                || matches!(existing, Some(existing) if existing.state.flagged_watched > 0);
+            // Imports without a duration resolve their progress against the known one
+            let (time_offset, duration) = match (row.progress, existing) {
+                (Some(progress), Some(existing))
+                    if row.duration == 0 && existing.state.duration > 0 =>
+                {
+                    (
+                        (progress / 100.0 * existing.state.duration as f64).round() as u64,
+                        existing.state.duration,
+                    )
+                }
+                _ => (row.time_offset, row.duration),
+            };
+            let imported = LibraryItem {
+                id: row.id.to_owned(),
+                name: Some(row.name.to_owned())
+                    .filter(|name| !name.is_empty())
+                    .or_else(|| existing.map(|existing| existing.name.to_owned()))
+                    .unwrap_or_else(|| row.id.to_owned()),
+                r#type: row.r#type.to_owned(),
+                poster: existing.and_then(|existing| existing.poster.to_owned()),
+                poster_shape: existing
+                    .map(|existing| existing.poster_shape.to_owned())
+                    .unwrap_or_default(),
+                removed,
+                temp: removed && !matches!(existing, Some(existing) if !existing.temp),
+                ctime: existing
+                    .and_then(|existing| existing.ctime)
+                    .or_else(|| Some(E::now())),
+                mtime: E::now(),
+                state: LibraryItemState {
+                    last_watched: row.last_watched,
+                    time_offset,
+                    duration,
+                    times_watched: watched as u32,
+                    flagged_watched: watched as u32,
+                    video_id: row.video_id.to_owned().or_else(|| {
+                        existing.and_then(|existing| existing.state.video_id.to_owned())
+                    }),
+                    last_vid_released: existing
+                        .and_then(|existing| existing.state.last_vid_released),
+                    no_notif: matches!(existing, Some(existing) if existing.state.no_notif),
+                    ..LibraryItemState::default()
+                },
+                behavior_hints: existing
+                    .map(|existing| existing.behavior_hints.to_owned())
+                    .unwrap_or_default(),
+                genres: existing
+                    .map(|existing| existing.genres.to_owned())
+                    .unwrap_or_default(),
+                released: existing.and_then(|existing| existing.released),
+            };
+            let (library_item, status) = match existing {
+                Some(existing) => {
+                    let merged = imported.merge(existing);
+                    let unchanged = LibraryItem {
+                        mtime: existing.mtime,
+                        ..merged.to_owned()
+                    } == *existing;
+                    let status = if unchanged {
+                        LibraryImportStatus::Unchanged
+                    } else {
+                        LibraryImportStatus::Updated
+                    };
+                    (merged, status)
+                }
+                _ => (imported, LibraryImportStatus::Added),
+            };
+            if status != LibraryImportStatus::Unchanged {
+                if !ids.contains(&library_item.id) {
+                    ids.push(library_item.id.to_owned());
+                };
+                library_items.insert(library_item.id.to_owned(), library_item.to_owned());
+            };
+            LibraryImportRowReport {
+                row: row_index,
+                id: Some(library_item.id),
+                status,
+            }
+        })
+        .collect();
+    let library_items = ids
+        .into_iter()
+        .filter_map(|id| library_items.remove(&id))
+        .collect();
+    (library_items, report)
+}
+
 fn update_and_push_items_to_storage<E: Env + 'static>(
     library: &mut LibraryBucket,
     items: Vec<LibraryItem>,
@@ -205,7 +420,9 @@ fn update_and_push_items_to_storage<E: Env + 'static>(
         .cloned()
         .collect::<Vec<_>>();
     let are_items_in_recent = library.are_ids_in_recent(&ids);
-    library.merge_items(items);
+    library
+        .items
+        .extend(items.into_iter().map(|item| (item.id.to_owned(), item)));
     let push_to_storage_future = if library.items.len() <= LIBRARY_RECENT_COUNT {
         Either::Left(
             future::try_join_all(vec![
@@ -302,6 +519,58 @@ fn push_items_to_api<E: Env + 'static>(items: Vec<LibraryItem>, auth_key: &AuthK
     .into()
 }
 
+// Requests the next batch in each direction and reports the progress of the sync.
+// Only one batch per direction is waiting for a response at a time.
+fn sync_batches_effects<E: Env + 'static>(
+    library: &LibraryBucket,
+    sync: &mut LibrarySync,
+    auth_key: &AuthKey,
+) -> Effects {
+    let push_batch_effects = match sync.push.next_batch() {
+        Some(ids) => {
+            let items = ids
+                .iter()
+                .filter_map(|id| library.items.get(id))
+                .cloned()
+                .collect();
+            Effects::one(push_batch_to_api::<E>(items, auth_key)).unchanged()
+        }
+        _ => Effects::none().unchanged(),
+    };
+    let pull_batch_effects = match sync.pull.next_batch() {
+        Some(ids) => Effects::one(pull_items_from_api::<E>(ids, auth_key)).unchanged(),
+        _ => Effects::none().unchanged(),
+    };
+    let progress = sync.progress();
+    let progress_effects = if progress.pull_total + progress.push_total > 0 {
+        Effects::msg(Msg::Event(Event::LibrarySyncProgressed { progress })).unchanged()
+    } else {
+        Effects::none().unchanged()
+    };
+    progress_effects
+        .join(push_batch_effects)
+        .join(pull_batch_effects)
+}
+
+fn push_batch_to_api<E: Env + 'static>(items: Vec<LibraryItem>, auth_key: &AuthKey) -> Effect {
+    let request = DatastoreRequest {
+        auth_key: auth_key.to_owned(),
+        collection: LIBRARY_COLLECTION_NAME.to_owned(),
+        command: DatastoreCommand::Put { changes: items },
+    };
+    EffectFuture::Concurrent(
+        fetch_api::<E, _, _, _>(&request)
+            .map_err(CtxError::from)
+            .and_then(|result| match result {
+                APIResult::Ok { result } => future::ok(result),
+                APIResult::Err { error } => future::err(CtxError::from(error)),
+            })
+            .map(move |result| Msg::Internal(Internal::LibraryPushResult(request, result)))
+            .boxed_env(),
+    )
+    .into()
+}
+
 fn pull_items_from_api<E: Env + 'static>(ids: Vec<String>, auth_key: &AuthKey) -> Effect {
     let request = DatastoreRequest {
         auth_key: auth_key.to_owned(),
@@ -347,24 +616,20 @@ fn plan_sync_with_api<E: Env + 'static>(library: &LibraryBucket, auth_key: &Auth
                     .collect::<HashMap<_, _>>()
             })
             .map_ok(move |remote_mtimes| {
+                // Items changed on both sides are pulled in order to be merged,
+                // the merged ones which differ from the remote are pushed back afterwards
                 let pull_ids = remote_mtimes
                     .iter()
-                    .filter(|(id, remote_mtime)| {
-                        local_mtimes
-                            .get(*id)
-                            .map_or(true, |local_mtime| local_mtime < remote_mtime)
+                    .filter(|(id, remote_mtime)| match local_mtimes.get(*id) {
+                        Some(local_mtime) => local_mtime != *remote_mtime,
+                        _ => true,
                     })
                     .map(|(id, _)| id)
                     .cloned()
                     .collect();
                 let push_ids = local_mtimes
-                    .iter()
-                    .filter(|(id, local_mtime)| {
-                        remote_mtimes
-                            .get(*id)
-                            .map_or(true, |remote_mtime| remote_mtime < local_mtime)
-                    })
-                    .map(|(id, _)| id)
+                    .keys()
+                    .filter(|id| !remote_mtimes.contains_key(*id))
                     .cloned()
                     .collect();
                 (pull_ids, push_ids)
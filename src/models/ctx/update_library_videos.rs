@@ -0,0 +1,169 @@
+use crate::constants::{
+    CATALOG_RESOURCE_NAME, LAST_VIDEOS_IDS_EXTRA_NAME, LIBRARY_VIDEOS_STORAGE_KEY,
+    META_RESOURCE_NAME,
+};
+use crate::models::ctx::{CtxError, CtxStatus};
+use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
+use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt};
+use crate::types::addon::{ResourceResponse, ResourceResponseWithMetadata};
+use crate::types::library::{LibraryBucket, LibraryVideosBucket};
+use crate::types::resource::Video;
+use futures::FutureExt;
+
+pub fn update_library_videos<E: Env + 'static>(
+    library_videos: &mut LibraryVideosBucket,
+    library: &LibraryBucket,
+    status: &CtxStatus,
+    msg: &Msg,
+) -> Effects {
+    match msg {
+        Msg::Action(Action::Ctx(ActionCtx::Logout)) => {
+            let next_library_videos = LibraryVideosBucket::default();
+            if *library_videos != next_library_videos {
+                *library_videos = next_library_videos;
+                Effects::msg(Msg::Internal(Internal::LibraryVideosChanged(false)))
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        Msg::Internal(Internal::ResourceRequestResult(request, result))
+            if request.path.resource == META_RESOURCE_NAME =>
+        {
+            match result.as_ref() {
+                Ok(ResourceResponseWithMetadata {
+                    content: Some(ResourceResponse::Meta { meta }),
+                    ..
+                }) if !meta.videos.is_empty() && is_series(library, &meta.preview.id) => {
+                    let videos = meta.videos.iter().map(library_video).collect::<Vec<_>>();
+                    if library_videos.videos.get(&meta.preview.id) != Some(&videos) {
+                        library_videos
+                            .videos
+                            .insert(meta.preview.id.to_owned(), videos);
+                        Effects::msg(Msg::Internal(Internal::LibraryVideosChanged(false)))
+                    } else {
+                        Effects::none().unchanged()
+                    }
+                }
+                _ => Effects::none().unchanged(),
+            }
+        }
+        // The notifications catalogs return the latest videos of the series in the library
+        Msg::Internal(Internal::ResourceRequestResult(request, result))
+            if request.path.resource == CATALOG_RESOURCE_NAME
+                && request
+                    .path
+                    .get_extra_first_value(LAST_VIDEOS_IDS_EXTRA_NAME)
+                    .is_some() =>
+        {
+            match result.as_ref() {
+                Ok(ResourceResponseWithMetadata {
+                    content: Some(ResourceResponse::MetasDetailed { metas_detailed }),
+                    ..
+                }) => {
+                    let mut changed = false;
+                    for meta in metas_detailed {
+                        if !is_series(library, &meta.preview.id) {
+                            continue;
+                        }
+                        // Only the cached videos are refreshed as the latest ones are not enough
+                        // to tell which of the videos are watched
+                        if let Some(videos) = library_videos.videos.get_mut(&meta.preview.id) {
+                            let next_videos = merge_videos(videos, &meta.videos);
+                            if *videos != next_videos {
+                                *videos = next_videos;
+                                changed = true;
+                            }
+                        }
+                    }
+                    if changed {
+                        Effects::msg(Msg::Internal(Internal::LibraryVideosChanged(false)))
+                    } else {
+                        Effects::none().unchanged()
+                    }
+                }
+                _ => Effects::none().unchanged(),
+            }
+        }
+        Msg::Internal(Internal::LibraryChanged(_)) => {
+            let videos_count = library_videos.videos.len();
+            library_videos.retain_library(library);
+            if library_videos.videos.len() != videos_count {
+                Effects::msg(Msg::Internal(Internal::LibraryVideosChanged(false)))
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        Msg::Internal(Internal::LibraryVideosChanged(persisted)) if !persisted => {
+            Effects::one(push_library_videos_to_storage::<E>(library_videos)).unchanged()
+        }
+        Msg::Internal(Internal::CtxAuthResult(auth_request, result)) => match (status, result) {
+            (CtxStatus::Loading(loading_auth_request), Ok((auth, _, _)))
+                if loading_auth_request == auth_request =>
+            {
+                let next_library_videos = LibraryVideosBucket::new(Some(auth.user.id.to_owned()));
+                if *library_videos != next_library_videos {
+                    *library_videos = next_library_videos;
+                    Effects::msg(Msg::Internal(Internal::LibraryVideosChanged(false)))
+                } else {
+                    Effects::none().unchanged()
+                }
+            }
+            _ => Effects::none().unchanged(),
+        },
+        _ => Effects::none().unchanged(),
+    }
+}
+
+fn is_series(library: &LibraryBucket, id: &str) -> bool {
+    matches!(library.items.get(id), Some(library_item) if library_item.r#type == "series")
+}
+
+// Only what is needed to find the next video is kept
+fn library_video(video: &Video) -> Video {
+    Video {
+        id: video.id.to_owned(),
+        title: Default::default(),
+        released: video.released,
+        overview: None,
+        thumbnail: None,
+        streams: vec![],
+        series_info: video.series_info.to_owned(),
+        trailer_streams: vec![],
+    }
+}
+
+// Updates the cached videos with the latest ones, keeping them ordered by season and episode
+fn merge_videos(videos: &[Video], latest_videos: &[Video]) -> Vec<Video> {
+    let mut next_videos = videos.to_owned();
+    for video in latest_videos.iter().map(library_video) {
+        match next_videos.iter_mut().find(|next| next.id == video.id) {
+            Some(next) => *next = video,
+            None => next_videos.push(video),
+        }
+    }
+    next_videos.sort_by_key(|video| {
+        video
+            .series_info
+            .as_ref()
+            .map(|info| (info.season, info.episode))
+    });
+    next_videos
+}
+
+fn push_library_videos_to_storage<E: Env + 'static>(
+    library_videos: &LibraryVideosBucket,
+) -> Effect {
+    let uid = library_videos.uid.to_owned();
+    EffectFuture::Sequential(
+        E::set_storage(LIBRARY_VIDEOS_STORAGE_KEY, Some(library_videos))
+            .map(move |result| match result {
+                Ok(_) => Msg::Event(Event::LibraryVideosPushedToStorage { uid }),
+                Err(error) => Msg::Event(Event::Error {
+                    error: CtxError::from(error),
+                    source: Box::new(Event::LibraryVideosPushedToStorage { uid }),
+                }),
+            })
+            .boxed_env(),
+    )
+    .into()
+}
@@ -0,0 +1,351 @@
+use crate::constants::{LISTS_COLLECTION_NAME, LISTS_STORAGE_KEY};
+use crate::models::ctx::{CtxError, CtxStatus, OtherError};
+use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
+use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt};
+use crate::types::api::{
+    fetch_api, APIResult, DatastoreCommand, DatastoreRequest, LibraryItemModified, SuccessResponse,
+};
+use crate::types::lists::{ListsBucket, UserList, UserListItem};
+use crate::types::profile::AuthKey;
+use futures::{future, FutureExt, TryFutureExt};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+pub fn update_lists<E: Env + 'static>(
+    lists: &mut ListsBucket,
+    auth_key: Option<&AuthKey>,
+    status: &CtxStatus,
+    msg: &Msg,
+) -> Effects {
+    match msg {
+        Msg::Action(Action::Ctx(ActionCtx::Logout)) => {
+            let next_lists = ListsBucket::default();
+            if *lists != next_lists {
+                *lists = next_lists;
+                Effects::msg(Msg::Internal(Internal::ListsChanged(false)))
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::CreateList(name))) => {
+            let timestamp = E::now().timestamp_millis();
+            let id = (0..)
+                .map(|index| match index {
+                    0 => timestamp.to_string(),
+                    _ => format!("{}.{}", timestamp, index),
+                })
+                .find(|id| !lists.lists.contains_key(id))
+                .expect("list id build failed");
+            let list = UserList::new::<E>(id.to_owned(), name.trim().to_owned());
+            Effects::msg(Msg::Internal(Internal::UpdateList(list)))
+                .join(Effects::msg(Msg::Event(Event::ListCreated { id })))
+                .unchanged()
+        }
+        Msg::Action(Action::Ctx(ActionCtx::RenameList { id, name })) => {
+            match active_list(lists, id) {
+                Some(list) => {
+                    let mut list = list.to_owned();
+                    list.name = name.trim().to_owned();
+                    Effects::msg(Msg::Internal(Internal::UpdateList(list)))
+                        .join(Effects::msg(Msg::Event(Event::ListRenamed {
+                            id: id.to_owned(),
+                        })))
+                        .unchanged()
+                }
+                _ => list_not_found_effects(Event::ListRenamed { id: id.to_owned() }),
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::DeleteList(id))) => match active_list(lists, id) {
+            Some(list) => {
+                let mut list = list.to_owned();
+                list.removed = true;
+                list.items = vec![];
+                Effects::msg(Msg::Internal(Internal::UpdateList(list)))
+                    .join(Effects::msg(Msg::Event(Event::ListDeleted {
+                        id: id.to_owned(),
+                    })))
+                    .unchanged()
+            }
+            _ => list_not_found_effects(Event::ListDeleted { id: id.to_owned() }),
+        },
+        Msg::Action(Action::Ctx(ActionCtx::AddToList { id, meta_item })) => {
+            let event = Event::ListItemAdded {
+                id: id.to_owned(),
+                item_id: meta_item.id.to_owned(),
+            };
+            match active_list(lists, id) {
+                Some(list) if list.items.iter().any(|item| item.id == meta_item.id) => {
+                    Effects::msg(Msg::Event(event)).unchanged()
+                }
+                Some(list) => {
+                    let mut list = list.to_owned();
+                    list.items
+                        .push(UserListItem::from((meta_item, PhantomData::<E>)));
+                    Effects::msg(Msg::Internal(Internal::UpdateList(list)))
+                        .join(Effects::msg(Msg::Event(event)))
+                        .unchanged()
+                }
+                _ => list_not_found_effects(event),
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::RemoveFromList { id, item_id })) => {
+            let event = Event::ListItemRemoved {
+                id: id.to_owned(),
+                item_id: item_id.to_owned(),
+            };
+            match active_list(lists, id) {
+                Some(list) if list.items.iter().any(|item| item.id == *item_id) => {
+                    let mut list = list.to_owned();
+                    list.items.retain(|item| item.id != *item_id);
+                    Effects::msg(Msg::Internal(Internal::UpdateList(list)))
+                        .join(Effects::msg(Msg::Event(event)))
+                        .unchanged()
+                }
+                Some(_) => Effects::msg(Msg::Event(event)).unchanged(),
+                _ => list_not_found_effects(event),
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::SyncListsWithAPI)) => match auth_key {
+            Some(auth_key) => Effects::one(plan_sync_with_api::<E>(lists, auth_key)).unchanged(),
+            _ => Effects::msg(Msg::Event(Event::Error {
+                error: CtxError::from(OtherError::UserNotLoggedIn),
+                source: Box::new(Event::ListsSyncWithAPIPlanned {
+                    plan: Default::default(),
+                }),
+            }))
+            .unchanged(),
+        },
+        Msg::Internal(Internal::UpdateList(list)) => {
+            let mut list = list.to_owned();
+            list.mtime = E::now();
+            let push_to_api_effects = match auth_key {
+                Some(auth_key) => {
+                    Effects::one(push_lists_to_api::<E>(vec![list.to_owned()], auth_key))
+                        .unchanged()
+                }
+                _ => Effects::none().unchanged(),
+            };
+            lists.lists.insert(list.id.to_owned(), list);
+            push_to_api_effects
+                .join(Effects::one(push_lists_to_storage::<E>(lists)).unchanged())
+                .join(Effects::msg(Msg::Internal(Internal::ListsChanged(true))))
+        }
+        Msg::Internal(Internal::ListsChanged(persisted)) if !persisted => {
+            Effects::one(push_lists_to_storage::<E>(lists)).unchanged()
+        }
+        Msg::Internal(Internal::CtxAuthResult(auth_request, result)) => match (status, result) {
+            (CtxStatus::Loading(loading_auth_request), Ok((auth, _, _)))
+                if loading_auth_request == auth_request =>
+            {
+                // The lists of the user are pulled as a whole after login
+                let next_lists = ListsBucket::new(Some(auth.user.id.to_owned()), vec![]);
+                let lists_effects = if *lists != next_lists {
+                    *lists = next_lists;
+                    Effects::msg(Msg::Internal(Internal::ListsChanged(false)))
+                } else {
+                    Effects::none().unchanged()
+                };
+                lists_effects.join(
+                    Effects::one(pull_lists_from_api::<E>(vec![], true, &auth.key)).unchanged(),
+                )
+            }
+            _ => Effects::none().unchanged(),
+        },
+        Msg::Internal(Internal::ListsSyncPlanResult(
+            DatastoreRequest {
+                auth_key: loading_auth_key,
+                ..
+            },
+            result,
+        )) if Some(loading_auth_key) == auth_key => match result {
+            Ok((pull_ids, push_ids)) => {
+                let push_lists = lists
+                    .lists
+                    .values()
+                    .filter(|list| push_ids.contains(&list.id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let push_lists_to_api_effects = if push_lists.is_empty() {
+                    Effects::none().unchanged()
+                } else {
+                    Effects::one(push_lists_to_api::<E>(push_lists, loading_auth_key)).unchanged()
+                };
+                let pull_lists_from_api_effects = if pull_ids.is_empty() {
+                    Effects::none().unchanged()
+                } else {
+                    Effects::one(pull_lists_from_api::<E>(
+                        pull_ids.to_owned(),
+                        false,
+                        loading_auth_key,
+                    ))
+                    .unchanged()
+                };
+                Effects::msg(Msg::Event(Event::ListsSyncWithAPIPlanned {
+                    plan: (pull_ids.to_owned(), push_ids.to_owned()),
+                }))
+                .join(push_lists_to_api_effects)
+                .join(pull_lists_from_api_effects)
+                .unchanged()
+            }
+            Err(error) => Effects::msg(Msg::Event(Event::Error {
+                error: error.to_owned(),
+                source: Box::new(Event::ListsSyncWithAPIPlanned {
+                    plan: Default::default(),
+                }),
+            }))
+            .unchanged(),
+        },
+        Msg::Internal(Internal::ListsPullResult(
+            DatastoreRequest {
+                auth_key: loading_auth_key,
+                command: DatastoreCommand::Get { ids, .. },
+                ..
+            },
+            result,
+        )) if Some(loading_auth_key) == auth_key => match result {
+            Ok(remote_lists) => {
+                let ids = remote_lists.iter().map(|list| &list.id).cloned().collect();
+                lists.merge_lists(remote_lists.to_owned());
+                Effects::msg(Msg::Event(Event::ListsPulledFromAPI { ids }))
+                    .join(Effects::one(push_lists_to_storage::<E>(lists)))
+                    .join(Effects::msg(Msg::Internal(Internal::ListsChanged(true))))
+            }
+            Err(error) => Effects::msg(Msg::Event(Event::Error {
+                error: error.to_owned(),
+                source: Box::new(Event::ListsPulledFromAPI {
+                    ids: ids.to_owned(),
+                }),
+            }))
+            .unchanged(),
+        },
+        _ => Effects::none().unchanged(),
+    }
+}
+
+fn active_list<'a>(lists: &'a ListsBucket, id: &str) -> Option<&'a UserList> {
+    lists.lists.get(id).filter(|list| !list.removed)
+}
+
+fn list_not_found_effects(source: Event) -> Effects {
+    Effects::msg(Msg::Event(Event::Error {
+        error: CtxError::from(OtherError::ListNotFound),
+        source: Box::new(source),
+    }))
+    .unchanged()
+}
+
+fn push_lists_to_storage<E: Env + 'static>(lists: &ListsBucket) -> Effect {
+    let ids = lists.lists.keys().cloned().collect();
+    EffectFuture::Sequential(
+        E::set_storage(LISTS_STORAGE_KEY, Some(lists))
+            .map(move |result| match result {
+                Ok(_) => Msg::Event(Event::ListsPushedToStorage { ids }),
+                Err(error) => Msg::Event(Event::Error {
+                    error: CtxError::from(error),
+                    source: Box::new(Event::ListsPushedToStorage { ids }),
+                }),
+            })
+            .boxed_env(),
+    )
+    .into()
+}
+
+fn push_lists_to_api<E: Env + 'static>(lists: Vec<UserList>, auth_key: &AuthKey) -> Effect {
+    let ids = lists.iter().map(|list| &list.id).cloned().collect();
+    EffectFuture::Concurrent(
+        fetch_api::<E, _, _, SuccessResponse>(&DatastoreRequest {
+            auth_key: auth_key.to_owned(),
+            collection: LISTS_COLLECTION_NAME.to_owned(),
+            command: DatastoreCommand::Put { changes: lists },
+        })
+        .map_err(CtxError::from)
+        .and_then(|result| match result {
+            APIResult::Ok { result } => future::ok(result),
+            APIResult::Err { error } => future::err(CtxError::from(error)),
+        })
+        .map(move |result| match result {
+            Ok(_) => Msg::Event(Event::ListsPushedToAPI { ids }),
+            Err(error) => Msg::Event(Event::Error {
+                error,
+                source: Box::new(Event::ListsPushedToAPI { ids }),
+            }),
+        })
+        .boxed_env(),
+    )
+    .into()
+}
+
+fn pull_lists_from_api<E: Env + 'static>(
+    ids: Vec<String>,
+    all: bool,
+    auth_key: &AuthKey,
+) -> Effect {
+    let request = DatastoreRequest {
+        auth_key: auth_key.to_owned(),
+        collection: LISTS_COLLECTION_NAME.to_owned(),
+        command: DatastoreCommand::Get { ids, all },
+    };
+    EffectFuture::Concurrent(
+        fetch_api::<E, _, _, _>(&request)
+            .map_err(CtxError::from)
+            .and_then(|result| match result {
+                APIResult::Ok { result } => future::ok(result),
+                APIResult::Err { error } => future::err(CtxError::from(error)),
+            })
+            .map(move |result| Msg::Internal(Internal::ListsPullResult(request, result)))
+            .boxed_env(),
+    )
+    .into()
+}
+
+fn plan_sync_with_api<E: Env + 'static>(lists: &ListsBucket, auth_key: &AuthKey) -> Effect {
+    let local_mtimes = lists
+        .lists
+        .iter()
+        .map(|(id, list)| (id.to_owned(), list.mtime.to_owned()))
+        .collect::<HashMap<_, _>>();
+    let request = DatastoreRequest {
+        auth_key: auth_key.to_owned(),
+        collection: LISTS_COLLECTION_NAME.to_owned(),
+        command: DatastoreCommand::Meta {},
+    };
+    EffectFuture::Concurrent(
+        fetch_api::<E, _, _, Vec<LibraryItemModified>>(&request)
+            .map_err(CtxError::from)
+            .and_then(|result| match result {
+                APIResult::Ok { result } => future::ok(result),
+                APIResult::Err { error } => future::err(CtxError::from(error)),
+            })
+            .map_ok(|remote_mtimes| {
+                remote_mtimes
+                    .into_iter()
+                    .map(|LibraryItemModified(id, mtime)| (id, mtime))
+                    .collect::<HashMap<_, _>>()
+            })
+            .map_ok(move |remote_mtimes| {
+                // The newer version of each list is kept on both sides
+                let pull_ids = remote_mtimes
+                    .iter()
+                    .filter(|(id, remote_mtime)| match local_mtimes.get(*id) {
+                        Some(local_mtime) => local_mtime < *remote_mtime,
+                        _ => true,
+                    })
+                    .map(|(id, _)| id)
+                    .cloned()
+                    .collect();
+                let push_ids = local_mtimes
+                    .iter()
+                    .filter(|(id, local_mtime)| match remote_mtimes.get(*id) {
+                        Some(remote_mtime) => remote_mtime < *local_mtime,
+                        _ => true,
+                    })
+                    .map(|(id, _)| id)
+                    .cloned()
+                    .collect();
+                (pull_ids, push_ids)
+            })
+            .map(move |result| Msg::Internal(Internal::ListsSyncPlanResult(request, result)))
+            .boxed_env(),
+    )
+    .into()
+}
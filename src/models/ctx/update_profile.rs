@@ -1,8 +1,9 @@
+use crate::addon_transport::{m3u_playlist_id, m3u_playlist_storage_key, M3UPlaylist};
 use crate::constants::{OFFICIAL_ADDONS, PROFILE_STORAGE_KEY};
 use crate::models::ctx::{CtxError, CtxStatus, OtherError};
 use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
 use crate::runtime::{Effect, EffectFuture, Effects, Env, EnvFutureExt};
-use crate::types::addon::Descriptor;
+use crate::types::addon::{Descriptor, DescriptorFlags};
 use crate::types::api::{fetch_api, APIRequest, APIResult, CollectionResponse, SuccessResponse};
 use crate::types::profile::{AuthKey, Profile, Settings};
 use enclose::enclose;
@@ -15,12 +16,22 @@ pub fn update_profile<E: Env + 'static>(
 ) -> Effects {
     match msg {
         Msg::Action(Action::Ctx(ActionCtx::Logout)) => {
+            let remove_m3u_playlists_effects = Effects::many(
+                profile
+                    .addons
+                    .iter()
+                    .filter_map(|addon| m3u_playlist_id(&addon.transport_url))
+                    .map(remove_m3u_playlist_from_storage::<E>)
+                    .collect(),
+            )
+            .unchanged();
             let next_profile = Profile::default();
             if *profile != next_profile {
                 *profile = next_profile;
                 Effects::msg(Msg::Internal(Internal::ProfileChanged))
+                    .join(remove_m3u_playlists_effects)
             } else {
-                Effects::none().unchanged()
+                remove_m3u_playlists_effects
             }
         }
         Msg::Action(Action::Ctx(ActionCtx::PushUserToAPI)) => {
@@ -191,7 +202,15 @@ pub fn update_profile<E: Env + 'static>(
                 .position(|transport_url| *transport_url == addon.transport_url);
             if let Some(addon_position) = addon_position {
                 if !profile.addons[addon_position].flags.protected {
-                    profile.addons.remove(addon_position);
+                    let removed_addon = profile.addons.remove(addon_position);
+                    let remove_m3u_playlist_effects =
+                        match m3u_playlist_id(&removed_addon.transport_url) {
+                            Some(playlist_id) => {
+                                Effects::one(remove_m3u_playlist_from_storage::<E>(playlist_id))
+                                    .unchanged()
+                            }
+                            _ => Effects::none().unchanged(),
+                        };
                     let push_to_api_effects = match profile.auth_key() {
                         Some(auth_key) => Effects::one(push_addons_to_api::<E>(
                             profile.addons.to_owned(),
@@ -205,6 +224,7 @@ pub fn update_profile<E: Env + 'static>(
                         id: addon.manifest.id.to_owned(),
                     }))
                     .join(push_to_api_effects)
+                    .join(remove_m3u_playlist_effects)
                     .join(Effects::msg(Msg::Internal(Internal::ProfileChanged)))
                 } else {
                     Effects::msg(Msg::Event(Event::Error {
@@ -227,6 +247,20 @@ pub fn update_profile<E: Env + 'static>(
                 .unchanged()
             }
         }
+        Msg::Action(Action::Ctx(ActionCtx::ImportM3UPlaylist { name, content })) => {
+            match M3UPlaylist::parse(name, content) {
+                Ok(playlist) => {
+                    Effects::one(push_m3u_playlist_to_storage::<E>(&playlist)).unchanged()
+                }
+                Err(_) => Effects::msg(Msg::Event(Event::Error {
+                    error: CtxError::from(OtherError::M3UPlaylistInvalid),
+                    source: Box::new(Event::M3UPlaylistImported {
+                        name: name.to_owned(),
+                    }),
+                }))
+                .unchanged(),
+            }
+        }
         Msg::Action(Action::Ctx(ActionCtx::UpdateSettings(settings))) => {
             if profile.settings != *settings {
                 profile.settings = settings.to_owned();
@@ -241,6 +275,87 @@ pub fn update_profile<E: Env + 'static>(
                 .unchanged()
             }
         }
+        Msg::Internal(Internal::RestoreAddon { addon, position }) => {
+            let installed = profile
+                .addons
+                .iter()
+                .any(|installed| installed.transport_url == addon.transport_url);
+            if !installed {
+                let position = (*position).min(profile.addons.len());
+                profile.addons.insert(position, addon.to_owned());
+                let push_to_api_effects = match profile.auth_key() {
+                    Some(auth_key) => {
+                        Effects::one(push_addons_to_api::<E>(profile.addons.to_owned(), auth_key))
+                            .unchanged()
+                    }
+                    _ => Effects::none().unchanged(),
+                };
+                Effects::msg(Msg::Event(Event::AddonRestored {
+                    transport_url: addon.transport_url.to_owned(),
+                    id: addon.manifest.id.to_owned(),
+                }))
+                .join(push_to_api_effects)
+                .join(Effects::msg(Msg::Internal(Internal::ProfileChanged)))
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        Msg::Internal(Internal::UpdateSeriesSubtitles { meta_id, language }) => {
+            let series_overrides = &mut profile.settings.subtitles_preferences.series_overrides;
+            if series_overrides.get(meta_id) != Some(language) {
+                series_overrides.insert(meta_id.to_owned(), language.to_owned());
+                Effects::msg(Msg::Event(Event::SettingsUpdated {
+                    settings: profile.settings.to_owned(),
+                }))
+                .join(Effects::msg(Msg::Internal(Internal::ProfileChanged)))
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        Msg::Internal(Internal::M3UPlaylistStored(addon, result)) => match result {
+            Ok(_) => {
+                let imported_effects = Effects::msg(Msg::Event(Event::M3UPlaylistImported {
+                    name: addon.manifest.name.to_owned(),
+                }))
+                .unchanged();
+                if !profile.addons.contains(addon) {
+                    let addon_position = profile
+                        .addons
+                        .iter()
+                        .map(|addon| &addon.transport_url)
+                        .position(|transport_url| *transport_url == addon.transport_url);
+                    if let Some(addon_position) = addon_position {
+                        profile.addons[addon_position] = addon.to_owned();
+                    } else {
+                        profile.addons.push(addon.to_owned());
+                    };
+                    let push_to_api_effects = match profile.auth_key() {
+                        Some(auth_key) => Effects::one(push_addons_to_api::<E>(
+                            profile.addons.to_owned(),
+                            auth_key,
+                        ))
+                        .unchanged(),
+                        _ => Effects::none().unchanged(),
+                    };
+                    imported_effects
+                        .join(Effects::msg(Msg::Event(Event::AddonInstalled {
+                            transport_url: addon.transport_url.to_owned(),
+                            id: addon.manifest.id.to_owned(),
+                        })))
+                        .join(push_to_api_effects)
+                        .join(Effects::msg(Msg::Internal(Internal::ProfileChanged)))
+                } else {
+                    imported_effects
+                }
+            }
+            Err(error) => Effects::msg(Msg::Event(Event::Error {
+                error: CtxError::from(error.to_owned()),
+                source: Box::new(Event::M3UPlaylistImported {
+                    name: addon.manifest.name.to_owned(),
+                }),
+            }))
+            .unchanged(),
+        },
         Msg::Internal(Internal::ProfileChanged) => {
             Effects::one(push_profile_to_storage::<E>(profile)).unchanged()
         }
@@ -250,7 +365,7 @@ pub fn update_profile<E: Env + 'static>(
             {
                 let next_proifle = Profile {
                     auth: Some(auth.to_owned()),
-                    addons: addons.to_owned(),
+                    addons: with_m3u_addons(addons, &profile.addons),
                     settings: Settings::default(),
                 };
                 if *profile != next_proifle {
@@ -272,8 +387,9 @@ pub fn update_profile<E: Env + 'static>(
                     .map(|addon| &addon.transport_url)
                     .cloned()
                     .collect();
-                if profile.addons != *addons {
-                    profile.addons = addons.to_owned();
+                let next_addons = with_m3u_addons(addons, &profile.addons);
+                if profile.addons != next_addons {
+                    profile.addons = next_addons;
                     Effects::msg(Msg::Event(Event::AddonsPulledFromAPI { transport_urls }))
                         .join(Effects::msg(Msg::Internal(Internal::ProfileChanged)))
                 } else {
@@ -293,7 +409,26 @@ pub fn update_profile<E: Env + 'static>(
     }
 }
 
+/// Imported M3U playlists live only in the local storage, so their addons are kept
+/// out of the API collection and carried over when it replaces the local one.
+fn with_m3u_addons(addons: &[Descriptor], local_addons: &[Descriptor]) -> Vec<Descriptor> {
+    addons
+        .iter()
+        .filter(|addon| m3u_playlist_id(&addon.transport_url).is_none())
+        .chain(
+            local_addons
+                .iter()
+                .filter(|addon| m3u_playlist_id(&addon.transport_url).is_some()),
+        )
+        .cloned()
+        .collect()
+}
+
 fn push_addons_to_api<E: Env + 'static>(addons: Vec<Descriptor>, auth_key: &AuthKey) -> Effect {
+    let addons = addons
+        .into_iter()
+        .filter(|addon| m3u_playlist_id(&addon.transport_url).is_none())
+        .collect::<Vec<_>>();
     let transport_urls = addons
         .iter()
         .map(|addon| &addon.transport_url)
@@ -355,3 +490,33 @@ fn push_profile_to_storage<E: Env + 'static>(profile: &Profile) -> Effect {
     )
     .into()
 }
+
+fn push_m3u_playlist_to_storage<E: Env + 'static>(playlist: &M3UPlaylist) -> Effect {
+    let addon = Descriptor {
+        manifest: playlist.manifest(),
+        transport_url: playlist.transport_url(),
+        flags: DescriptorFlags::default(),
+    };
+    EffectFuture::Sequential(
+        E::set_storage(&m3u_playlist_storage_key(&playlist.id), Some(playlist))
+            .map(move |result| Msg::Internal(Internal::M3UPlaylistStored(addon, result)))
+            .boxed_env(),
+    )
+    .into()
+}
+
+fn remove_m3u_playlist_from_storage<E: Env + 'static>(playlist_id: &str) -> Effect {
+    let id = playlist_id.to_owned();
+    EffectFuture::Sequential(
+        E::set_storage::<()>(&m3u_playlist_storage_key(&id), None)
+            .map(move |result| match result {
+                Ok(_) => Msg::Event(Event::M3UPlaylistRemovedFromStorage { id }),
+                Err(error) => Msg::Event(Event::Error {
+                    error: CtxError::from(error),
+                    source: Box::new(Event::M3UPlaylistRemovedFromStorage { id }),
+                }),
+            })
+            .boxed_env(),
+    )
+    .into()
+}
@@ -0,0 +1,80 @@
+use crate::addon_transport::m3u_playlist_id;
+use crate::models::common::eq_update;
+use crate::models::ctx::CtxStatus;
+use crate::runtime::msg::{Action, ActionCtx, Event, Internal, Msg};
+use crate::runtime::{Effects, Env};
+use crate::types::library::LibraryBucket;
+use crate::types::profile::Profile;
+use crate::types::undo::{UndoSnapshot, UndoStack};
+
+/// Must run before `update_library` and `update_profile` so the snapshots
+/// capture the state from before the destructive action.
+pub fn update_undo<E: Env + 'static>(
+    undo: &mut UndoStack,
+    profile: &Profile,
+    library: &LibraryBucket,
+    status: &CtxStatus,
+    msg: &Msg,
+) -> Effects {
+    match msg {
+        Msg::Action(Action::Ctx(ActionCtx::Logout)) => eq_update(undo, UndoStack::default()),
+        Msg::Action(Action::Ctx(ActionCtx::RemoveFromLibrary(id)))
+        | Msg::Action(Action::Ctx(ActionCtx::RewindLibraryItem(id))) => {
+            match library.items.get(id) {
+                Some(library_item) => {
+                    undo.push::<E>(UndoSnapshot::LibraryItem {
+                        library_item: library_item.to_owned(),
+                    });
+                    Effects::none()
+                }
+                _ => Effects::none().unchanged(),
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::UninstallAddon(addon))) => {
+            let position = profile
+                .addons
+                .iter()
+                .position(|installed| installed.transport_url == addon.transport_url);
+            match position {
+                // imported playlists are deleted from the storage on uninstall, so they can't be restored
+                Some(position)
+                    if !profile.addons[position].flags.protected
+                        && m3u_playlist_id(&profile.addons[position].transport_url).is_none() =>
+                {
+                    undo.push::<E>(UndoSnapshot::Addon {
+                        addon: profile.addons[position].to_owned(),
+                        position,
+                    });
+                    Effects::none()
+                }
+                _ => Effects::none().unchanged(),
+            }
+        }
+        Msg::Action(Action::Ctx(ActionCtx::Undo)) => {
+            let entries_len = undo.entries.len();
+            match undo.pop::<E>() {
+                Some(UndoSnapshot::LibraryItem { library_item }) => {
+                    Effects::msg(Msg::Event(Event::LibraryItemRestored {
+                        id: library_item.id.to_owned(),
+                    }))
+                    .join(Effects::msg(Msg::Internal(
+                        Internal::UpdateLibraryItem(library_item),
+                    )))
+                }
+                Some(UndoSnapshot::Addon { addon, position }) => {
+                    Effects::msg(Msg::Internal(Internal::RestoreAddon { addon, position }))
+                }
+                // expired entries may have been dropped
+                None if undo.entries.len() != entries_len => Effects::none(),
+                None => Effects::none().unchanged(),
+            }
+        }
+        Msg::Internal(Internal::CtxAuthResult(auth_request, Ok(_))) => match status {
+            CtxStatus::Loading(loading_auth_request) if loading_auth_request == auth_request => {
+                eq_update(undo, UndoStack::default())
+            }
+            _ => Effects::none().unchanged(),
+        },
+        _ => Effects::none().unchanged(),
+    }
+}
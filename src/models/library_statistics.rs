@@ -0,0 +1,237 @@
+use crate::constants::{LIBRARY_STATISTICS_TOP_GENRES_COUNT, TYPE_PRIORITIES};
+use crate::models::common::{compare_with_priorities, eq_update};
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Internal, Msg};
+use crate::runtime::{Effects, Env, UpdateWithCtx};
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::types::library::{LibraryBucket, LibraryItem};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use derivative::Derivative;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use stremio_watched_bitfield::WatchedBitField;
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct TypeStatistics {
+    pub r#type: String,
+    pub time_watched: u64,
+    pub titles: usize,
+    pub completed: usize,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPeriod {
+    pub start: DateTime<Utc>,
+    pub titles: usize,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct GenreStatistics {
+    pub genre: String,
+    pub titles: usize,
+}
+
+/// The contribution of a single library item, recomputed only when its mtime changes
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+struct LibraryItemStatistics {
+    mtime: DateTime<Utc>,
+    r#type: String,
+    time_watched: u64,
+    completed: bool,
+    watched_episodes: usize,
+    genres: Vec<String>,
+}
+
+impl LibraryItemStatistics {
+    fn new(library_item: &LibraryItem) -> Option<Self> {
+        let completed =
+            library_item.state.flagged_watched > 0 || library_item.state.times_watched > 0;
+        let watched = completed
+            || library_item.state.overall_time_watched > 0
+            || library_item.state.last_watched.is_some();
+        if library_item.r#type == "other" || !watched {
+            return None;
+        }
+        Some(LibraryItemStatistics {
+            mtime: library_item.mtime,
+            r#type: library_item.r#type.to_owned(),
+            time_watched: library_item.state.overall_time_watched,
+            completed,
+            watched_episodes: library_item
+                .state
+                .watched
+                .as_ref()
+                .and_then(|watched| WatchedBitField::count_serialized(watched).ok())
+                .unwrap_or_default(),
+            genres: library_item.genres.to_owned(),
+        })
+    }
+}
+
+/// Watching statistics of the titles in the library which have been watched.
+///
+/// Times are in milliseconds. Activity counts the titles played in each UTC week, starting on monday,
+/// and month of the watch history, and streaks count consecutive days with a play session.
+#[derive(Derivative, Default, Serialize)]
+#[derivative(PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryStatistics {
+    pub time_watched: u64,
+    pub types: Vec<TypeStatistics>,
+    pub titles: usize,
+    pub completed: usize,
+    pub watched_episodes: usize,
+    pub weekly_activity: Vec<ActivityPeriod>,
+    pub monthly_activity: Vec<ActivityPeriod>,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub top_genres: Vec<GenreStatistics>,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    items: HashMap<String, LibraryItemStatistics>,
+}
+
+impl LibraryStatistics {
+    pub fn new<E: Env + 'static>(
+        library: &LibraryBucket,
+        history: &HistoryBucket,
+    ) -> (Self, Effects) {
+        let mut statistics = LibraryStatistics::default();
+        let effects = statistics_update::<E>(&mut statistics, library, history);
+        (statistics, effects.unchanged())
+    }
+}
+
+impl<E: Env + 'static> UpdateWithCtx<E> for LibraryStatistics {
+    fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
+        match msg {
+            Msg::Internal(Internal::LibraryChanged(_))
+            | Msg::Internal(Internal::HistoryChanged(_)) => {
+                statistics_update::<E>(self, &ctx.library, &ctx.history)
+            }
+            _ => Effects::none().unchanged(),
+        }
+    }
+}
+
+fn statistics_update<E: Env + 'static>(
+    statistics: &mut LibraryStatistics,
+    library: &LibraryBucket,
+    history: &HistoryBucket,
+) -> Effects {
+    let mut items = std::mem::take(&mut statistics.items);
+    items.retain(|id, _| library.items.contains_key(id));
+    for library_item in library.items.values() {
+        let is_outdated = match items.get(&library_item.id) {
+            Some(item) => item.mtime != library_item.mtime,
+            _ => true,
+        };
+        if is_outdated {
+            match LibraryItemStatistics::new(library_item) {
+                Some(item) => items.insert(library_item.id.to_owned(), item),
+                _ => items.remove(&library_item.id),
+            };
+        };
+    }
+    let next_statistics = aggregate_statistics(&items, &history.entries, E::now().date_naive());
+    let effects = eq_update(statistics, next_statistics);
+    statistics.items = items;
+    effects
+}
+
+fn aggregate_statistics(
+    items: &HashMap<String, LibraryItemStatistics>,
+    history: &[HistoryEntry],
+    today: NaiveDate,
+) -> LibraryStatistics {
+    let types = items
+        .values()
+        .into_group_map_by(|item| item.r#type.as_str())
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| compare_with_priorities(*a, *b, &*TYPE_PRIORITIES))
+        .rev()
+        .map(|(r#type, items)| TypeStatistics {
+            r#type: r#type.to_owned(),
+            time_watched: items.iter().map(|item| item.time_watched).sum(),
+            titles: items.len(),
+            completed: items.iter().filter(|item| item.completed).count(),
+        })
+        .collect::<Vec<_>>();
+    let activity = |period_start: fn(NaiveDate) -> NaiveDate| {
+        history
+            .iter()
+            .map(|entry| (period_start(entry.start.date_naive()), entry.id.as_str()))
+            .unique()
+            .map(|(start, _)| start)
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|(start, titles)| ActivityPeriod {
+                start: Utc
+                    .from_utc_datetime(&start.and_hms_opt(0, 0, 0).expect("midnight is valid")),
+                titles,
+            })
+            .collect::<Vec<_>>()
+    };
+    let days = history
+        .iter()
+        .map(|entry| entry.start.date_naive())
+        .unique()
+        .sorted()
+        .collect::<Vec<_>>();
+    let streaks = days
+        .iter()
+        .enumerate()
+        .group_by(|(index, day)| **day - Duration::days(*index as i64))
+        .into_iter()
+        .map(|(_, streak)| {
+            let streak = streak.collect::<Vec<_>>();
+            (*streak[streak.len() - 1].1, streak.len() as u32)
+        })
+        .collect::<Vec<_>>();
+    LibraryStatistics {
+        time_watched: types.iter().map(|r#type| r#type.time_watched).sum(),
+        titles: types.iter().map(|r#type| r#type.titles).sum(),
+        completed: types.iter().map(|r#type| r#type.completed).sum(),
+        types,
+        watched_episodes: items.values().map(|item| item.watched_episodes).sum(),
+        weekly_activity: activity(|date| {
+            date - Duration::days(date.weekday().num_days_from_monday() as i64)
+        }),
+        monthly_activity: activity(|date| date.with_day(1).expect("first day is valid")),
+        current_streak: streaks
+            .last()
+            .filter(|(last_day, _)| today - *last_day <= Duration::days(1))
+            .map(|(_, length)| *length)
+            .unwrap_or_default(),
+        longest_streak: streaks
+            .iter()
+            .map(|(_, length)| *length)
+            .max()
+            .unwrap_or_default(),
+        top_genres: items
+            .values()
+            .flat_map(|item| item.genres.iter())
+            .counts()
+            .into_iter()
+            .sorted_by(|(a_genre, a_titles), (b_genre, b_titles)| {
+                b_titles.cmp(a_titles).then_with(|| a_genre.cmp(b_genre))
+            })
+            .take(LIBRARY_STATISTICS_TOP_GENRES_COUNT)
+            .map(|(genre, titles)| GenreStatistics {
+                genre: genre.to_owned(),
+                titles,
+            })
+            .collect(),
+        items: HashMap::new(),
+    }
+}
@@ -9,11 +9,14 @@ use derivative::Derivative;
 use derive_more::Deref;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::iter;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
 pub trait LibraryFilter {
     fn predicate(library_item: &LibraryItem) -> bool;
@@ -43,6 +46,46 @@ pub enum Sort {
     LastWatched,
     Name,
     TimesWatched,
+    DateAdded,
+    Progress,
+    Released,
+    RecentlyUpdated,
+}
+
+impl Sort {
+    fn compare(&self, a: &LibraryItem, b: &LibraryItem) -> Ordering {
+        match self {
+            Sort::LastWatched => b.state.last_watched.cmp(&a.state.last_watched),
+            Sort::TimesWatched => b.state.times_watched.cmp(&a.state.times_watched),
+            Sort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Sort::DateAdded => b.ctime.cmp(&a.ctime),
+            Sort::Progress => progress(b)
+                .partial_cmp(&progress(a))
+                .unwrap_or(Ordering::Equal),
+            Sort::Released => b.released.cmp(&a.released),
+            Sort::RecentlyUpdated => b.state.last_vid_released.cmp(&a.state.last_vid_released),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Watched,
+    Unwatched,
+    InProgress,
+}
+
+impl Status {
+    fn predicate(&self, library_item: &LibraryItem) -> bool {
+        let watched =
+            library_item.state.flagged_watched > 0 || library_item.state.times_watched > 0;
+        match self {
+            Status::Watched => watched,
+            Status::Unwatched => !watched && library_item.state.time_offset == 0,
+            Status::InProgress => !watched && library_item.state.time_offset > 0,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -52,6 +95,33 @@ pub struct LibraryRequest {
     pub sort: Sort,
     #[serde(default)]
     pub page: LibraryRequestPage,
+    /// Matched against the names regardless of case and diacritics
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub status: Option<Status>,
+}
+
+impl LibraryRequest {
+    // The search is folded once per update instead of once per item
+    fn folded_search(&self) -> Option<String> {
+        self.search.as_ref().map(|search| fold_text(search.trim()))
+    }
+    fn predicate(&self, library_item: &LibraryItem, folded_search: Option<&str>) -> bool {
+        let type_matches = match &self.r#type {
+            Some(r#type) => library_item.r#type == *r#type,
+            None => true,
+        };
+        let search_matches = match folded_search {
+            Some(search) => fold_text(&library_item.name).contains(search),
+            None => true,
+        };
+        let status_matches = match &self.status {
+            Some(status) => status.predicate(library_item),
+            None => true,
+        };
+        type_matches && search_matches && status_matches
+    }
 }
 
 #[derive(Clone, Deref, PartialEq, Serialize, Deserialize)]
@@ -82,6 +152,13 @@ pub struct SelectableSort {
     pub request: LibraryRequest,
 }
 
+#[derive(PartialEq, Serialize)]
+pub struct SelectableStatus {
+    pub status: Option<Status>,
+    pub selected: bool,
+    pub request: LibraryRequest,
+}
+
 #[derive(PartialEq, Serialize)]
 pub struct SelectablePage {
     pub request: LibraryRequest,
@@ -91,6 +168,7 @@ pub struct SelectablePage {
 pub struct Selectable {
     pub types: Vec<SelectableType>,
     pub sorts: Vec<SelectableSort>,
+    pub statuses: Vec<SelectableStatus>,
     pub prev_page: Option<SelectablePage>,
     pub next_page: Option<SelectablePage>,
 }
@@ -161,6 +239,17 @@ fn selectable_update<F: LibraryFilter>(
     selected: &Option<Selected>,
     library: &LibraryBucket,
 ) -> Effects {
+    let selected_request = LibraryRequest {
+        r#type: None,
+        sort: Sort::default(),
+        page: LibraryRequestPage::default(),
+        search: None,
+        status: None,
+    };
+    let selected_request = selected
+        .as_ref()
+        .map(|selected| &selected.request)
+        .unwrap_or(&selected_request);
     let selectable_types = library
         .items
         .values()
@@ -175,11 +264,8 @@ fn selectable_update<F: LibraryFilter>(
             r#type: r#type.to_owned(),
             request: LibraryRequest {
                 r#type: r#type.to_owned(),
-                sort: selected
-                    .as_ref()
-                    .map(|selected| selected.request.sort.to_owned())
-                    .unwrap_or_default(),
                 page: LibraryRequestPage::default(),
+                ..selected_request.to_owned()
             },
             selected: selected
                 .as_ref()
@@ -190,11 +276,8 @@ fn selectable_update<F: LibraryFilter>(
         r#type: None,
         request: LibraryRequest {
             r#type: None,
-            sort: selected
-                .as_ref()
-                .map(|selected| selected.request.sort.to_owned())
-                .unwrap_or_default(),
             page: LibraryRequestPage::default(),
+            ..selected_request.to_owned()
         },
         selected: selected
             .as_ref()
@@ -207,11 +290,9 @@ fn selectable_update<F: LibraryFilter>(
         .map(|sort| SelectableSort {
             sort: sort.to_owned(),
             request: LibraryRequest {
-                r#type: selected
-                    .as_ref()
-                    .and_then(|selected| selected.request.r#type.to_owned()),
                 sort: sort.to_owned(),
                 page: LibraryRequestPage::default(),
+                ..selected_request.to_owned()
             },
             selected: selected
                 .as_ref()
@@ -219,6 +300,21 @@ fn selectable_update<F: LibraryFilter>(
                 .unwrap_or_default(),
         })
         .collect();
+    let selectable_statuses = iter::once(None)
+        .chain(Status::iter().map(Some))
+        .map(|status| SelectableStatus {
+            status: status.to_owned(),
+            request: LibraryRequest {
+                status: status.to_owned(),
+                page: LibraryRequestPage::default(),
+                ..selected_request.to_owned()
+            },
+            selected: selected
+                .as_ref()
+                .map(|selected| selected.request.status == status)
+                .unwrap_or_default(),
+        })
+        .collect();
     let (prev_page, next_page) = match selected {
         Some(selected) => {
             let prev_page = (selected.request.page.get() > 1)
@@ -231,13 +327,15 @@ fn selectable_update<F: LibraryFilter>(
                         ..selected.request.to_owned()
                     },
                 });
+            let folded_search = selected.request.folded_search();
             let next_page = library
                 .items
                 .values()
                 .filter(|library_item| F::predicate(library_item))
-                .filter(|library_item| match &selected.request.r#type {
-                    Some(r#type) => library_item.r#type == *r#type,
-                    None => true,
+                .filter(|library_item| {
+                    selected
+                        .request
+                        .predicate(library_item, folded_search.as_deref())
                 })
                 .nth(selected.request.page.get() * CATALOG_PAGE_SIZE)
                 .map(|_| SelectablePage {
@@ -255,6 +353,7 @@ fn selectable_update<F: LibraryFilter>(
     let next_selectable = Selectable {
         types: selectable_types,
         sorts: selectable_sorts,
+        statuses: selectable_statuses,
         prev_page,
         next_page,
     };
@@ -267,24 +366,47 @@ fn catalog_update<F: LibraryFilter>(
     library: &LibraryBucket,
 ) -> Effects {
     let next_catalog = match selected {
-        Some(selected) => library
-            .items
-            .values()
-            .filter(|library_item| F::predicate(library_item))
-            .filter(|library_item| match &selected.request.r#type {
-                Some(r#type) => library_item.r#type == *r#type,
-                None => true,
-            })
-            .sorted_by(|a, b| match &selected.request.sort {
-                Sort::LastWatched => b.state.last_watched.cmp(&a.state.last_watched),
-                Sort::TimesWatched => b.state.times_watched.cmp(&a.state.times_watched),
-                Sort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-            })
-            .skip((selected.request.page.get() - 1) * CATALOG_PAGE_SIZE)
-            .take(CATALOG_PAGE_SIZE)
-            .cloned()
-            .collect(),
+        Some(selected) => {
+            let folded_search = selected.request.folded_search();
+            library
+                .items
+                .values()
+                .filter(|library_item| F::predicate(library_item))
+                .filter(|library_item| {
+                    selected
+                        .request
+                        .predicate(library_item, folded_search.as_deref())
+                })
+                .sorted_by(|a, b| {
+                    // Ties are ordered by id so that the pages are stable
+                    selected
+                        .request
+                        .sort
+                        .compare(a, b)
+                        .then_with(|| a.id.cmp(&b.id))
+                })
+                .skip((selected.request.page.get() - 1) * CATALOG_PAGE_SIZE)
+                .take(CATALOG_PAGE_SIZE)
+                .cloned()
+                .collect()
+        }
         _ => vec![],
     };
     eq_update(catalog, next_catalog)
 }
+
+fn progress(library_item: &LibraryItem) -> f64 {
+    if library_item.state.duration > 0 {
+        library_item.state.time_offset as f64 / library_item.state.duration as f64
+    } else {
+        0.0
+    }
+}
+
+/// Lowercases the text and strips the diacritics
+fn fold_text(text: &str) -> String {
+    text.nfd()
+        .filter(|char| !is_combining_mark(*char))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
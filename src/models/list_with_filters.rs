@@ -0,0 +1,270 @@
+use crate::constants::{CATALOG_PAGE_SIZE, TYPE_PRIORITIES};
+use crate::models::common::{compare_with_priorities, eq_update};
+use crate::models::ctx::Ctx;
+use crate::models::library_with_filters::LibraryRequestPage;
+use crate::runtime::msg::{Action, ActionLoad, Internal, Msg};
+use crate::runtime::{Effects, Env, UpdateWithCtx};
+use crate::types::lists::{ListsBucket, UserListItem};
+use boolinator::Boolinator;
+use derivative::Derivative;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::iter;
+use std::num::NonZeroUsize;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Derivative, Clone, PartialEq, EnumIter, Serialize, Deserialize)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    #[derivative(Default)]
+    Added,
+    Name,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListRequest {
+    pub id: String,
+    pub r#type: Option<String>,
+    #[serde(default)]
+    pub sort: Sort,
+    #[serde(default)]
+    pub page: LibraryRequestPage,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selected {
+    pub request: ListRequest,
+}
+
+#[derive(PartialEq, Serialize)]
+pub struct SelectableList {
+    pub id: String,
+    pub name: String,
+    pub selected: bool,
+    pub request: ListRequest,
+}
+
+#[derive(PartialEq, Serialize)]
+pub struct SelectableType {
+    pub r#type: Option<String>,
+    pub selected: bool,
+    pub request: ListRequest,
+}
+
+#[derive(PartialEq, Serialize)]
+pub struct SelectableSort {
+    pub sort: Sort,
+    pub selected: bool,
+    pub request: ListRequest,
+}
+
+#[derive(PartialEq, Serialize)]
+pub struct SelectablePage {
+    pub request: ListRequest,
+}
+
+#[derive(Default, PartialEq, Serialize)]
+pub struct Selectable {
+    pub lists: Vec<SelectableList>,
+    pub types: Vec<SelectableType>,
+    pub sorts: Vec<SelectableSort>,
+    pub prev_page: Option<SelectablePage>,
+    pub next_page: Option<SelectablePage>,
+}
+
+/// The items of a user list, filtered and sorted like the library.
+#[derive(Default, Serialize)]
+pub struct ListWithFilters {
+    pub selected: Option<Selected>,
+    pub selectable: Selectable,
+    pub catalog: Vec<UserListItem>,
+}
+
+impl ListWithFilters {
+    pub fn new(lists: &ListsBucket) -> (Self, Effects) {
+        let selected = None;
+        let mut selectable = Selectable::default();
+        let effects = selectable_update(&mut selectable, &selected, lists);
+        (
+            Self {
+                selectable,
+                selected,
+                ..Self::default()
+            },
+            effects.unchanged(),
+        )
+    }
+}
+
+impl<E: Env + 'static> UpdateWithCtx<E> for ListWithFilters {
+    fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
+        match msg {
+            Msg::Action(Action::Load(ActionLoad::ListWithFilters(selected))) => {
+                let selected_effects = eq_update(&mut self.selected, Some(selected.to_owned()));
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.lists);
+                let catalog_effects = catalog_update(&mut self.catalog, &self.selected, &ctx.lists);
+                selected_effects
+                    .join(selectable_effects)
+                    .join(catalog_effects)
+            }
+            Msg::Action(Action::Unload) => {
+                let selected_effects = eq_update(&mut self.selected, None);
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.lists);
+                let catalog_effects = catalog_update(&mut self.catalog, &self.selected, &ctx.lists);
+                selected_effects
+                    .join(selectable_effects)
+                    .join(catalog_effects)
+            }
+            Msg::Internal(Internal::ListsChanged(_)) => {
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.lists);
+                let catalog_effects = catalog_update(&mut self.catalog, &self.selected, &ctx.lists);
+                selectable_effects.join(catalog_effects)
+            }
+            _ => Effects::none().unchanged(),
+        }
+    }
+}
+
+fn list_items<'a>(
+    lists: &'a ListsBucket,
+    request: &'a ListRequest,
+) -> impl Iterator<Item = &'a UserListItem> + 'a {
+    lists
+        .lists
+        .get(&request.id)
+        .filter(|list| !list.removed)
+        .into_iter()
+        .flat_map(|list| list.items.iter())
+        .filter(move |item| match &request.r#type {
+            Some(r#type) => item.r#type == *r#type,
+            None => true,
+        })
+}
+
+fn selectable_update(
+    selectable: &mut Selectable,
+    selected: &Option<Selected>,
+    lists: &ListsBucket,
+) -> Effects {
+    let selectable_lists = lists
+        .active_lists()
+        .into_iter()
+        .map(|list| SelectableList {
+            id: list.id.to_owned(),
+            name: list.name.to_owned(),
+            selected: selected
+                .as_ref()
+                .map(|selected| selected.request.id == list.id)
+                .unwrap_or_default(),
+            request: ListRequest {
+                id: list.id.to_owned(),
+                r#type: None,
+                sort: selected
+                    .as_ref()
+                    .map(|selected| selected.request.sort.to_owned())
+                    .unwrap_or_default(),
+                page: LibraryRequestPage::default(),
+            },
+        })
+        .collect();
+    let (types, sorts, prev_page, next_page) = match selected {
+        Some(selected) => {
+            let list = lists
+                .lists
+                .get(&selected.request.id)
+                .filter(|list| !list.removed);
+            let selectable_types = list
+                .into_iter()
+                .flat_map(|list| list.items.iter())
+                .map(|item| &item.r#type)
+                .unique()
+                .sorted_by(|a, b| {
+                    compare_with_priorities(a.as_str(), b.as_str(), &*TYPE_PRIORITIES)
+                })
+                .rev()
+                .cloned()
+                .map(Some);
+            let selectable_types = iter::once(None)
+                .chain(selectable_types)
+                .map(|r#type| SelectableType {
+                    r#type: r#type.to_owned(),
+                    selected: selected.request.r#type == r#type,
+                    request: ListRequest {
+                        r#type,
+                        page: LibraryRequestPage::default(),
+                        ..selected.request.to_owned()
+                    },
+                })
+                .collect();
+            let selectable_sorts = Sort::iter()
+                .map(|sort| SelectableSort {
+                    sort: sort.to_owned(),
+                    selected: selected.request.sort == sort,
+                    request: ListRequest {
+                        sort,
+                        page: LibraryRequestPage::default(),
+                        ..selected.request.to_owned()
+                    },
+                })
+                .collect();
+            let prev_page = (selected.request.page.get() > 1)
+                .as_option()
+                .map(|_| SelectablePage {
+                    request: ListRequest {
+                        page: LibraryRequestPage(
+                            NonZeroUsize::new(selected.request.page.get() - 1).unwrap(),
+                        ),
+                        ..selected.request.to_owned()
+                    },
+                });
+            let next_page = list_items(lists, &selected.request)
+                .nth(selected.request.page.get() * CATALOG_PAGE_SIZE)
+                .map(|_| SelectablePage {
+                    request: ListRequest {
+                        page: LibraryRequestPage(
+                            NonZeroUsize::new(selected.request.page.get() + 1).unwrap(),
+                        ),
+                        ..selected.request.to_owned()
+                    },
+                });
+            (selectable_types, selectable_sorts, prev_page, next_page)
+        }
+        _ => Default::default(),
+    };
+    let next_selectable = Selectable {
+        lists: selectable_lists,
+        types,
+        sorts,
+        prev_page,
+        next_page,
+    };
+    eq_update(selectable, next_selectable)
+}
+
+fn catalog_update(
+    catalog: &mut Vec<UserListItem>,
+    selected: &Option<Selected>,
+    lists: &ListsBucket,
+) -> Effects {
+    let next_catalog = match selected {
+        Some(selected) => list_items(lists, &selected.request)
+            .sorted_by(|a, b| {
+                match &selected.request.sort {
+                    Sort::Added => b.added.cmp(&a.added),
+                    Sort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                }
+                .then_with(|| a.id.cmp(&b.id))
+            })
+            .skip((selected.request.page.get() - 1) * CATALOG_PAGE_SIZE)
+            .take(CATALOG_PAGE_SIZE)
+            .cloned()
+            .collect(),
+        _ => vec![],
+    };
+    eq_update(catalog, next_catalog)
+}
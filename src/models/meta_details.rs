@@ -1,18 +1,26 @@
+use crate::addon_transport::AddonRequestPriority;
 use crate::constants::{META_RESOURCE_NAME, STREAM_RESOURCE_NAME};
 use crate::models::common::{
-    eq_update, resources_update, resources_update_with_vector_content, Loadable, ResourceLoadable,
-    ResourcesAction,
+    eq_update, resource_update, resources_update, resources_update_with_vector_content, Loadable,
+    ResourceAction, ResourceLoadable, ResourcesAction,
 };
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::{Action, ActionLoad, ActionMetaDetails, Internal, Msg};
 use crate::runtime::{Effects, Env, UpdateWithCtx};
 use crate::types::addon::{AggrRequest, ResourcePath, ResourceRequest};
+use crate::types::is_same_language;
 use crate::types::library::{LibraryBucket, LibraryItem};
-use crate::types::resource::{MetaItem, Stream};
+use crate::types::profile::{StreamDelivery, StreamPreferences};
+use crate::types::resource::{MetaItem, Stream, StreamInfo, StreamSource, Video};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use stremio_watched_bitfield::WatchedBitField;
+use url::Url;
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -21,15 +29,32 @@ pub struct Selected {
     pub stream_path: Option<ResourcePath>,
 }
 
+/// A stream which may be returned by several addons
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct RankedStream {
+    /// The stream of the first addon, with the subtitles, trackers and hints of the rest merged in
+    pub stream: Stream,
+    pub info: StreamInfo,
+    /// Transport urls of all addons which returned the stream
+    pub addons: Vec<Url>,
+}
+
 #[derive(Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetaDetails {
     pub selected: Option<Selected>,
     pub meta_items: Vec<ResourceLoadable<MetaItem>>,
     pub streams: Vec<ResourceLoadable<Vec<Stream>>>,
+    /// The streams of all addons, de-duplicated and ranked by the stream preferences
+    pub ranked_streams: Vec<RankedStream>,
     pub library_item: Option<LibraryItem>,
     #[serde(skip_serializing)]
     pub watched: Option<WatchedBitField>,
+    /// The meta path which was redirected, as only one redirect is followed per load
+    #[serde(skip_serializing)]
+    pub redirected_meta_path: Option<ResourcePath>,
 }
 
 impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
@@ -37,11 +62,13 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
         match msg {
             Msg::Action(Action::Load(ActionLoad::MetaDetails(selected))) => {
                 let selected_effects = eq_update(&mut self.selected, Some(selected.to_owned()));
+                self.redirected_meta_path = None;
                 let meta_items_effects = resources_update::<E, _>(
                     &mut self.meta_items,
                     ResourcesAction::ResourcesRequested {
                         request: &AggrRequest::AllOfResource(selected.meta_path.to_owned()),
                         addons: &ctx.profile.addons,
+                        priority: AddonRequestPriority::High,
                     },
                 );
                 let streams_effects = match &selected.stream_path {
@@ -56,12 +83,18 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                                 ResourcesAction::ResourcesRequested {
                                     request: &AggrRequest::AllOfResource(stream_path.to_owned()),
                                     addons: &ctx.profile.addons,
+                                    priority: AddonRequestPriority::High,
                                 },
                             )
                         }
                     }
                     None => eq_update(&mut self.streams, vec![]),
                 };
+                let ranked_streams_effects = ranked_streams_update(
+                    &mut self.ranked_streams,
+                    &self.streams,
+                    &ctx.profile.settings.stream_preferences,
+                );
                 let library_item_effects = library_item_update::<E>(
                     &mut self.library_item,
                     &self.selected,
@@ -73,6 +106,7 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                 selected_effects
                     .join(meta_items_effects)
                     .join(streams_effects)
+                    .join(ranked_streams_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
             }
@@ -80,11 +114,14 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                 let selected_effects = eq_update(&mut self.selected, None);
                 let meta_items_effects = eq_update(&mut self.meta_items, vec![]);
                 let streams_effects = eq_update(&mut self.streams, vec![]);
+                let ranked_streams_effects = eq_update(&mut self.ranked_streams, vec![]);
                 let library_item_effects = eq_update(&mut self.library_item, None);
                 let watched_effects = eq_update(&mut self.watched, None);
+                self.redirected_meta_path = None;
                 selected_effects
                     .join(meta_items_effects)
                     .join(streams_effects)
+                    .join(ranked_streams_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
             }
@@ -102,6 +139,56 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                 }
                 _ => Effects::none().unchanged(),
             },
+            Msg::Action(Action::MetaDetails(ActionMetaDetails::MarkSeasonAsWatched(
+                season,
+                is_watched,
+            ))) => mark_videos_as_watched_effects(
+                &self.meta_items,
+                &self.library_item,
+                &self.watched,
+                |video| match &video.series_info {
+                    Some(series_info) => series_info.season == *season,
+                    _ => false,
+                },
+                *is_watched,
+            ),
+            Msg::Action(Action::MetaDetails(ActionMetaDetails::MarkAsWatchedUntil(
+                video_id,
+                is_watched,
+            ))) => {
+                let last_video = ready_meta_item(&self.meta_items)
+                    .and_then(|meta_item| {
+                        meta_item.videos.iter().find(|video| video.id == *video_id)
+                    })
+                    .cloned();
+                match last_video {
+                    Some(last_video) => mark_videos_as_watched_effects(
+                        &self.meta_items,
+                        &self.library_item,
+                        &self.watched,
+                        |video| match (&video.series_info, &last_video.series_info) {
+                            // Specials are marked only up to another special
+                            (Some(series_info), Some(last_series_info)) => {
+                                (series_info.season != 0 || last_series_info.season == 0)
+                                    && (series_info.season, series_info.episode)
+                                        <= (last_series_info.season, last_series_info.episode)
+                            }
+                            _ => video.id == last_video.id,
+                        },
+                        *is_watched,
+                    ),
+                    _ => Effects::none().unchanged(),
+                }
+            }
+            Msg::Action(Action::MetaDetails(ActionMetaDetails::MarkSeriesAsWatched(
+                is_watched,
+            ))) => mark_videos_as_watched_effects(
+                &self.meta_items,
+                &self.library_item,
+                &self.watched,
+                |_| true,
+                *is_watched,
+            ),
             Msg::Internal(Internal::ResourceRequestResult(request, result))
                 if request.path.resource == META_RESOURCE_NAME =>
             {
@@ -109,6 +196,12 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                     &mut self.meta_items,
                     ResourcesAction::ResourceRequestResult { request, result },
                 );
+                let redirect_effects = meta_redirect_update::<E>(
+                    &mut self.meta_items,
+                    &mut self.selected,
+                    &mut self.redirected_meta_path,
+                    request,
+                );
                 let streams_effects = match &self.selected {
                     Some(Selected {
                         stream_path: Some(stream_path),
@@ -124,6 +217,11 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                     }
                     _ => Effects::none().unchanged(),
                 };
+                let ranked_streams_effects = ranked_streams_update(
+                    &mut self.ranked_streams,
+                    &self.streams,
+                    &ctx.profile.settings.stream_preferences,
+                );
                 let library_item_effects = library_item_update::<E>(
                     &mut self.library_item,
                     &self.selected,
@@ -133,18 +231,31 @@ impl<E: Env + 'static> UpdateWithCtx<E> for MetaDetails {
                 let watched_effects =
                     watched_update::<E>(&mut self.watched, &self.meta_items, &self.library_item);
                 meta_items_effects
+                    .join(redirect_effects)
                     .join(streams_effects)
+                    .join(ranked_streams_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
             }
             Msg::Internal(Internal::ResourceRequestResult(request, result))
                 if request.path.resource == STREAM_RESOURCE_NAME =>
             {
-                resources_update_with_vector_content::<E, _>(
+                let streams_effects = resources_update_with_vector_content::<E, _>(
                     &mut self.streams,
                     ResourcesAction::ResourceRequestResult { request, result },
-                )
+                );
+                let ranked_streams_effects = ranked_streams_update(
+                    &mut self.ranked_streams,
+                    &self.streams,
+                    &ctx.profile.settings.stream_preferences,
+                );
+                streams_effects.join(ranked_streams_effects)
             }
+            Msg::Internal(Internal::ProfileChanged) => ranked_streams_update(
+                &mut self.ranked_streams,
+                &self.streams,
+                &ctx.profile.settings.stream_preferences,
+            ),
             Msg::Internal(Internal::LibraryChanged(_)) => {
                 let library_item_effects = library_item_update::<E>(
                     &mut self.library_item,
@@ -191,17 +302,51 @@ fn library_item_update<E: Env + 'static>(
     eq_update(library_item, next_library_item)
 }
 
-fn watched_update<E: Env>(
-    watched: &mut Option<WatchedBitField>,
-    meta_items: &[ResourceLoadable<MetaItem>],
-    library_item: &Option<LibraryItem>,
-) -> Effects {
-    let next_watched = meta_items
+fn ready_meta_item(meta_items: &[ResourceLoadable<MetaItem>]) -> Option<&MetaItem> {
+    meta_items
         .iter()
         .find_map(|meta_item| match &meta_item.content {
             Some(Loadable::Ready(meta_item)) => Some(meta_item),
             _ => None,
         })
+}
+
+// Sets the watched state of all matching videos with a single library item update.
+fn mark_videos_as_watched_effects(
+    meta_items: &[ResourceLoadable<MetaItem>],
+    library_item: &Option<LibraryItem>,
+    watched: &Option<WatchedBitField>,
+    predicate: impl Fn(&Video) -> bool,
+    is_watched: bool,
+) -> Effects {
+    match (ready_meta_item(meta_items), library_item, watched) {
+        (Some(meta_item), Some(library_item), Some(watched)) => {
+            let video_ids = meta_item
+                .videos
+                .iter()
+                .filter(|video| predicate(video))
+                .map(|video| video.id.as_str())
+                .collect::<Vec<_>>();
+            let mut library_item = library_item.to_owned();
+            if library_item
+                .state
+                .set_videos_watched(watched, &video_ids, is_watched)
+            {
+                Effects::msg(Msg::Internal(Internal::UpdateLibraryItem(library_item))).unchanged()
+            } else {
+                Effects::none().unchanged()
+            }
+        }
+        _ => Effects::none().unchanged(),
+    }
+}
+
+fn watched_update<E: Env>(
+    watched: &mut Option<WatchedBitField>,
+    meta_items: &[ResourceLoadable<MetaItem>],
+    library_item: &Option<LibraryItem>,
+) -> Effects {
+    let next_watched = ready_meta_item(meta_items)
         .and_then(|meta_item| {
             library_item
                 .as_ref()
@@ -211,6 +356,63 @@ fn watched_update<E: Env>(
     eq_update(watched, next_watched)
 }
 
+fn meta_redirect_update<E: Env + 'static>(
+    meta_items: &mut [ResourceLoadable<MetaItem>],
+    selected: &mut Option<Selected>,
+    redirected_meta_path: &mut Option<ResourcePath>,
+    request: &ResourceRequest,
+) -> Effects {
+    // Only the requests for the selected meta are redirected and only once, so redirect cycles are not followed
+    let meta_item = match (&selected, &redirected_meta_path) {
+        (Some(selected), None) if selected.meta_path == request.path => meta_items
+            .iter_mut()
+            .find(|meta_item| meta_item.request == *request),
+        _ => None,
+    };
+    let redirect = meta_item.and_then(|meta_item| {
+        meta_item
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.redirect.to_owned())
+            .filter(|redirect| *redirect != request.path.id)
+            .map(|redirect| (meta_item, redirect))
+    });
+    match (redirect, selected) {
+        (Some((meta_item, redirect)), Some(selected)) => {
+            let redirect_path = |path: &ResourcePath| ResourcePath {
+                id: redirect.to_owned(),
+                ..path.to_owned()
+            };
+            let redirect_request = ResourceRequest {
+                base: request.base.to_owned(),
+                path: redirect_path(&request.path),
+            };
+            // The streams of a movie are requested by the id of the meta
+            let next_selected = Selected {
+                meta_path: redirect_path(&selected.meta_path),
+                stream_path: selected.stream_path.as_ref().map(|stream_path| {
+                    if stream_path.id == request.path.id {
+                        redirect_path(stream_path)
+                    } else {
+                        stream_path.to_owned()
+                    }
+                }),
+            };
+            *redirected_meta_path = Some(request.path.to_owned());
+            let selected_effects = eq_update(selected, next_selected);
+            let meta_item_effects = resource_update::<E, _>(
+                meta_item,
+                ResourceAction::ResourceRequested {
+                    request: &redirect_request,
+                    priority: AddonRequestPriority::High,
+                },
+            );
+            selected_effects.join(meta_item_effects)
+        }
+        _ => Effects::none().unchanged(),
+    }
+}
+
 fn streams_from_meta_items(
     meta_items: &[ResourceLoadable<MetaItem>],
     video_id: &str,
@@ -221,6 +423,7 @@ fn streams_from_meta_items(
             ResourceLoadable {
                 request,
                 content: Some(Loadable::Ready(meta_item)),
+                ..
             } => Some((request, meta_item)),
             _ => None,
         })
@@ -251,5 +454,177 @@ fn streams_from_meta_items(
                 },
             },
             content: Some(Loadable::Ready(streams.into_owned())),
+            metadata: None,
+        })
+}
+
+fn ranked_streams_update(
+    ranked_streams: &mut Vec<RankedStream>,
+    streams: &[ResourceLoadable<Vec<Stream>>],
+    preferences: &StreamPreferences,
+) -> Effects {
+    let mut next_ranked_streams = Vec::<RankedStream>::new();
+    let mut positions = HashMap::new();
+    let streams = streams
+        .iter()
+        .filter_map(|streams| match &streams.content {
+            Some(Loadable::Ready(content)) => Some((&streams.request.base, content)),
+            _ => None,
         })
+        .flat_map(|(transport_url, streams)| {
+            streams.iter().map(move |stream| (transport_url, stream))
+        });
+    for (transport_url, stream) in streams {
+        match positions.entry(source_identity(&stream.source)) {
+            Entry::Occupied(entry) => {
+                let ranked_stream: &mut RankedStream = &mut next_ranked_streams[*entry.get()];
+                merge_stream(&mut ranked_stream.stream, stream);
+                if !ranked_stream.addons.contains(transport_url) {
+                    ranked_stream.addons.push(transport_url.to_owned());
+                };
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(next_ranked_streams.len());
+                next_ranked_streams.push(RankedStream {
+                    stream: stream.to_owned(),
+                    info: stream.info(),
+                    addons: vec![transport_url.to_owned()],
+                });
+            }
+        };
+    }
+    // The sort is stable, so equally ranked streams keep the order of the addons
+    next_ranked_streams.sort_by_cached_key(|ranked_stream| {
+        let info = &ranked_stream.info;
+        let exceeds_limits = matches!(
+            preferences.max_resolution.zip(info.resolution),
+            Some((max_resolution, resolution)) if resolution > max_resolution
+        ) || matches!(
+            preferences.max_size.zip(info.size),
+            Some((max_size, size)) if size > max_size
+        );
+        let language_rank = preferences
+            .preferred_languages
+            .iter()
+            .position(|preferred_language| {
+                info.languages
+                    .iter()
+                    .any(|language| is_same_language(language, preferred_language))
+            })
+            .unwrap_or(preferences.preferred_languages.len());
+        let delivery = match ranked_stream.stream.source {
+            StreamSource::Torrent { .. } => StreamDelivery::P2P,
+            _ => StreamDelivery::Cached,
+        };
+        let delivery_mismatch = matches!(
+            preferences.delivery,
+            Some(preferred_delivery) if preferred_delivery != delivery
+        );
+        (
+            exceeds_limits,
+            language_rank,
+            delivery_mismatch,
+            Reverse(info.resolution),
+            Reverse(info.source),
+            Reverse(info.seeders),
+        )
+    });
+    eq_update(ranked_streams, next_ranked_streams)
+}
+
+fn source_identity(source: &StreamSource) -> String {
+    match source {
+        StreamSource::Url { url } => url.to_string(),
+        StreamSource::YouTube { yt_id } => format!("yt:{}", yt_id),
+        StreamSource::Torrent {
+            info_hash,
+            file_idx,
+            ..
+        } => format!(
+            "magnet:{}:{}",
+            hex::encode(info_hash),
+            file_idx
+                .map(|file_idx| file_idx.to_string())
+                .unwrap_or_default()
+        ),
+        StreamSource::External { external_url } => external_url.to_string(),
+        StreamSource::PlayerFrame { player_frame_url } => player_frame_url.to_string(),
+        StreamSource::Rar {
+            rar_urls: urls,
+            file_idx,
+            ..
+        }
+        | StreamSource::Zip {
+            zip_urls: urls,
+            file_idx,
+            ..
+        } => format!(
+            "archive:{}:{}",
+            urls.iter()
+                .map(|archive_url| archive_url.url.as_str())
+                .join(","),
+            file_idx
+                .map(|file_idx| file_idx.to_string())
+                .unwrap_or_default()
+        ),
+        StreamSource::Nzb { nzb_url, .. } => nzb_url.to_string(),
+    }
+}
+
+fn merge_stream(stream: &mut Stream, duplicate: &Stream) {
+    for subtitles in &duplicate.subtitles {
+        if !stream
+            .subtitles
+            .iter()
+            .any(|stream_subtitles| stream_subtitles.url == subtitles.url)
+        {
+            stream.subtitles.push(subtitles.to_owned());
+        };
+    }
+    if let (
+        StreamSource::Torrent { announce, .. },
+        StreamSource::Torrent {
+            announce: duplicate_announce,
+            ..
+        },
+    ) = (&mut stream.source, &duplicate.source)
+    {
+        for source in duplicate_announce {
+            if !announce.contains(source) {
+                announce.push(source.to_owned());
+            };
+        }
+    };
+    let behavior_hints = &mut stream.behavior_hints;
+    let duplicate_behavior_hints = &duplicate.behavior_hints;
+    if behavior_hints.binge_group.is_none() {
+        behavior_hints.binge_group = duplicate_behavior_hints.binge_group.to_owned();
+    };
+    if behavior_hints.country_whitelist.is_none() {
+        behavior_hints.country_whitelist = duplicate_behavior_hints.country_whitelist.to_owned();
+    };
+    for (name, value) in &duplicate_behavior_hints.headers {
+        behavior_hints
+            .headers
+            .entry(name.to_owned())
+            .or_insert_with(|| value.to_owned());
+    }
+    if behavior_hints.filename.is_none() {
+        behavior_hints.filename = duplicate_behavior_hints.filename.to_owned();
+    };
+    if behavior_hints.video_hash.is_none() {
+        behavior_hints.video_hash = duplicate_behavior_hints.video_hash.to_owned();
+    };
+    if behavior_hints.video_size.is_none() {
+        behavior_hints.video_size = duplicate_behavior_hints.video_size;
+    };
+    if behavior_hints.proxy_headers.is_none() {
+        behavior_hints.proxy_headers = duplicate_behavior_hints.proxy_headers.to_owned();
+    };
+    for (name, value) in &duplicate_behavior_hints.other {
+        behavior_hints
+            .other
+            .entry(name.to_owned())
+            .or_insert_with(|| value.to_owned());
+    }
 }
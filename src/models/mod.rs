@@ -6,9 +6,13 @@ pub mod catalog_with_filters;
 pub mod catalogs_with_extra;
 pub mod continue_watching_preview;
 pub mod installed_addons_with_filters;
+pub mod library_statistics;
 pub mod library_with_filters;
 pub mod link;
+pub mod list_with_filters;
 pub mod meta_details;
 pub mod notifications;
 pub mod player;
+pub mod remote_addons;
 pub mod streaming_server;
+pub mod watch_history;
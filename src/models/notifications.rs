@@ -1,3 +1,5 @@
+use crate::addon_transport::AddonRequestPriority;
+use crate::constants::LAST_VIDEOS_IDS_EXTRA_NAME;
 use crate::models::common::{resources_update, Loadable, ResourceLoadable, ResourcesAction};
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::Internal::*;
@@ -12,8 +14,6 @@ use serde::*;
 // Cinemeta/Channels are curently limited to that many
 // but in general, it's healthy to have some sort of a limit
 const MAX_PER_REQUEST: usize = 50;
-// The name of the extra property
-const LAST_VID_IDS: &str = "lastVideosIds";
 
 #[derive(Default, Serialize)]
 pub struct Notifications {
@@ -31,11 +31,11 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Notifications {
                     .iter()
                     .flat_map(|addon| {
                         // The catalog supports this property
-                        let viable_catalogs = addon
-                            .manifest
-                            .catalogs
-                            .iter()
-                            .filter(|cat| cat.extra.iter().any(|e| e.name == LAST_VID_IDS));
+                        let viable_catalogs = addon.manifest.catalogs.iter().filter(|cat| {
+                            cat.extra
+                                .iter()
+                                .any(|e| e.name == LAST_VIDEOS_IDS_EXTRA_NAME)
+                        });
 
                         viable_catalogs.flat_map(move |cat| {
                             let relevant_items = library
@@ -66,7 +66,7 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Notifications {
                                     let ids =
                                         items_page.iter().map(|x| x.id.clone()).collect::<Vec<_>>();
                                     let extra_props = [ExtraValue {
-                                        name: LAST_VID_IDS.into(),
+                                        name: LAST_VIDEOS_IDS_EXTRA_NAME.into(),
                                         value: ids.join(","),
                                     }];
                                     let path = ResourcePath::with_extra(
@@ -82,17 +82,21 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Notifications {
                                         ResourceLoadable {
                                             request: addon_req.to_owned(),
                                             content: Some(Loadable::Loading),
+                                            metadata: None,
                                         },
                                         EffectFuture::Concurrent(
-                                            E::addon_transport(&addon_req.base)
-                                                .resource(&addon_req.path)
-                                                .map(move |result| {
-                                                    Msg::Internal(Internal::ResourceRequestResult(
-                                                        addon_req,
-                                                        Box::new(result),
-                                                    ))
-                                                })
-                                                .boxed_env(),
+                                            E::addon_transport_with_priority(
+                                                &addon_req.base,
+                                                AddonRequestPriority::Low,
+                                            )
+                                            .resource(&addon_req.path)
+                                            .map(move |result| {
+                                                Msg::Internal(Internal::ResourceRequestResult(
+                                                    addon_req,
+                                                    Box::new(result),
+                                                ))
+                                            })
+                                            .boxed_env(),
                                         )
                                         .into(),
                                     )
@@ -1,20 +1,29 @@
-use crate::constants::WATCHED_THRESHOLD_COEF;
+use crate::addon_transport::AddonRequestPriority;
+use crate::constants::{
+    VIDEO_FILENAME_EXTRA_NAME, VIDEO_HASH_EXTRA_NAME, VIDEO_SIZE_EXTRA_NAME, WATCHED_THRESHOLD_COEF,
+};
 use crate::models::common::{
     eq_update, resource_update, resources_update_with_vector_content, Loadable, ResourceAction,
     ResourceLoadable, ResourcesAction,
 };
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::{Action, ActionLoad, ActionPlayer, Internal, Msg};
-use crate::runtime::{Effects, Env, UpdateWithCtx};
-use crate::types::addon::{AggrRequest, ResourcePath, ResourceRequest};
+use crate::runtime::{EffectFuture, Effects, Env, EnvFutureExt, UpdateWithCtx};
+use crate::subtitles::stream_video_hash;
+use crate::types::addon::{AggrRequest, ExtraValue, ResourcePath, ResourceRequest};
+use crate::types::history::HistoryEntry;
 use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::normalize_language;
 use crate::types::profile::Settings as ProfileSettings;
-use crate::types::resource::{MetaItem, SeriesInfo, Stream, Subtitles, Video};
+use crate::types::resource::{MetaItem, SeriesInfo, Stream, StreamSource, Subtitles, Video};
+use enclose::enclose;
+use futures::FutureExt;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::cmp;
 use std::marker::PhantomData;
 use stremio_watched_bitfield::WatchedBitField;
+use url::Url;
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +34,26 @@ pub struct Selected {
     pub subtitles_path: Option<ResourcePath>,
 }
 
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SubtitlesOrigin {
+    /// Subtitles which came with the selected stream
+    Embedded,
+    Addon {
+        transport_url: Url,
+    },
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitlesCandidate {
+    pub subtitles: Subtitles,
+    pub origin: SubtitlesOrigin,
+    pub hearing_impaired: bool,
+}
+
 #[derive(Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Player {
@@ -36,12 +65,36 @@ pub struct Player {
     pub library_item: Option<LibraryItem>,
     #[serde(skip_serializing)]
     pub watched: Option<WatchedBitField>,
+    pub selected_subtitles: Option<SubtitlesCandidate>,
+    /// All the other subtitles ordered by preference
+    pub subtitles_alternatives: Vec<SubtitlesCandidate>,
+    /// The url of the subtitles selected by the user, `Some(None)` when the subtitles were turned off
+    #[serde(skip_serializing)]
+    pub manual_subtitles: Option<Option<Url>>,
+    /// OpenSubtitles hash computed for streams without a `videoHash` hint
+    #[serde(skip_serializing)]
+    pub video_hash: Option<String>,
+    /// The play session being recorded in the watch history
+    #[serde(skip_serializing)]
+    pub history_entry: Option<HistoryEntry>,
 }
 
 impl<E: Env + 'static> UpdateWithCtx<E> for Player {
     fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
         match msg {
             Msg::Action(Action::Load(ActionLoad::Player(selected))) => {
+                let (video_hash_effects, history_entry_effects) =
+                    if self.selected.as_ref().map(|selected| &selected.stream)
+                        != Some(&selected.stream)
+                    {
+                        self.video_hash = None;
+                        (
+                            video_hash_effects::<E>(&selected.stream),
+                            record_history_entry_effects(&self.history_entry.take()),
+                        )
+                    } else {
+                        (Effects::none().unchanged(), Effects::none().unchanged())
+                    };
                 let selected_effects = eq_update(&mut self.selected, Some(selected.to_owned()));
                 let meta_item_effects = match &selected.meta_request {
                     Some(meta_request) => match &mut self.meta_item {
@@ -49,17 +102,20 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                             meta_item,
                             ResourceAction::ResourceRequested {
                                 request: meta_request,
+                                priority: AddonRequestPriority::High,
                             },
                         ),
                         _ => {
                             let mut meta_item = ResourceLoadable {
                                 request: meta_request.to_owned(),
                                 content: None,
+                                metadata: None,
                             };
                             let meta_item_effects = resource_update::<E, _>(
                                 &mut meta_item,
                                 ResourceAction::ResourceRequested {
                                     request: meta_request,
+                                    priority: AddonRequestPriority::High,
                                 },
                             );
                             self.meta_item = Some(meta_item);
@@ -72,8 +128,13 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     Some(subtitles_path) => resources_update_with_vector_content::<E, _>(
                         &mut self.subtitles,
                         ResourcesAction::ResourcesRequested {
-                            request: &AggrRequest::AllOfResource(subtitles_path.to_owned()),
+                            request: &AggrRequest::AllOfResource(subtitles_path_with_hints(
+                                subtitles_path,
+                                &selected.stream,
+                                &self.video_hash,
+                            )),
                             addons: &ctx.profile.addons,
+                            priority: AddonRequestPriority::High,
                         },
                     ),
                     _ => eq_update(&mut self.subtitles, vec![]),
@@ -86,6 +147,16 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                 );
                 let series_info_effects =
                     series_info_update(&mut self.series_info, &self.selected, &self.meta_item);
+                self.manual_subtitles = None;
+                let subtitles_selection_effects = subtitles_selection_update(
+                    &mut self.selected_subtitles,
+                    &mut self.subtitles_alternatives,
+                    &self.selected,
+                    &self.subtitles,
+                    &self.series_info,
+                    &self.manual_subtitles,
+                    &ctx.profile.settings,
+                );
                 let library_item_effects = library_item_update::<E>(
                     &mut self.library_item,
                     &self.selected,
@@ -99,10 +170,15 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     .join(subtitles_effects)
                     .join(next_video_effects)
                     .join(series_info_effects)
+                    .join(subtitles_selection_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
+                    .join(video_hash_effects)
+                    .join(history_entry_effects)
             }
             Msg::Action(Action::Unload) => {
+                let history_entry_effects =
+                    record_history_entry_effects(&self.history_entry.take());
                 let selected_effects = eq_update(&mut self.selected, None);
                 let meta_item_effects = eq_update(&mut self.meta_item, None);
                 let subtitles_effects = eq_update(&mut self.subtitles, vec![]);
@@ -110,6 +186,11 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                 let series_info_effects = eq_update(&mut self.series_info, None);
                 let library_item_effects = eq_update(&mut self.library_item, None);
                 let watched_effects = eq_update(&mut self.watched, None);
+                let selected_subtitles_effects = eq_update(&mut self.selected_subtitles, None);
+                let subtitles_alternatives_effects =
+                    eq_update(&mut self.subtitles_alternatives, vec![]);
+                self.manual_subtitles = None;
+                self.video_hash = None;
                 selected_effects
                     .join(meta_item_effects)
                     .join(subtitles_effects)
@@ -117,6 +198,9 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     .join(series_info_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
+                    .join(selected_subtitles_effects)
+                    .join(subtitles_alternatives_effects)
+                    .join(history_entry_effects)
             }
             Msg::Action(Action::Player(ActionPlayer::UpdateLibraryItemState {
                 time,
@@ -133,6 +217,12 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     }),
                     Some(library_item),
                 ) => {
+                    let history_entry_effects = history_entry_update::<E>(
+                        &mut self.history_entry,
+                        library_item,
+                        video_id,
+                        *time,
+                    );
                     library_item.state.last_watched = Some(E::now());
                     if library_item.state.video_id != Some(video_id.to_owned()) {
                         library_item.state.video_id = Some(video_id.to_owned());
@@ -173,17 +263,94 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     if library_item.removed {
                         library_item.temp = true;
                     };
-                    Effects::none()
+                    Effects::none().join(history_entry_effects)
                 }
                 _ => Effects::none().unchanged(),
             },
-            Msg::Action(Action::Player(ActionPlayer::PushToLibrary)) => match &self.library_item {
-                Some(library_item) => Effects::msg(Msg::Internal(Internal::UpdateLibraryItem(
-                    library_item.to_owned(),
-                )))
-                .unchanged(),
+            Msg::Action(Action::Player(ActionPlayer::PushToLibrary)) => {
+                let library_item_effects = match &self.library_item {
+                    Some(library_item) => Effects::msg(Msg::Internal(Internal::UpdateLibraryItem(
+                        library_item.to_owned(),
+                    )))
+                    .unchanged(),
+                    _ => Effects::none().unchanged(),
+                };
+                library_item_effects.join(record_history_entry_effects(&self.history_entry))
+            }
+            Msg::Action(Action::Player(ActionPlayer::SelectSubtitles(url))) => {
+                match &self.selected {
+                    Some(selected) => {
+                        self.manual_subtitles = Some(url.to_owned());
+                        let subtitles_selection_effects = subtitles_selection_update(
+                            &mut self.selected_subtitles,
+                            &mut self.subtitles_alternatives,
+                            &self.selected,
+                            &self.subtitles,
+                            &self.series_info,
+                            &self.manual_subtitles,
+                            &ctx.profile.settings,
+                        );
+                        let language = match (url, &self.selected_subtitles) {
+                            (Some(_), Some(candidate)) => {
+                                Some(Some(normalize_language(&candidate.subtitles.lang)))
+                            }
+                            (None, _) => Some(None),
+                            _ => None,
+                        };
+                        let series_subtitles_effects =
+                            match (&selected.meta_request, &self.series_info, language) {
+                                (Some(meta_request), Some(_), Some(language)) => {
+                                    Effects::msg(Msg::Internal(Internal::UpdateSeriesSubtitles {
+                                        meta_id: meta_request.path.id.to_owned(),
+                                        language,
+                                    }))
+                                    .unchanged()
+                                }
+                                _ => Effects::none().unchanged(),
+                            };
+                        subtitles_selection_effects.join(series_subtitles_effects)
+                    }
+                    _ => Effects::none().unchanged(),
+                }
+            }
+            Msg::Internal(Internal::VideoHashResult(url, Ok(video_hash))) => match &self.selected {
+                Some(Selected {
+                    stream:
+                        stream @ Stream {
+                            source: StreamSource::Url { url: stream_url },
+                            ..
+                        },
+                    subtitles_path,
+                    ..
+                }) if stream_url == url && self.video_hash.as_ref() != Some(video_hash) => {
+                    self.video_hash = Some(video_hash.to_owned());
+                    match subtitles_path {
+                        Some(subtitles_path) => resources_update_with_vector_content::<E, _>(
+                            &mut self.subtitles,
+                            ResourcesAction::ResourcesRequested {
+                                request: &AggrRequest::AllOfResource(subtitles_path_with_hints(
+                                    subtitles_path,
+                                    stream,
+                                    &self.video_hash,
+                                )),
+                                addons: &ctx.profile.addons,
+                                priority: AddonRequestPriority::High,
+                            },
+                        ),
+                        _ => Effects::none().unchanged(),
+                    }
+                }
                 _ => Effects::none().unchanged(),
             },
+            Msg::Internal(Internal::ProfileChanged) => subtitles_selection_update(
+                &mut self.selected_subtitles,
+                &mut self.subtitles_alternatives,
+                &self.selected,
+                &self.subtitles,
+                &self.series_info,
+                &self.manual_subtitles,
+                &ctx.profile.settings,
+            ),
             Msg::Internal(Internal::ResourceRequestResult(request, result)) => {
                 let meta_item_effects = match &mut self.meta_item {
                     Some(meta_item) => resource_update::<E, _>(
@@ -204,6 +371,15 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                 );
                 let series_info_effects =
                     series_info_update(&mut self.series_info, &self.selected, &self.meta_item);
+                let subtitles_selection_effects = subtitles_selection_update(
+                    &mut self.selected_subtitles,
+                    &mut self.subtitles_alternatives,
+                    &self.selected,
+                    &self.subtitles,
+                    &self.series_info,
+                    &self.manual_subtitles,
+                    &ctx.profile.settings,
+                );
                 let library_item_effects = library_item_update::<E>(
                     &mut self.library_item,
                     &self.selected,
@@ -216,6 +392,7 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
                     .join(subtitles_effects)
                     .join(next_video_effects)
                     .join(series_info_effects)
+                    .join(subtitles_selection_effects)
                     .join(library_item_effects)
                     .join(watched_effects)
             }
@@ -224,6 +401,65 @@ impl<E: Env + 'static> UpdateWithCtx<E> for Player {
     }
 }
 
+fn video_hash_effects<E: Env + 'static>(stream: &Stream) -> Effects {
+    match (&stream.source, &stream.behavior_hints.video_hash) {
+        (StreamSource::Url { url }, None) => match stream_video_hash::<E>(stream) {
+            Some(video_hash) => Effects::future(EffectFuture::Concurrent(
+                video_hash
+                    .map(enclose!((url) move |result| {
+                        Msg::Internal(Internal::VideoHashResult(url, result))
+                    }))
+                    .boxed_env(),
+            ))
+            .unchanged(),
+            _ => Effects::none().unchanged(),
+        },
+        _ => Effects::none().unchanged(),
+    }
+}
+
+/// Adds the video hash, size and filename of the stream as extra,
+/// so subtitles addons which match by hash can find exact matches.
+/// The hash hint of the stream takes precedence over the computed one.
+fn subtitles_path_with_hints(
+    subtitles_path: &ResourcePath,
+    stream: &Stream,
+    video_hash: &Option<String>,
+) -> ResourcePath {
+    let behavior_hints = &stream.behavior_hints;
+    let hints = [
+        (
+            VIDEO_HASH_EXTRA_NAME,
+            behavior_hints
+                .video_hash
+                .to_owned()
+                .or_else(|| video_hash.to_owned()),
+        ),
+        (
+            VIDEO_SIZE_EXTRA_NAME,
+            behavior_hints
+                .video_size
+                .map(|video_size| video_size.to_string()),
+        ),
+        (
+            VIDEO_FILENAME_EXTRA_NAME,
+            behavior_hints.filename.to_owned(),
+        ),
+    ];
+    let mut subtitles_path = subtitles_path.to_owned();
+    for (name, value) in hints {
+        if let Some(value) = value {
+            if subtitles_path.get_extra_first_value(name).is_none() {
+                subtitles_path.extra.push(ExtraValue {
+                    name: name.to_owned(),
+                    value,
+                });
+            };
+        };
+    }
+    subtitles_path
+}
+
 fn next_video_update(
     video: &mut Option<Video>,
     selected: &Option<Selected>,
@@ -304,6 +540,91 @@ fn series_info_update(
     eq_update(series_info, next_series_info)
 }
 
+fn subtitles_selection_update(
+    selected_subtitles: &mut Option<SubtitlesCandidate>,
+    subtitles_alternatives: &mut Vec<SubtitlesCandidate>,
+    selected: &Option<Selected>,
+    subtitles: &[ResourceLoadable<Vec<Subtitles>>],
+    series_info: &Option<SeriesInfo>,
+    manual_subtitles: &Option<Option<Url>>,
+    settings: &ProfileSettings,
+) -> Effects {
+    let preferences = &settings.subtitles_preferences;
+    let series_override = match (selected, series_info) {
+        (
+            Some(Selected {
+                meta_request: Some(meta_request),
+                ..
+            }),
+            Some(_),
+        ) => preferences.series_overrides.get(&meta_request.path.id),
+        _ => None,
+    };
+    let languages = series_override
+        .cloned()
+        .flatten()
+        .into_iter()
+        .chain(Some(settings.subtitles_language.to_owned()))
+        .chain(preferences.secondary_language.to_owned())
+        .collect::<Vec<_>>();
+    let embedded_subtitles = selected
+        .iter()
+        .flat_map(|selected| selected.stream.subtitles.iter())
+        .map(|subtitles| (subtitles, SubtitlesOrigin::Embedded));
+    let addon_subtitles = subtitles
+        .iter()
+        .filter_map(|subtitles| match &subtitles.content {
+            Some(Loadable::Ready(content)) => Some((&subtitles.request.base, content)),
+            _ => None,
+        })
+        .flat_map(|(transport_url, subtitles)| {
+            subtitles.iter().map(move |subtitles| {
+                (
+                    subtitles,
+                    SubtitlesOrigin::Addon {
+                        transport_url: transport_url.to_owned(),
+                    },
+                )
+            })
+        });
+    let mut candidates = embedded_subtitles
+        .chain(addon_subtitles)
+        .unique_by(|(subtitles, _)| &subtitles.url)
+        .map(|(subtitles, origin)| SubtitlesCandidate {
+            subtitles: subtitles.to_owned(),
+            origin,
+            hearing_impaired: subtitles.is_hearing_impaired(),
+        })
+        .collect::<Vec<_>>();
+    let language_rank = |candidate: &SubtitlesCandidate| {
+        languages
+            .iter()
+            .position(|language| candidate.subtitles.is_language(language))
+            .unwrap_or(languages.len())
+    };
+    // The sort is stable, so equally ranked subtitles keep the order of the addons
+    candidates.sort_by_cached_key(|candidate| {
+        (
+            language_rank(candidate),
+            candidate.hearing_impaired != preferences.hearing_impaired,
+            candidate.origin != SubtitlesOrigin::Embedded,
+        )
+    });
+    let position = match (manual_subtitles, series_override) {
+        (Some(Some(url)), _) => candidates
+            .iter()
+            .position(|candidate| candidate.subtitles.url == *url),
+        (Some(None), _) | (None, Some(None)) => None,
+        (None, _) => candidates
+            .first()
+            .filter(|candidate| language_rank(candidate) < languages.len())
+            .map(|_| 0),
+    };
+    let next_selected_subtitles = position.map(|position| candidates.remove(position));
+    eq_update(selected_subtitles, next_selected_subtitles)
+        .join(eq_update(subtitles_alternatives, candidates))
+}
+
 fn library_item_update<E: Env + 'static>(
     library_item: &mut Option<LibraryItem>,
     selected: &Option<Selected>,
@@ -354,6 +675,41 @@ fn library_item_update<E: Env + 'static>(
     }
 }
 
+fn history_entry_update<E: Env + 'static>(
+    history_entry: &mut Option<HistoryEntry>,
+    library_item: &LibraryItem,
+    video_id: &str,
+    time: u64,
+) -> Effects {
+    match history_entry {
+        Some(history_entry)
+            if history_entry.id == library_item.id && history_entry.video_id == video_id =>
+        {
+            history_entry.end = E::now();
+            history_entry.duration = history_entry.duration.saturating_add(cmp::min(
+                1000,
+                time.saturating_sub(library_item.state.time_offset),
+            ));
+            Effects::none().unchanged()
+        }
+        _ => {
+            let record_effects = record_history_entry_effects(history_entry);
+            *history_entry = Some(HistoryEntry::new::<E>(library_item, video_id));
+            record_effects
+        }
+    }
+}
+
+fn record_history_entry_effects(history_entry: &Option<HistoryEntry>) -> Effects {
+    match history_entry {
+        Some(history_entry) if history_entry.duration > 0 => Effects::msg(Msg::Internal(
+            Internal::RecordHistoryEntry(history_entry.to_owned()),
+        ))
+        .unchanged(),
+        _ => Effects::none().unchanged(),
+    }
+}
+
 fn watched_update<E: Env>(
     watched: &mut Option<WatchedBitField>,
     meta_item: &Option<ResourceLoadable<MetaItem>>,
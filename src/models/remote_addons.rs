@@ -0,0 +1,169 @@
+use crate::models::catalog_with_filters::CatalogWithFilters;
+use crate::models::common::{eq_update, Loadable};
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Action, ActionRemoteAddons, Internal, Msg};
+use crate::runtime::{Effects, Env, UpdateWithCtx};
+use crate::types::addon::{Descriptor, DescriptorPreview};
+use crate::types::profile::Profile;
+use boolinator::Boolinator;
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum RemoteAddonAction {
+    Install,
+    Upgrade,
+    Configure,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteAddon {
+    pub addon: DescriptorPreview,
+    pub installed: bool,
+    pub actions: Vec<RemoteAddonAction>,
+}
+
+impl RemoteAddon {
+    pub fn new(addon: &DescriptorPreview, profile: &Profile) -> Self {
+        let installed_addon = profile.addons.iter().find(|installed_addon| {
+            installed_addon.transport_url == addon.transport_url
+                || installed_addon.manifest.id == addon.manifest.id
+        });
+        let behavior_hints = &addon.manifest.behavior_hints;
+        let install_action = match installed_addon {
+            Some(Descriptor { manifest, .. }) if manifest.version < addon.manifest.version => {
+                Some(RemoteAddonAction::Upgrade)
+            }
+            None if !behavior_hints.configuration_required => Some(RemoteAddonAction::Install),
+            _ => None,
+        };
+        let configure_action = (behavior_hints.configurable
+            || behavior_hints.configuration_required)
+            .as_some(RemoteAddonAction::Configure);
+        RemoteAddon {
+            addon: addon.to_owned(),
+            installed: installed_addon.is_some(),
+            actions: install_action.into_iter().chain(configure_action).collect(),
+        }
+    }
+}
+
+/// Addons from the `addon_catalog` resources joined with the installed addons.
+///
+/// Handles the same messages as `CatalogWithFilters<DescriptorPreview>` and
+/// filters the addons from all loaded pages by the search text.
+#[derive(Default, Serialize)]
+pub struct RemoteAddons {
+    #[serde(flatten)]
+    pub catalog_with_filters: CatalogWithFilters<DescriptorPreview>,
+    pub search: Option<String>,
+    pub addons: Vec<RemoteAddon>,
+}
+
+impl RemoteAddons {
+    pub fn new(profile: &Profile) -> (Self, Effects) {
+        let (catalog_with_filters, effects) = CatalogWithFilters::new(profile);
+        (
+            RemoteAddons {
+                catalog_with_filters,
+                ..RemoteAddons::default()
+            },
+            effects,
+        )
+    }
+}
+
+impl<E: Env + 'static> UpdateWithCtx<E> for RemoteAddons {
+    fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
+        match msg {
+            Msg::Action(Action::RemoteAddons(ActionRemoteAddons::Search(search))) => {
+                let search = search.trim();
+                let search_effects = eq_update(
+                    &mut self.search,
+                    (!search.is_empty()).as_some(search.to_owned()),
+                );
+                let addons_effects = addons_update(
+                    &mut self.addons,
+                    &self.catalog_with_filters,
+                    &self.search,
+                    &ctx.profile,
+                );
+                search_effects.join(addons_effects)
+            }
+            Msg::Action(Action::Unload) => {
+                let catalog_with_filters_effects =
+                    UpdateWithCtx::<E>::update(&mut self.catalog_with_filters, msg, ctx);
+                let search_effects = eq_update(&mut self.search, None);
+                let addons_effects = addons_update(
+                    &mut self.addons,
+                    &self.catalog_with_filters,
+                    &self.search,
+                    &ctx.profile,
+                );
+                catalog_with_filters_effects
+                    .join(search_effects)
+                    .join(addons_effects)
+            }
+            Msg::Internal(Internal::ProfileChanged) => {
+                let catalog_with_filters_effects =
+                    UpdateWithCtx::<E>::update(&mut self.catalog_with_filters, msg, ctx);
+                let addons_effects = addons_update(
+                    &mut self.addons,
+                    &self.catalog_with_filters,
+                    &self.search,
+                    &ctx.profile,
+                );
+                catalog_with_filters_effects.join(addons_effects)
+            }
+            _ => {
+                let catalog_with_filters_effects =
+                    UpdateWithCtx::<E>::update(&mut self.catalog_with_filters, msg, ctx);
+                if catalog_with_filters_effects.has_changed {
+                    let addons_effects = addons_update(
+                        &mut self.addons,
+                        &self.catalog_with_filters,
+                        &self.search,
+                        &ctx.profile,
+                    );
+                    catalog_with_filters_effects.join(addons_effects)
+                } else {
+                    catalog_with_filters_effects
+                }
+            }
+        }
+    }
+}
+
+fn addons_update(
+    addons: &mut Vec<RemoteAddon>,
+    catalog_with_filters: &CatalogWithFilters<DescriptorPreview>,
+    search: &Option<String>,
+    profile: &Profile,
+) -> Effects {
+    let search = search.as_ref().map(|search| search.to_lowercase());
+    let next_addons = catalog_with_filters
+        .catalog
+        .iter()
+        .filter_map(|page| match &page.content {
+            Some(Loadable::Ready(addons)) => Some(addons),
+            _ => None,
+        })
+        .flatten()
+        .filter(|addon| match &search {
+            Some(search) => {
+                addon.manifest.name.to_lowercase().contains(search)
+                    || addon
+                        .manifest
+                        .description
+                        .as_ref()
+                        .map(|description| description.to_lowercase().contains(search))
+                        .unwrap_or_default()
+            }
+            None => true,
+        })
+        .map(|addon| RemoteAddon::new(addon, profile))
+        .collect::<Vec<_>>();
+    eq_update(addons, next_addons)
+}
@@ -0,0 +1,122 @@
+use crate::constants::CATALOG_PAGE_SIZE;
+use crate::models::common::eq_update;
+use crate::models::ctx::Ctx;
+use crate::models::library_with_filters::LibraryRequestPage;
+use crate::runtime::msg::{Action, ActionLoad, Internal, Msg};
+use crate::runtime::{Effects, Env, UpdateWithCtx};
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use boolinator::Boolinator;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRequest {
+    #[serde(default)]
+    pub page: LibraryRequestPage,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selected {
+    pub request: HistoryRequest,
+}
+
+#[derive(PartialEq, Serialize)]
+pub struct SelectablePage {
+    pub request: HistoryRequest,
+}
+
+#[derive(Default, PartialEq, Serialize)]
+pub struct Selectable {
+    pub prev_page: Option<SelectablePage>,
+    pub next_page: Option<SelectablePage>,
+}
+
+/// The play sessions of the watch history, most recent first.
+#[derive(Default, Serialize)]
+pub struct WatchHistory {
+    pub selected: Option<Selected>,
+    pub selectable: Selectable,
+    pub catalog: Vec<HistoryEntry>,
+}
+
+impl<E: Env + 'static> UpdateWithCtx<E> for WatchHistory {
+    fn update(&mut self, msg: &Msg, ctx: &Ctx) -> Effects {
+        match msg {
+            Msg::Action(Action::Load(ActionLoad::WatchHistory(selected))) => {
+                let selected_effects = eq_update(&mut self.selected, Some(selected.to_owned()));
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.history);
+                let catalog_effects =
+                    catalog_update(&mut self.catalog, &self.selected, &ctx.history);
+                selected_effects
+                    .join(selectable_effects)
+                    .join(catalog_effects)
+            }
+            Msg::Action(Action::Unload) => {
+                let selected_effects = eq_update(&mut self.selected, None);
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.history);
+                let catalog_effects =
+                    catalog_update(&mut self.catalog, &self.selected, &ctx.history);
+                selected_effects
+                    .join(selectable_effects)
+                    .join(catalog_effects)
+            }
+            Msg::Internal(Internal::HistoryChanged(_)) => {
+                let selectable_effects =
+                    selectable_update(&mut self.selectable, &self.selected, &ctx.history);
+                let catalog_effects =
+                    catalog_update(&mut self.catalog, &self.selected, &ctx.history);
+                selectable_effects.join(catalog_effects)
+            }
+            _ => Effects::none().unchanged(),
+        }
+    }
+}
+
+fn selectable_update(
+    selectable: &mut Selectable,
+    selected: &Option<Selected>,
+    history: &HistoryBucket,
+) -> Effects {
+    let next_selectable = match selected {
+        Some(selected) => {
+            let page = selected.request.page.get();
+            Selectable {
+                prev_page: (page > 1).as_option().map(|_| SelectablePage {
+                    request: HistoryRequest {
+                        page: LibraryRequestPage(NonZeroUsize::new(page - 1).unwrap()),
+                    },
+                }),
+                next_page: (history.entries.len() > page * CATALOG_PAGE_SIZE)
+                    .as_option()
+                    .map(|_| SelectablePage {
+                        request: HistoryRequest {
+                            page: LibraryRequestPage(NonZeroUsize::new(page + 1).unwrap()),
+                        },
+                    }),
+            }
+        }
+        _ => Selectable::default(),
+    };
+    eq_update(selectable, next_selectable)
+}
+
+fn catalog_update(
+    catalog: &mut Vec<HistoryEntry>,
+    selected: &Option<Selected>,
+    history: &HistoryBucket,
+) -> Effects {
+    let next_catalog = match selected {
+        Some(selected) => history
+            .entries
+            .iter()
+            .rev()
+            .skip((selected.request.page.get() - 1) * CATALOG_PAGE_SIZE)
+            .take(CATALOG_PAGE_SIZE)
+            .cloned()
+            .collect(),
+        _ => vec![],
+    };
+    eq_update(catalog, next_catalog)
+}
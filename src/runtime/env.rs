@@ -1,10 +1,16 @@
-use crate::addon_transport::{AddonHTTPTransport, AddonTransport, UnsupportedTransport};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::addon_transport::AddonProcessTransport;
+use crate::addon_transport::{
+    AddonHTTPTransport, AddonM3UTransport, AddonRequestPriority, AddonScheduledTransport,
+    AddonTransport, UnsupportedTransport,
+};
 use crate::constants::{
     LIBRARY_RECENT_STORAGE_KEY, LIBRARY_STORAGE_KEY, PROFILE_STORAGE_KEY, SCHEMA_VERSION,
     SCHEMA_VERSION_STORAGE_KEY,
 };
 use crate::models::ctx::Ctx;
 use crate::models::streaming_server::StreamingServer;
+use crate::types::normalize_language;
 use chrono::{DateTime, Utc};
 use futures::{future, Future, FutureExt, TryFutureExt};
 use http::Request;
@@ -24,6 +30,9 @@ pub enum EnvError {
     StorageSchemaVersionUpgrade(Box<EnvError>),
     StorageReadError(String),
     StorageWriteError(String),
+    // Env::fetch should fail with this error when the server responds with 429,
+    // optionally carrying the Retry-After header in seconds.
+    TooManyRequests(Option<u64>),
     Other(String),
 }
 
@@ -44,6 +53,10 @@ impl EnvError {
             ),
             EnvError::StorageReadError(message) => format!("Storage read error: {}", message),
             EnvError::StorageWriteError(message) => format!("Storage write error: {}", message),
+            EnvError::TooManyRequests(Some(retry_after)) => {
+                format!("Too many requests, retry after {} seconds", retry_after)
+            }
+            EnvError::TooManyRequests(None) => "Too many requests".to_owned(),
             EnvError::Other(message) => format!("Other error: {}", message),
         }
     }
@@ -57,6 +70,7 @@ impl EnvError {
             EnvError::StorageSchemaVersionUpgrade(_) => 6,
             EnvError::StorageReadError(_) => 7,
             EnvError::StorageWriteError(_) => 8,
+            EnvError::TooManyRequests(_) => 9,
             EnvError::Other(_) => 1001,
         }
     }
@@ -86,6 +100,15 @@ impl From<serde_json::Error> for EnvError {
     }
 }
 
+/// A range of bytes read from a local or a remote file
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct FileChunk {
+    pub bytes: Vec<u8>,
+    /// Size of the whole file in bytes
+    pub file_size: u64,
+}
+
 #[cfg(not(feature = "env-future-send"))]
 pub type EnvFuture<T> = futures::future::LocalBoxFuture<'static, T>;
 
@@ -115,6 +138,9 @@ pub trait EnvFutureExt: Future {
 pub type TryEnvFuture<T> = EnvFuture<Result<T, EnvError>>;
 
 pub trait Env {
+    /// Sends a request and deserializes the JSON body of the response.
+    /// A response with status 429 must fail with `EnvError::TooManyRequests`, carrying the
+    /// `Retry-After` header in seconds when present, as the addon scheduler relies on it.
     fn fetch<
         #[cfg(not(feature = "env-future-send"))] IN: Serialize + 'static,
         #[cfg(feature = "env-future-send")] IN: Serialize + Send + 'static,
@@ -147,12 +173,52 @@ pub trait Env {
     fn analytics_context(ctx: &Ctx, streaming_server: &StreamingServer) -> serde_json::Value;
     #[cfg(debug_assertions)]
     fn log(message: String);
+    /// Reads up to `length` bytes at `offset` of a local file.
+    /// Environments without access to the file system keep the default implementation.
+    fn read_file(_path: &str, _offset: u64, _length: u64) -> TryEnvFuture<FileChunk> {
+        future::err(EnvError::Other(
+            "Reading local files is not supported".to_owned(),
+        ))
+        .boxed_env()
+    }
+    /// Fetches up to `length` bytes at `offset` of a remote file with a `Range` request,
+    /// `file_size` is taken from the `Content-Range` header of the response.
+    /// Only partial content responses are accepted: when the server ignores the `Range` header
+    /// the request fails (or the body is read only up to the range) instead of buffering the whole file.
+    /// Environments without range requests keep the default implementation.
+    fn fetch_range(_url: &Url, _offset: u64, _length: u64) -> TryEnvFuture<FileChunk> {
+        future::err(EnvError::Other(
+            "Range requests are not supported".to_owned(),
+        ))
+        .boxed_env()
+    }
     fn addon_transport(transport_url: &Url) -> Box<dyn AddonTransport>
+    where
+        Self: Sized + 'static,
+    {
+        Self::addon_transport_with_priority(transport_url, AddonRequestPriority::default())
+    }
+    fn addon_transport_with_priority(
+        transport_url: &Url,
+        priority: AddonRequestPriority,
+    ) -> Box<dyn AddonTransport>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(AddonScheduledTransport::<Self>::new(
+            transport_url.to_owned(),
+            priority,
+        ))
+    }
+    fn unscheduled_addon_transport(transport_url: &Url) -> Box<dyn AddonTransport>
     where
         Self: Sized + 'static,
     {
         match transport_url.scheme() {
             "http" | "https" => Box::new(AddonHTTPTransport::<Self>::new(transport_url.to_owned())),
+            #[cfg(not(target_arch = "wasm32"))]
+            "stdio" => Box::new(AddonProcessTransport::new(transport_url.to_owned())),
+            "m3u" => Box::new(AddonM3UTransport::<Self>::new(transport_url.to_owned())),
             _ => Box::new(UnsupportedTransport::new(transport_url.to_owned())),
         }
     }
@@ -199,6 +265,12 @@ pub trait Env {
                         .await?;
                     schema_version = 5;
                 };
+                if schema_version == 5 {
+                    migrate_storage_schema_to_v6::<Self>()
+                        .map_err(|error| EnvError::StorageSchemaVersionUpgrade(Box::new(error)))
+                        .await?;
+                    schema_version = 6;
+                };
                 if schema_version != SCHEMA_VERSION {
                     panic!(
                         "Storage schema version must be upgraded from {} to {}",
@@ -364,3 +436,27 @@ fn migrate_storage_schema_to_v5<E: Env>() -> TryEnvFuture<()> {
         .and_then(|_| E::set_storage(SCHEMA_VERSION_STORAGE_KEY, Some(&5)))
         .boxed_env()
 }
+
+fn migrate_storage_schema_to_v6<E: Env>() -> TryEnvFuture<()> {
+    E::get_storage::<serde_json::Value>(PROFILE_STORAGE_KEY)
+        .and_then(|mut profile| {
+            match profile
+                .as_mut()
+                .and_then(|profile| profile.as_object_mut())
+                .and_then(|profile| profile.get_mut("settings"))
+                .and_then(|settings| settings.as_object_mut())
+            {
+                Some(settings) => {
+                    for key in ["audioLanguage", "subtitlesLanguage"] {
+                        if let Some(serde_json::Value::String(language)) = settings.get_mut(key) {
+                            *language = normalize_language(language);
+                        };
+                    }
+                    E::set_storage(PROFILE_STORAGE_KEY, Some(&profile))
+                }
+                _ => E::set_storage::<()>(PROFILE_STORAGE_KEY, None),
+            }
+        })
+        .and_then(|_| E::set_storage(SCHEMA_VERSION_STORAGE_KEY, Some(&6)))
+        .boxed_env()
+}
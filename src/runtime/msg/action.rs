@@ -3,15 +3,20 @@ use crate::models::catalog_with_filters::Selected as CatalogWithFiltersSelected;
 use crate::models::catalogs_with_extra::Selected as CatalogsWithExtraSelected;
 use crate::models::installed_addons_with_filters::Selected as InstalledAddonsWithFiltersSelected;
 use crate::models::library_with_filters::Selected as LibraryWithFiltersSelected;
+use crate::models::list_with_filters::Selected as ListWithFiltersSelected;
 use crate::models::meta_details::Selected as MetaDetailsSelected;
 use crate::models::player::Selected as PlayerSelected;
 use crate::models::streaming_server::Settings as StreamingServerSettings;
+use crate::models::watch_history::Selected as WatchHistorySelected;
 use crate::types::addon::Descriptor;
 use crate::types::api::AuthRequest;
+use crate::types::library::LibraryExportFormat;
 use crate::types::profile::Settings as ProfileSettings;
 use crate::types::resource::MetaItemPreview;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::ops::Range;
+use url::Url;
 
 #[derive(Clone, Deserialize)]
 #[serde(tag = "action", content = "args")]
@@ -19,17 +24,45 @@ pub enum ActionCtx {
     Authenticate(AuthRequest),
     Logout,
     InstallAddon(Descriptor),
+    ImportM3UPlaylist {
+        name: String,
+        content: String,
+    },
     UpgradeAddon(Descriptor),
     UninstallAddon(Descriptor),
     UpdateSettings(ProfileSettings),
     AddToLibrary(MetaItemPreview),
     RemoveFromLibrary(String),
     RewindLibraryItem(String),
+    ImportLibrary {
+        format: LibraryExportFormat,
+        content: String,
+    },
     PushUserToAPI,
     PullUserFromAPI,
     PushAddonsToAPI,
     PullAddonsFromAPI,
     SyncLibraryWithAPI,
+    CreateList(String),
+    RenameList {
+        id: String,
+        name: String,
+    },
+    DeleteList(String),
+    AddToList {
+        id: String,
+        meta_item: MetaItemPreview,
+    },
+    RemoveFromList {
+        id: String,
+        item_id: String,
+    },
+    SyncListsWithAPI,
+    RemoveHistoryEntry {
+        id: String,
+        start: DateTime<Utc>,
+    },
+    Undo,
 }
 
 #[derive(Clone, Deserialize)]
@@ -49,6 +82,15 @@ pub enum ActionCatalogsWithExtra {
 #[serde(tag = "action", content = "args")]
 pub enum ActionMetaDetails {
     MarkAsWatched(String, bool),
+    MarkSeasonAsWatched(u32, bool),
+    MarkAsWatchedUntil(String, bool),
+    MarkSeriesAsWatched(bool),
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "action", content = "args")]
+pub enum ActionRemoteAddons {
+    Search(String),
 }
 
 #[derive(Clone, Deserialize)]
@@ -69,6 +111,7 @@ pub enum ActionLink {
 pub enum ActionPlayer {
     UpdateLibraryItemState { time: u64, duration: u64 },
     PushToLibrary,
+    SelectSubtitles(Option<Url>),
 }
 
 #[derive(Clone, Deserialize)]
@@ -79,6 +122,8 @@ pub enum ActionLoad {
     CatalogsWithExtra(CatalogsWithExtraSelected),
     InstalledAddonsWithFilters(InstalledAddonsWithFiltersSelected),
     LibraryWithFilters(LibraryWithFiltersSelected),
+    ListWithFilters(ListWithFiltersSelected),
+    WatchHistory(WatchHistorySelected),
     MetaDetails(MetaDetailsSelected),
     Player(PlayerSelected),
     Link,
@@ -96,6 +141,7 @@ pub enum Action {
     CatalogWithFilters(ActionCatalogWithFilters),
     CatalogsWithExtra(ActionCatalogsWithExtra),
     MetaDetails(ActionMetaDetails),
+    RemoteAddons(ActionRemoteAddons),
     StreamingServer(ActionStreamingServer),
     Player(ActionPlayer),
     Load(ActionLoad),
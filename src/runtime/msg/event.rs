@@ -1,6 +1,8 @@
 use crate::models::ctx::CtxError;
 use crate::types::api::AuthRequest;
+use crate::types::library::{LibraryImportRowReport, LibrarySyncProgress};
 use crate::types::profile::{AuthKey, Settings, UID};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use url::Url;
 
@@ -20,15 +22,34 @@ pub enum Event {
     LibrarySyncWithAPIPlanned { plan: (Vec<String>, Vec<String>) },
     LibraryItemsPushedToAPI { ids: Vec<String> },
     LibraryItemsPulledFromAPI { ids: Vec<String> },
+    LibraryItemsConflictsMerged { ids: Vec<String> },
+    LibrarySyncProgressed { progress: LibrarySyncProgress },
     UserAuthenticated { auth_request: AuthRequest },
     UserLoggedOut { uid: UID },
     SessionDeleted { auth_key: AuthKey },
     AddonInstalled { transport_url: Url, id: String },
     AddonUpgraded { transport_url: Url, id: String },
     AddonUninstalled { transport_url: Url, id: String },
+    AddonRestored { transport_url: Url, id: String },
+    M3UPlaylistImported { name: String },
+    M3UPlaylistRemovedFromStorage { id: String },
     SettingsUpdated { settings: Settings },
     LibraryItemAdded { id: String },
     LibraryItemRemoved { id: String },
     LibraryItemRewinded { id: String },
+    LibraryItemRestored { id: String },
+    LibraryImported { report: Vec<LibraryImportRowReport> },
+    ListsPushedToStorage { ids: Vec<String> },
+    ListsSyncWithAPIPlanned { plan: (Vec<String>, Vec<String>) },
+    ListsPushedToAPI { ids: Vec<String> },
+    ListsPulledFromAPI { ids: Vec<String> },
+    ListCreated { id: String },
+    ListRenamed { id: String },
+    ListDeleted { id: String },
+    ListItemAdded { id: String, item_id: String },
+    ListItemRemoved { id: String, item_id: String },
+    HistoryPushedToStorage { uid: UID },
+    LibraryVideosPushedToStorage { uid: UID },
+    HistoryEntryRemoved { id: String, start: DateTime<Utc> },
     Error { error: CtxError, source: Box<Event> },
 }
@@ -2,11 +2,14 @@ use crate::models::ctx::CtxError;
 use crate::models::link::LinkError;
 use crate::models::streaming_server::Settings as StreamingServerSettings;
 use crate::runtime::EnvError;
-use crate::types::addon::{Descriptor, Manifest, ResourceRequest, ResourceResponse};
+use crate::types::addon::{Descriptor, Manifest, ResourceRequest, ResourceResponseWithMetadata};
 use crate::types::api::{
-    APIRequest, AuthRequest, DatastoreRequest, LinkCodeResponse, LinkDataResponse,
+    APIRequest, AuthRequest, DatastoreRequest, LibraryItemModified, LinkCodeResponse,
+    LinkDataResponse, SuccessResponse,
 };
-use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryVideosBucket};
+use crate::types::lists::{ListsBucket, UserList};
 use crate::types::profile::{Auth, Profile};
 use url::Url;
 
@@ -14,9 +17,12 @@ pub type CtxStorageResponse = (
     Option<Profile>,
     Option<LibraryBucket>,
     Option<LibraryBucket>,
+    Option<ListsBucket>,
+    Option<HistoryBucket>,
+    Option<LibraryVideosBucket>,
 );
 
-pub type AuthResponse = (Auth, Vec<Descriptor>, Vec<LibraryItem>);
+pub type AuthResponse = (Auth, Vec<Descriptor>, Vec<LibraryItemModified>);
 
 pub type LibraryPlanResponse = (Vec<String>, Vec<String>);
 
@@ -32,12 +38,43 @@ pub enum Internal {
     LibrarySyncPlanResult(DatastoreRequest, Result<LibraryPlanResponse, CtxError>),
     // Result for pull library items from API.
     LibraryPullResult(DatastoreRequest, Result<Vec<LibraryItem>, CtxError>),
+    // Result for push a batch of library items to API.
+    LibraryPushResult(DatastoreRequest, Result<SuccessResponse, CtxError>),
     // Dispatched when library item needs to be updated in the memory, storage and API.
     UpdateLibraryItem(LibraryItem),
+    // Result for lists sync plan with API.
+    ListsSyncPlanResult(
+        DatastoreRequest<UserList>,
+        Result<LibraryPlanResponse, CtxError>,
+    ),
+    // Result for pull lists from API.
+    ListsPullResult(DatastoreRequest<UserList>, Result<Vec<UserList>, CtxError>),
+    // Dispatched when a list needs to be updated in the memory, storage and API.
+    UpdateList(UserList),
+    // Dispatched when a play session needs to be recorded in the watch history.
+    RecordHistoryEntry(HistoryEntry),
+    // Dispatched when an uninstalled addon is restored at its previous position.
+    RestoreAddon {
+        addon: Descriptor,
+        position: usize,
+    },
+    // Dispatched when the user selects the subtitles language of a series manually.
+    UpdateSeriesSubtitles {
+        meta_id: String,
+        language: Option<String>,
+    },
+    // Result for storing an imported M3U playlist, installed as the given addon.
+    M3UPlaylistStored(Descriptor, Result<(), EnvError>),
     // Dispatched when some of auth, addons or settings changed.
     ProfileChanged,
     // Dispatched when library changes with a flag if its already persisted.
     LibraryChanged(bool),
+    // Dispatched when lists change with a flag if they are already persisted.
+    ListsChanged(bool),
+    // Dispatched when watch history changes with a flag if its already persisted.
+    HistoryChanged(bool),
+    // Dispatched when the cached library videos change with a flag if they are already persisted.
+    LibraryVideosChanged(bool),
     // Result for loading link code.
     LinkCodeResult(Result<LinkCodeResponse, LinkError>),
     // Result for loading link data.
@@ -49,7 +86,12 @@ pub enum Internal {
     // Result for updating streaming server settings.
     StreamingServerUpdateSettingsResult(Url, Result<(), EnvError>),
     // Result for fetching resource from addons.
-    ResourceRequestResult(ResourceRequest, Box<Result<ResourceResponse, EnvError>>),
+    ResourceRequestResult(
+        ResourceRequest,
+        Box<Result<ResourceResponseWithMetadata, EnvError>>,
+    ),
+    // Result for computing the OpenSubtitles hash of the video at the given url.
+    VideoHashResult(Url, Result<String, EnvError>),
     // Result for fetching manifest from addon.
     ManifestRequestResult(Url, Result<Manifest, EnvError>),
 }
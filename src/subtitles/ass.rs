@@ -0,0 +1,84 @@
+use crate::subtitles::subtitles_track::parse_timestamp;
+use crate::subtitles::SubtitlesCue;
+
+const DEFAULT_FORMAT: &[&str] = &[
+    "layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text",
+];
+
+pub(super) fn parse(text: &str) -> Vec<SubtitlesCue> {
+    let mut format = DEFAULT_FORMAT
+        .iter()
+        .map(|field| field.to_string())
+        .collect::<Vec<_>>();
+    let mut is_events = false;
+    let mut cues = vec![];
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            is_events = line.eq_ignore_ascii_case("[events]");
+        } else if !is_events {
+            continue;
+        } else if let Some(fields) = line.strip_prefix("Format:") {
+            format = fields
+                .split(',')
+                .map(|field| field.trim().to_lowercase())
+                .collect();
+        } else if let Some(values) = line.strip_prefix("Dialogue:") {
+            // The text is the last field and it may contain commas
+            let values = values
+                .trim_start()
+                .splitn(format.len(), ',')
+                .collect::<Vec<_>>();
+            let value = |name: &str| {
+                format
+                    .iter()
+                    .position(|field| field == name)
+                    .and_then(|position| values.get(position))
+            };
+            let cue = value("start")
+                .and_then(|start| parse_timestamp(start))
+                .zip(value("end").and_then(|end| parse_timestamp(end)))
+                .zip(value("text"))
+                .map(|((start, end), text)| SubtitlesCue {
+                    start,
+                    end,
+                    text: convert_text(text),
+                });
+            cues.extend(cue);
+        };
+    }
+    cues
+}
+
+/// Converts the italic, bold and underline override tags and drops all other overrides
+fn convert_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+        for tag in rest[start + 1..end].split('\\') {
+            let html = match tag {
+                "i1" => "<i>",
+                "i0" => "</i>",
+                "b1" => "<b>",
+                "b0" => "</b>",
+                "u1" => "<u>",
+                "u0" => "</u>",
+                _ => continue,
+            };
+            result.push_str(html);
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+        .replace("\\N", "\n")
+        .replace("\\n", "\n")
+        .replace("\\h", "\u{a0}")
+}
@@ -0,0 +1,21 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+/// Decodes a subtitles file into text.
+///
+/// The encoding is taken from the byte order mark when there is one.
+/// Otherwise UTF-8 is assumed when the bytes are valid UTF-8
+/// and the legacy encoding is guessed from the content as a last resort.
+pub fn decode_subtitles(bytes: &[u8]) -> String {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        }
+    };
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
@@ -0,0 +1,12 @@
+mod ass;
+mod srt;
+mod vtt;
+
+mod encoding;
+pub use encoding::*;
+
+mod subtitles_track;
+pub use subtitles_track::*;
+
+mod video_hash;
+pub use video_hash::*;
@@ -0,0 +1,47 @@
+use crate::subtitles::subtitles_track::{blocks, parse_timestamp};
+use crate::subtitles::SubtitlesCue;
+
+pub(super) fn parse(text: &str) -> Vec<SubtitlesCue> {
+    blocks(text)
+        .into_iter()
+        .filter_map(|block| {
+            let mut lines = block.into_iter().skip_while(|line| !line.contains("-->"));
+            let (start, end) = parse_timing(lines.next()?)?;
+            let text = lines
+                .map(|line| strip_font_tags(line.trim_end()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(SubtitlesCue { start, end, text })
+        })
+        .collect()
+}
+
+/// Parses `00:00:01,000 --> 00:00:02,000`, ignoring the legacy `X1:` position coordinates
+pub(super) fn parse_timing(line: &str) -> Option<(u64, u64)> {
+    let (start, end) = line.split_once("-->")?;
+    let end = end.split_whitespace().next()?;
+    Some((parse_timestamp(start)?, parse_timestamp(end)?))
+}
+
+/// `<font>` tags are not supported by WebVTT, their content is kept
+fn strip_font_tags(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('<') {
+        result.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let is_font = matches!(
+            tag.get(1..6),
+            Some(name) if name.eq_ignore_ascii_case("font ") || name.eq_ignore_ascii_case("font>")
+        ) || matches!(tag.get(1..7), Some(name) if name.eq_ignore_ascii_case("/font>"));
+        match tag.find('>') {
+            Some(end) if is_font => rest = &tag[end + 1..],
+            _ => {
+                result.push('<');
+                rest = &tag[1..];
+            }
+        };
+    }
+    result.push_str(rest);
+    result
+}
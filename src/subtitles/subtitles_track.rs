@@ -0,0 +1,180 @@
+use crate::subtitles::{ass, decode_subtitles, srt, vtt};
+use boolinator::Boolinator;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum SubtitlesFormat {
+    Srt,
+    WebVtt,
+    Ass,
+}
+
+impl SubtitlesFormat {
+    /// Detects the format from the content of the file
+    pub fn detect(text: &str) -> Option<Self> {
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with("WEBVTT") {
+            Some(SubtitlesFormat::WebVtt)
+        } else if text.contains("[Script Info]") || text.contains("[Events]") {
+            Some(SubtitlesFormat::Ass)
+        } else if text.contains("-->") {
+            Some(SubtitlesFormat::Srt)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum SubtitlesError {
+    UnknownFormat,
+    NoCues,
+}
+
+impl fmt::Display for SubtitlesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubtitlesError::UnknownFormat => write!(f, "Unknown subtitles format"),
+            SubtitlesError::NoCues => write!(f, "No subtitles cues found"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct SubtitlesCue {
+    /// Start time in milliseconds
+    pub start: u64,
+    /// End time in milliseconds
+    pub end: u64,
+    /// Lines of the cue, `<b>`, `<i>` and `<u>` tags are kept
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct SubtitlesTrack {
+    pub cues: Vec<SubtitlesCue>,
+}
+
+impl SubtitlesTrack {
+    /// Decodes and parses a subtitles file of any of the supported formats
+    pub fn parse(bytes: &[u8]) -> Result<Self, SubtitlesError> {
+        let text = decode_subtitles(bytes);
+        let format = SubtitlesFormat::detect(&text).ok_or(SubtitlesError::UnknownFormat)?;
+        SubtitlesTrack::parse_str(&text, format)
+    }
+    /// Parses subtitles text of the given format.
+    ///
+    /// Malformed cues are skipped, the parsing fails only when no cue is found.
+    pub fn parse_str(text: &str, format: SubtitlesFormat) -> Result<Self, SubtitlesError> {
+        let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        let mut cues = match format {
+            SubtitlesFormat::Srt => srt::parse(&text),
+            SubtitlesFormat::WebVtt => vtt::parse(&text),
+            SubtitlesFormat::Ass => ass::parse(&text),
+        };
+        if cues.is_empty() {
+            return Err(SubtitlesError::NoCues);
+        };
+        cues.sort_by_key(|cue| cue.start);
+        Ok(SubtitlesTrack { cues })
+    }
+    /// Shifts all cues by the given offset in milliseconds.
+    ///
+    /// Cues which end before the start of the video are removed.
+    pub fn offset(&mut self, offset: i64) {
+        let shift = |time: u64| (time as i64).saturating_add(offset).max(0) as u64;
+        self.cues.iter_mut().for_each(|cue| {
+            cue.start = shift(cue.start);
+            cue.end = shift(cue.end);
+        });
+        self.cues.retain(|cue| cue.end > 0);
+    }
+    /// Retimes cues made for a video with `from` frame rate to a video with `to` frame rate,
+    /// like subtitles for a 23.976 fps release played with a 25 fps release.
+    pub fn convert_frame_rate(&mut self, from: f64, to: f64) {
+        if from <= 0.0 || to <= 0.0 {
+            return;
+        };
+        let ratio = from / to;
+        let convert = |time: u64| (time as f64 * ratio).round() as u64;
+        self.cues.iter_mut().for_each(|cue| {
+            cue.start = convert(cue.start);
+            cue.end = convert(cue.end);
+        });
+    }
+    pub fn to_vtt(&self) -> String {
+        let cues = self
+            .cues
+            .iter()
+            .map(|cue| {
+                format!(
+                    "{} --> {}\n{}\n",
+                    format_timestamp(cue.start),
+                    format_timestamp(cue.end),
+                    vtt::escape_text(&cue.text)
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("WEBVTT\n\n{}", cues.join("\n"))
+    }
+}
+
+/// Splits the text into blocks of lines, separated by lines which are empty or contain only whitespace
+pub(super) fn blocks(text: &str) -> Vec<Vec<&str>> {
+    text.lines()
+        .collect::<Vec<_>>()
+        .split(|line| line.trim().is_empty())
+        .filter(|block| !block.is_empty())
+        .map(<[_]>::to_vec)
+        .collect()
+}
+
+/// Parses `hh:mm:ss.ttt`, `mm:ss.ttt` and the SRT (`,`) and ASS (centiseconds) variants into milliseconds
+pub(super) fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.trim();
+    let (time, fraction) = match timestamp.find(['.', ',']) {
+        Some(position) => (&timestamp[..position], &timestamp[position + 1..]),
+        None => (timestamp, ""),
+    };
+    if !fraction.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    };
+    let milliseconds = format!("{:0<3}", fraction.get(..3).unwrap_or(fraction))
+        .parse::<u64>()
+        .ok()?;
+    let parts = time
+        .split(':')
+        .map(|part| {
+            part.chars()
+                .all(|char| char.is_ascii_digit())
+                .as_option()
+                .and_then(|_| part.parse::<u64>().ok())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    // Timestamps come from addon supplied files, so the overflows are checked
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        [minutes, seconds] => (0, *minutes, *seconds),
+        _ => return None,
+    };
+    hours
+        .checked_mul(3600)?
+        .checked_add(minutes.checked_mul(60)?)?
+        .checked_add(seconds)?
+        .checked_mul(1000)?
+        .checked_add(milliseconds)
+}
+
+fn format_timestamp(time: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        time / 3_600_000,
+        time / 60_000 % 60,
+        time / 1000 % 60,
+        time % 1000
+    )
+}
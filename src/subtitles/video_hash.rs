@@ -0,0 +1,84 @@
+use crate::constants::VIDEO_HASH_CHUNK_SIZE;
+use crate::runtime::{Env, EnvError, EnvFutureExt, FileChunk, TryEnvFuture};
+use crate::types::resource::{Stream, StreamSource};
+use futures::{future, TryFutureExt};
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+/// The OpenSubtitles hash of a video: the file size plus the sums of the 64-bit little endian
+/// words of the first and the last 64 KiB, formatted as 16 hex digits.
+///
+/// `head` and `tail` are expected to be the first and the last `VIDEO_HASH_CHUNK_SIZE` bytes,
+/// or the whole file when it is smaller than that.
+pub fn video_hash(file_size: u64, head: &[u8], tail: &[u8]) -> String {
+    let checksum = |bytes: &[u8]| {
+        bytes.chunks(8).fold(0u64, |checksum, word| {
+            let mut padded = [0; 8];
+            padded[..word.len()].copy_from_slice(word);
+            checksum.wrapping_add(u64::from_le_bytes(padded))
+        })
+    };
+    format!(
+        "{:016x}",
+        file_size
+            .wrapping_add(checksum(head))
+            .wrapping_add(checksum(tail))
+    )
+}
+
+/// Hashes a local file through `Env::read_file`
+pub fn local_video_hash<E: Env + 'static>(path: &str) -> TryEnvFuture<String> {
+    let path = path.to_owned();
+    E::read_file(&path, 0, VIDEO_HASH_CHUNK_SIZE)
+        .and_then(move |head| {
+            let tail_offset = head.file_size.saturating_sub(VIDEO_HASH_CHUNK_SIZE);
+            E::read_file(&path, tail_offset, VIDEO_HASH_CHUNK_SIZE).map_ok(move |tail| (head, tail))
+        })
+        .map_ok(|(head, tail): (FileChunk, FileChunk)| {
+            video_hash(head.file_size, &head.bytes, &tail.bytes)
+        })
+        .boxed_env()
+}
+
+/// Hashes a remote file with two range requests through `Env::fetch_range`
+pub fn remote_video_hash<E: Env + 'static>(url: &Url) -> TryEnvFuture<String> {
+    let url = url.to_owned();
+    fetch_range::<E>(&url, 0, VIDEO_HASH_CHUNK_SIZE)
+        .and_then(move |head| {
+            let tail_offset = head.file_size.saturating_sub(VIDEO_HASH_CHUNK_SIZE);
+            fetch_range::<E>(&url, tail_offset, VIDEO_HASH_CHUNK_SIZE)
+                .map_ok(move |tail| (head, tail))
+        })
+        .map_ok(|(head, tail)| video_hash(head.file_size, &head.bytes, &tail.bytes))
+        .boxed_env()
+}
+
+/// Hashes the video of a local file stream or of a url stream.
+/// Returns `None` for streams which can not be hashed.
+pub fn stream_video_hash<E: Env + 'static>(stream: &Stream) -> Option<TryEnvFuture<String>> {
+    match &stream.source {
+        StreamSource::Url { url } if url.scheme() == "file" => Some(local_video_hash::<E>(
+            &percent_decode_str(url.path()).decode_utf8_lossy(),
+        )),
+        StreamSource::Url { url } if matches!(url.scheme(), "http" | "https") => {
+            Some(remote_video_hash::<E>(url))
+        }
+        _ => None,
+    }
+}
+
+/// Environments are expected to reject responses which ignore the range,
+/// a chunk larger than requested is rejected here as well
+fn fetch_range<E: Env + 'static>(url: &Url, offset: u64, length: u64) -> TryEnvFuture<FileChunk> {
+    E::fetch_range(url, offset, length)
+        .and_then(move |chunk| {
+            if chunk.bytes.len() as u64 <= length {
+                future::ok(chunk)
+            } else {
+                future::err(EnvError::Fetch(
+                    "Range request responded with more than the range".to_owned(),
+                ))
+            }
+        })
+        .boxed_env()
+}
@@ -0,0 +1,46 @@
+use crate::subtitles::srt::parse_timing;
+use crate::subtitles::subtitles_track::blocks;
+use crate::subtitles::SubtitlesCue;
+
+pub(super) fn parse(text: &str) -> Vec<SubtitlesCue> {
+    blocks(text)
+        .into_iter()
+        .filter_map(|block| {
+            // The header, NOTE, STYLE and REGION blocks have no timing line
+            let mut lines = block.into_iter().skip_while(|line| !line.contains("-->"));
+            let (start, end) = parse_timing(lines.next()?)?;
+            let text = lines
+                .map(|line| unescape_text(line.trim_end()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(SubtitlesCue { start, end, text })
+        })
+        .collect()
+}
+
+fn unescape_text(line: &str) -> String {
+    line.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&")
+}
+
+/// Escapes everything except the `<b>`, `<i>` and `<u>` tags.
+///
+/// Empty lines are dropped as they would end the cue.
+pub(super) fn escape_text(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let line = line
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            ["b", "i", "u"].iter().fold(line, |line, tag| {
+                line.replace(&format!("&lt;{}&gt;", tag), &format!("<{}>", tag))
+                    .replace(&format!("&lt;/{}&gt;", tag), &format!("</{}>", tag))
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
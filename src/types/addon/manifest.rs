@@ -91,6 +91,8 @@ pub struct ManifestPreview {
     pub logo: Option<String>,
     pub background: Option<String>,
     pub types: Vec<String>,
+    #[serde(default)]
+    pub behavior_hints: ManifestBehaviorHints,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -2,6 +2,7 @@ use crate::types::addon::DescriptorPreview;
 use crate::types::resource::{MetaItem, MetaItemPreview, Stream, Subtitles};
 use derive_more::TryInto;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
 #[derive(Clone, TryInto, Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
@@ -28,3 +29,66 @@ pub enum ResourceResponse {
         addons: Vec<DescriptorPreview>,
     },
 }
+
+/// Hints which addons may send alongside the content of a resource response.
+///
+/// The caching hints are in seconds. The http transport leaves honoring them to the
+/// `Cache-Control` headers which the addon sdk derives from the same values.
+#[derive(Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceResponseMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale_revalidate: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale_error: Option<u64>,
+    /// Id of the item which should be requested instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(try_from = "ResourceResponseWithMetadataDeserialize")]
+pub struct ResourceResponseWithMetadata {
+    /// Missing only when the response is a redirect
+    #[serde(flatten)]
+    pub content: Option<ResourceResponse>,
+    #[serde(flatten)]
+    pub metadata: ResourceResponseMetadata,
+}
+
+impl From<ResourceResponse> for ResourceResponseWithMetadata {
+    fn from(content: ResourceResponse) -> Self {
+        ResourceResponseWithMetadata {
+            content: Some(content),
+            metadata: ResourceResponseMetadata::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ResourceResponseWithMetadataDeserialize {
+    #[serde(flatten)]
+    content: Option<ResourceResponse>,
+    #[serde(flatten)]
+    metadata: ResourceResponseMetadata,
+}
+
+impl TryFrom<ResourceResponseWithMetadataDeserialize> for ResourceResponseWithMetadata {
+    type Error = &'static str;
+    fn try_from(response: ResourceResponseWithMetadataDeserialize) -> Result<Self, Self::Error> {
+        match response {
+            ResourceResponseWithMetadataDeserialize {
+                content: None,
+                metadata: ResourceResponseMetadata { redirect: None, .. },
+            } => Err("data did not match any variant of ResourceResponse"),
+            ResourceResponseWithMetadataDeserialize { content, metadata } => {
+                Ok(ResourceResponseWithMetadata { content, metadata })
+            }
+        }
+    }
+}
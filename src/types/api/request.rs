@@ -141,14 +141,14 @@ pub struct GDPRConsentRequest {
 #[derive(Clone, PartialEq, Serialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[serde(rename_all = "camelCase")]
-pub struct DatastoreRequest {
+pub struct DatastoreRequest<T = LibraryItem> {
     pub auth_key: AuthKey,
     pub collection: String,
     #[serde(flatten)]
-    pub command: DatastoreCommand,
+    pub command: DatastoreCommand<T>,
 }
 
-impl FetchRequestParams<DatastoreRequest> for DatastoreRequest {
+impl<T> FetchRequestParams<DatastoreRequest<T>> for DatastoreRequest<T> {
     fn endpoint(&self) -> Url {
         API_URL.to_owned()
     }
@@ -165,17 +165,18 @@ impl FetchRequestParams<DatastoreRequest> for DatastoreRequest {
     fn query(&self) -> Option<String> {
         None
     }
-    fn body(self) -> DatastoreRequest {
+    fn body(self) -> DatastoreRequest<T> {
         self
     }
 }
 
+/// `T` is the type of the items in the collection
 #[derive(Clone, PartialEq, Serialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[cfg_attr(test, derive(Derivative))]
-#[cfg_attr(test, derivative(Default))]
+#[cfg_attr(test, derivative(Default(bound = "")))]
 #[serde(untagged)]
-pub enum DatastoreCommand {
+pub enum DatastoreCommand<T = LibraryItem> {
     #[cfg_attr(test, derivative(Default))]
     Meta,
     Get {
@@ -185,6 +186,6 @@ pub enum DatastoreCommand {
     },
     Put {
         #[serde(default)]
-        changes: Vec<LibraryItem>,
+        changes: Vec<T>,
     },
 }
@@ -0,0 +1,54 @@
+use crate::constants::HISTORY_MAX_ENTRIES;
+use crate::types::history::HistoryEntry;
+use crate::types::profile::UID;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The watch history of a user, oldest session first.
+/// Only the latest `HISTORY_MAX_ENTRIES` sessions are retained.
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct HistoryBucket {
+    pub uid: UID,
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryBucket {
+    pub fn new(uid: UID, entries: Vec<HistoryEntry>) -> Self {
+        let mut bucket = HistoryBucket {
+            uid,
+            entries: vec![],
+        };
+        for entry in entries.into_iter() {
+            bucket.record(entry);
+        }
+        bucket
+    }
+    /// Appends a session or replaces it if it was already recorded
+    pub fn record(&mut self, entry: HistoryEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|recorded| recorded.is_session(&entry.id, &entry.start))
+        {
+            Some(recorded) => *recorded = entry,
+            _ => {
+                let position = self
+                    .entries
+                    .partition_point(|recorded| recorded.start <= entry.start);
+                self.entries.insert(position, entry);
+            }
+        };
+        if self.entries.len() > HISTORY_MAX_ENTRIES {
+            let overflow = self.entries.len() - HISTORY_MAX_ENTRIES;
+            self.entries.drain(..overflow);
+        };
+    }
+    pub fn remove(&mut self, id: &str, start: &DateTime<Utc>) -> Option<HistoryEntry> {
+        self.entries
+            .iter()
+            .position(|entry| entry.is_session(id, start))
+            .map(|position| self.entries.remove(position))
+    }
+}
@@ -0,0 +1,37 @@
+use crate::runtime::Env;
+use crate::types::library::LibraryItem;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A single play session of a video, recorded by the player.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct HistoryEntry {
+    pub id: String,
+    pub r#type: String,
+    pub name: String,
+    pub video_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time watched in milliseconds, excluding pauses and seeks
+    pub duration: u64,
+}
+
+impl HistoryEntry {
+    pub fn new<E: Env + 'static>(library_item: &LibraryItem, video_id: &str) -> Self {
+        HistoryEntry {
+            id: library_item.id.to_owned(),
+            r#type: library_item.r#type.to_owned(),
+            name: library_item.name.to_owned(),
+            video_id: video_id.to_owned(),
+            start: E::now(),
+            end: E::now(),
+            duration: 0,
+        }
+    }
+    /// Both the meta item and the start of the session are needed to identify an entry
+    pub fn is_session(&self, id: &str, start: &DateTime<Utc>) -> bool {
+        self.id == id && self.start == *start
+    }
+}
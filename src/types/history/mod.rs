@@ -0,0 +1,5 @@
+mod history_bucket;
+pub use history_bucket::*;
+
+mod history_entry;
+pub use history_entry::*;
@@ -0,0 +1,734 @@
+use serde::Serialize;
+
+/// An ISO 639 language.
+///
+/// The canonical code is the ISO 639-2/B code, as used in the settings and by most addons.
+#[derive(PartialEq, Eq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct Language {
+    /// ISO 639-2/B code
+    pub code: &'static str,
+    /// ISO 639-2/T code, when it differs from the bibliographic one
+    #[serde(skip)]
+    pub terminology_code: Option<&'static str>,
+    /// ISO 639-1 code
+    #[serde(skip)]
+    pub alpha2: Option<&'static str>,
+    /// English display name
+    pub name: &'static str,
+    /// Deprecated and non standard codes and names used by addons
+    #[serde(skip)]
+    pub aliases: &'static [&'static str],
+}
+
+pub static LANGUAGES: &[Language] = &[
+    Language {
+        code: "alb",
+        terminology_code: Some("sqi"),
+        alpha2: Some("sq"),
+        name: "Albanian",
+        aliases: &[],
+    },
+    Language {
+        code: "afr",
+        terminology_code: None,
+        alpha2: Some("af"),
+        name: "Afrikaans",
+        aliases: &[],
+    },
+    Language {
+        code: "amh",
+        terminology_code: None,
+        alpha2: Some("am"),
+        name: "Amharic",
+        aliases: &[],
+    },
+    Language {
+        code: "ara",
+        terminology_code: None,
+        alpha2: Some("ar"),
+        name: "Arabic",
+        aliases: &[],
+    },
+    Language {
+        code: "arm",
+        terminology_code: Some("hye"),
+        alpha2: Some("hy"),
+        name: "Armenian",
+        aliases: &[],
+    },
+    Language {
+        code: "aze",
+        terminology_code: None,
+        alpha2: Some("az"),
+        name: "Azerbaijani",
+        aliases: &[],
+    },
+    Language {
+        code: "baq",
+        terminology_code: Some("eus"),
+        alpha2: Some("eu"),
+        name: "Basque",
+        aliases: &[],
+    },
+    Language {
+        code: "bel",
+        terminology_code: None,
+        alpha2: Some("be"),
+        name: "Belarusian",
+        aliases: &[],
+    },
+    Language {
+        code: "ben",
+        terminology_code: None,
+        alpha2: Some("bn"),
+        name: "Bengali",
+        aliases: &[],
+    },
+    Language {
+        code: "bos",
+        terminology_code: None,
+        alpha2: Some("bs"),
+        name: "Bosnian",
+        aliases: &[],
+    },
+    Language {
+        code: "bre",
+        terminology_code: None,
+        alpha2: Some("br"),
+        name: "Breton",
+        aliases: &[],
+    },
+    Language {
+        code: "bul",
+        terminology_code: None,
+        alpha2: Some("bg"),
+        name: "Bulgarian",
+        aliases: &[],
+    },
+    Language {
+        code: "bur",
+        terminology_code: Some("mya"),
+        alpha2: Some("my"),
+        name: "Burmese",
+        aliases: &[],
+    },
+    Language {
+        code: "cat",
+        terminology_code: None,
+        alpha2: Some("ca"),
+        name: "Catalan",
+        aliases: &[],
+    },
+    Language {
+        code: "chi",
+        terminology_code: Some("zho"),
+        alpha2: Some("zh"),
+        name: "Chinese",
+        aliases: &["zht", "zhs", "zhe", "chs", "cht"],
+    },
+    Language {
+        code: "hrv",
+        terminology_code: None,
+        alpha2: Some("hr"),
+        name: "Croatian",
+        aliases: &["scr"],
+    },
+    Language {
+        code: "cze",
+        terminology_code: Some("ces"),
+        alpha2: Some("cs"),
+        name: "Czech",
+        aliases: &[],
+    },
+    Language {
+        code: "dan",
+        terminology_code: None,
+        alpha2: Some("da"),
+        name: "Danish",
+        aliases: &[],
+    },
+    Language {
+        code: "dut",
+        terminology_code: Some("nld"),
+        alpha2: Some("nl"),
+        name: "Dutch",
+        aliases: &["flemish"],
+    },
+    Language {
+        code: "eng",
+        terminology_code: None,
+        alpha2: Some("en"),
+        name: "English",
+        aliases: &[],
+    },
+    Language {
+        code: "epo",
+        terminology_code: None,
+        alpha2: Some("eo"),
+        name: "Esperanto",
+        aliases: &[],
+    },
+    Language {
+        code: "est",
+        terminology_code: None,
+        alpha2: Some("et"),
+        name: "Estonian",
+        aliases: &[],
+    },
+    Language {
+        code: "fao",
+        terminology_code: None,
+        alpha2: Some("fo"),
+        name: "Faroese",
+        aliases: &[],
+    },
+    Language {
+        code: "fil",
+        terminology_code: None,
+        alpha2: None,
+        name: "Filipino",
+        aliases: &[],
+    },
+    Language {
+        code: "fin",
+        terminology_code: None,
+        alpha2: Some("fi"),
+        name: "Finnish",
+        aliases: &[],
+    },
+    Language {
+        code: "fre",
+        terminology_code: Some("fra"),
+        alpha2: Some("fr"),
+        name: "French",
+        aliases: &[],
+    },
+    Language {
+        code: "geo",
+        terminology_code: Some("kat"),
+        alpha2: Some("ka"),
+        name: "Georgian",
+        aliases: &[],
+    },
+    Language {
+        code: "ger",
+        terminology_code: Some("deu"),
+        alpha2: Some("de"),
+        name: "German",
+        aliases: &[],
+    },
+    Language {
+        code: "gla",
+        terminology_code: None,
+        alpha2: Some("gd"),
+        name: "Gaelic",
+        aliases: &[],
+    },
+    Language {
+        code: "gle",
+        terminology_code: None,
+        alpha2: Some("ga"),
+        name: "Irish",
+        aliases: &[],
+    },
+    Language {
+        code: "glg",
+        terminology_code: None,
+        alpha2: Some("gl"),
+        name: "Galician",
+        aliases: &[],
+    },
+    Language {
+        code: "gre",
+        terminology_code: Some("ell"),
+        alpha2: Some("el"),
+        name: "Greek",
+        aliases: &[],
+    },
+    Language {
+        code: "guj",
+        terminology_code: None,
+        alpha2: Some("gu"),
+        name: "Gujarati",
+        aliases: &[],
+    },
+    Language {
+        code: "heb",
+        terminology_code: None,
+        alpha2: Some("he"),
+        name: "Hebrew",
+        aliases: &["iw"],
+    },
+    Language {
+        code: "hin",
+        terminology_code: None,
+        alpha2: Some("hi"),
+        name: "Hindi",
+        aliases: &[],
+    },
+    Language {
+        code: "hun",
+        terminology_code: None,
+        alpha2: Some("hu"),
+        name: "Hungarian",
+        aliases: &[],
+    },
+    Language {
+        code: "ice",
+        terminology_code: Some("isl"),
+        alpha2: Some("is"),
+        name: "Icelandic",
+        aliases: &[],
+    },
+    Language {
+        code: "ind",
+        terminology_code: None,
+        alpha2: Some("id"),
+        name: "Indonesian",
+        aliases: &["in"],
+    },
+    Language {
+        code: "ita",
+        terminology_code: None,
+        alpha2: Some("it"),
+        name: "Italian",
+        aliases: &[],
+    },
+    Language {
+        code: "jpn",
+        terminology_code: None,
+        alpha2: Some("ja"),
+        name: "Japanese",
+        aliases: &[],
+    },
+    Language {
+        code: "kan",
+        terminology_code: None,
+        alpha2: Some("kn"),
+        name: "Kannada",
+        aliases: &[],
+    },
+    Language {
+        code: "kaz",
+        terminology_code: None,
+        alpha2: Some("kk"),
+        name: "Kazakh",
+        aliases: &[],
+    },
+    Language {
+        code: "khm",
+        terminology_code: None,
+        alpha2: Some("km"),
+        name: "Khmer",
+        aliases: &[],
+    },
+    Language {
+        code: "kir",
+        terminology_code: None,
+        alpha2: Some("ky"),
+        name: "Kyrgyz",
+        aliases: &[],
+    },
+    Language {
+        code: "kor",
+        terminology_code: None,
+        alpha2: Some("ko"),
+        name: "Korean",
+        aliases: &[],
+    },
+    Language {
+        code: "kur",
+        terminology_code: None,
+        alpha2: Some("ku"),
+        name: "Kurdish",
+        aliases: &[],
+    },
+    Language {
+        code: "lao",
+        terminology_code: None,
+        alpha2: Some("lo"),
+        name: "Lao",
+        aliases: &[],
+    },
+    Language {
+        code: "lat",
+        terminology_code: None,
+        alpha2: Some("la"),
+        name: "Latin",
+        aliases: &[],
+    },
+    Language {
+        code: "lav",
+        terminology_code: None,
+        alpha2: Some("lv"),
+        name: "Latvian",
+        aliases: &[],
+    },
+    Language {
+        code: "lit",
+        terminology_code: None,
+        alpha2: Some("lt"),
+        name: "Lithuanian",
+        aliases: &[],
+    },
+    Language {
+        code: "ltz",
+        terminology_code: None,
+        alpha2: Some("lb"),
+        name: "Luxembourgish",
+        aliases: &[],
+    },
+    Language {
+        code: "mac",
+        terminology_code: Some("mkd"),
+        alpha2: Some("mk"),
+        name: "Macedonian",
+        aliases: &[],
+    },
+    Language {
+        code: "may",
+        terminology_code: Some("msa"),
+        alpha2: Some("ms"),
+        name: "Malay",
+        aliases: &[],
+    },
+    Language {
+        code: "mal",
+        terminology_code: None,
+        alpha2: Some("ml"),
+        name: "Malayalam",
+        aliases: &[],
+    },
+    Language {
+        code: "mlt",
+        terminology_code: None,
+        alpha2: Some("mt"),
+        name: "Maltese",
+        aliases: &[],
+    },
+    Language {
+        code: "mar",
+        terminology_code: None,
+        alpha2: Some("mr"),
+        name: "Marathi",
+        aliases: &[],
+    },
+    Language {
+        code: "mon",
+        terminology_code: None,
+        alpha2: Some("mn"),
+        name: "Mongolian",
+        aliases: &[],
+    },
+    Language {
+        code: "nep",
+        terminology_code: None,
+        alpha2: Some("ne"),
+        name: "Nepali",
+        aliases: &[],
+    },
+    Language {
+        code: "nor",
+        terminology_code: None,
+        alpha2: Some("no"),
+        name: "Norwegian",
+        aliases: &[],
+    },
+    Language {
+        code: "nob",
+        terminology_code: None,
+        alpha2: Some("nb"),
+        name: "Norwegian Bokmål",
+        aliases: &[],
+    },
+    Language {
+        code: "nno",
+        terminology_code: None,
+        alpha2: Some("nn"),
+        name: "Norwegian Nynorsk",
+        aliases: &[],
+    },
+    Language {
+        code: "ori",
+        terminology_code: None,
+        alpha2: Some("or"),
+        name: "Oriya",
+        aliases: &[],
+    },
+    Language {
+        code: "pan",
+        terminology_code: None,
+        alpha2: Some("pa"),
+        name: "Punjabi",
+        aliases: &[],
+    },
+    Language {
+        code: "per",
+        terminology_code: Some("fas"),
+        alpha2: Some("fa"),
+        name: "Persian",
+        aliases: &["farsi"],
+    },
+    Language {
+        code: "pol",
+        terminology_code: None,
+        alpha2: Some("pl"),
+        name: "Polish",
+        aliases: &[],
+    },
+    Language {
+        code: "por",
+        terminology_code: None,
+        alpha2: Some("pt"),
+        name: "Portuguese",
+        aliases: &["pob", "pb"],
+    },
+    Language {
+        code: "pus",
+        terminology_code: None,
+        alpha2: Some("ps"),
+        name: "Pashto",
+        aliases: &[],
+    },
+    Language {
+        code: "rum",
+        terminology_code: Some("ron"),
+        alpha2: Some("ro"),
+        name: "Romanian",
+        aliases: &["mol", "moldavian", "moldovan"],
+    },
+    Language {
+        code: "rus",
+        terminology_code: None,
+        alpha2: Some("ru"),
+        name: "Russian",
+        aliases: &[],
+    },
+    Language {
+        code: "srp",
+        terminology_code: None,
+        alpha2: Some("sr"),
+        name: "Serbian",
+        aliases: &["scc"],
+    },
+    Language {
+        code: "sin",
+        terminology_code: None,
+        alpha2: Some("si"),
+        name: "Sinhala",
+        aliases: &["sinhalese"],
+    },
+    Language {
+        code: "slo",
+        terminology_code: Some("slk"),
+        alpha2: Some("sk"),
+        name: "Slovak",
+        aliases: &[],
+    },
+    Language {
+        code: "slv",
+        terminology_code: None,
+        alpha2: Some("sl"),
+        name: "Slovenian",
+        aliases: &["slovene"],
+    },
+    Language {
+        code: "snd",
+        terminology_code: None,
+        alpha2: Some("sd"),
+        name: "Sindhi",
+        aliases: &[],
+    },
+    Language {
+        code: "som",
+        terminology_code: None,
+        alpha2: Some("so"),
+        name: "Somali",
+        aliases: &[],
+    },
+    Language {
+        code: "spa",
+        terminology_code: None,
+        alpha2: Some("es"),
+        name: "Spanish",
+        aliases: &["castilian", "spn", "ea"],
+    },
+    Language {
+        code: "swa",
+        terminology_code: None,
+        alpha2: Some("sw"),
+        name: "Swahili",
+        aliases: &[],
+    },
+    Language {
+        code: "swe",
+        terminology_code: None,
+        alpha2: Some("sv"),
+        name: "Swedish",
+        aliases: &[],
+    },
+    Language {
+        code: "tam",
+        terminology_code: None,
+        alpha2: Some("ta"),
+        name: "Tamil",
+        aliases: &[],
+    },
+    Language {
+        code: "tat",
+        terminology_code: None,
+        alpha2: Some("tt"),
+        name: "Tatar",
+        aliases: &[],
+    },
+    Language {
+        code: "tel",
+        terminology_code: None,
+        alpha2: Some("te"),
+        name: "Telugu",
+        aliases: &[],
+    },
+    Language {
+        code: "tgk",
+        terminology_code: None,
+        alpha2: Some("tg"),
+        name: "Tajik",
+        aliases: &[],
+    },
+    Language {
+        code: "tgl",
+        terminology_code: None,
+        alpha2: Some("tl"),
+        name: "Tagalog",
+        aliases: &[],
+    },
+    Language {
+        code: "tha",
+        terminology_code: None,
+        alpha2: Some("th"),
+        name: "Thai",
+        aliases: &[],
+    },
+    Language {
+        code: "tib",
+        terminology_code: Some("bod"),
+        alpha2: Some("bo"),
+        name: "Tibetan",
+        aliases: &[],
+    },
+    Language {
+        code: "tuk",
+        terminology_code: None,
+        alpha2: Some("tk"),
+        name: "Turkmen",
+        aliases: &[],
+    },
+    Language {
+        code: "tur",
+        terminology_code: None,
+        alpha2: Some("tr"),
+        name: "Turkish",
+        aliases: &[],
+    },
+    Language {
+        code: "ukr",
+        terminology_code: None,
+        alpha2: Some("uk"),
+        name: "Ukrainian",
+        aliases: &[],
+    },
+    Language {
+        code: "urd",
+        terminology_code: None,
+        alpha2: Some("ur"),
+        name: "Urdu",
+        aliases: &[],
+    },
+    Language {
+        code: "uzb",
+        terminology_code: None,
+        alpha2: Some("uz"),
+        name: "Uzbek",
+        aliases: &[],
+    },
+    Language {
+        code: "vie",
+        terminology_code: None,
+        alpha2: Some("vi"),
+        name: "Vietnamese",
+        aliases: &[],
+    },
+    Language {
+        code: "wel",
+        terminology_code: Some("cym"),
+        alpha2: Some("cy"),
+        name: "Welsh",
+        aliases: &[],
+    },
+    Language {
+        code: "yid",
+        terminology_code: None,
+        alpha2: Some("yi"),
+        name: "Yiddish",
+        aliases: &[],
+    },
+    Language {
+        code: "yor",
+        terminology_code: None,
+        alpha2: Some("yo"),
+        name: "Yoruba",
+        aliases: &[],
+    },
+    Language {
+        code: "zul",
+        terminology_code: None,
+        alpha2: Some("zu"),
+        name: "Zulu",
+        aliases: &[],
+    },
+];
+
+impl Language {
+    /// Finds a language by any of its ISO 639 codes or its English name, case insensitive.
+    ///
+    /// Region and script subtags are ignored, so `pt-BR`, `en_US` and `Chinese (Simplified)` are found as well.
+    pub fn find(value: &str) -> Option<&'static Language> {
+        let value = value.trim().to_lowercase();
+        find_exact(&value).or_else(|| {
+            value
+                .split(['-', '_', '(', ','])
+                .next()
+                .map(str::trim)
+                .filter(|base| *base != value)
+                .and_then(find_exact)
+        })
+    }
+}
+
+fn find_exact(value: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| {
+        language.code == value
+            || language.terminology_code == Some(value)
+            || language.alpha2 == Some(value)
+            || language.name.to_lowercase() == value
+            || language.aliases.contains(&value)
+    })
+}
+
+/// Returns the canonical code of the language or the value unchanged when the language is unknown
+pub fn normalize_language(value: &str) -> String {
+    Language::find(value)
+        .map(|language| language.code.to_owned())
+        .unwrap_or_else(|| value.to_owned())
+}
+
+/// Compares the canonical codes of the languages, unknown languages are compared as they are
+pub fn is_same_language(a: &str, b: &str) -> bool {
+    match (Language::find(a), Language::find(b)) {
+        (Some(a), Some(b)) => a.code == b.code,
+        (None, None) => a.trim().eq_ignore_ascii_case(b.trim()),
+        _ => false,
+    }
+}
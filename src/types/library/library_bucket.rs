@@ -28,19 +28,25 @@ impl LibraryBucket {
             self.merge_items(bucket.items.into_iter().map(|(_, item)| item).collect());
         };
     }
-    pub fn merge_items(&mut self, items: Vec<LibraryItem>) {
+    /// Merges the items field by field with the ones in the bucket.
+    /// Returns the ids of the items for which neither version was kept as it is.
+    pub fn merge_items(&mut self, items: Vec<LibraryItem>) -> Vec<String> {
+        let mut conflicted_ids = vec![];
         for new_item in items.into_iter() {
             match self.items.get_mut(&new_item.id) {
                 Some(item) => {
-                    if new_item.mtime > item.mtime {
-                        *item = new_item;
+                    let merged_item = item.merge(&new_item);
+                    if merged_item != *item && merged_item != new_item {
+                        conflicted_ids.push(new_item.id.to_owned());
                     }
+                    *item = merged_item;
                 }
                 None => {
                     self.items.insert(new_item.id.to_owned(), new_item);
                 }
             }
         }
+        conflicted_ids
     }
     pub fn are_ids_in_recent(&self, ids: &[String]) -> bool {
         let recent_item_ids = self
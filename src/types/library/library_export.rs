@@ -0,0 +1,429 @@
+use crate::types::library::{LibraryBucket, LibraryItem};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const JSON_EXPORT_VERSION: u32 = 1;
+const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "type",
+    "name",
+    "in_library",
+    "watched",
+    "last_watched",
+    "time_offset",
+    "duration",
+    "video_id",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub enum LibraryExportFormat {
+    /// `{ "version": 1, "items": [LibraryExportRow] }`
+    Json,
+    /// Trakt style `{ "watchlist": [..], "history": [..], "playback": [..] }` with movies and shows,
+    /// episodes are imported as the last watched video of their show
+    Trakt,
+    /// A header with the fields of `LibraryExportRow` followed by one line per item
+    Csv,
+}
+
+/// A library item as exported and imported. `time_offset` and `duration` are in milliseconds.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryExportRow {
+    pub id: String,
+    pub r#type: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub in_library: bool,
+    #[serde(default)]
+    pub watched: bool,
+    #[serde(default)]
+    pub last_watched: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub time_offset: u64,
+    #[serde(default)]
+    pub duration: u64,
+    /// The last watched video of a series
+    #[serde(default)]
+    pub video_id: Option<String>,
+    /// The playback progress in percent, for imports which have no duration
+    #[serde(skip)]
+    pub progress: Option<f64>,
+}
+
+impl From<&LibraryItem> for LibraryExportRow {
+    fn from(item: &LibraryItem) -> Self {
+        LibraryExportRow {
+            id: item.id.to_owned(),
+            r#type: item.r#type.to_owned(),
+            name: item.name.to_owned(),
+            in_library: !item.removed,
+            watched: item.state.flagged_watched > 0 || item.state.times_watched > 0,
+            last_watched: item.state.last_watched,
+            time_offset: item.state.time_offset,
+            duration: item.state.duration,
+            video_id: item.state.video_id.to_owned(),
+            progress: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(tag = "status", content = "reason")]
+pub enum LibraryImportStatus {
+    Added,
+    Updated,
+    Unchanged,
+    Invalid(String),
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LibraryImportRowReport {
+    pub row: usize,
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub status: LibraryImportStatus,
+}
+
+impl LibraryBucket {
+    /// Exports the items which are in the library or have been watched, most recent first
+    pub fn export(&self, format: LibraryExportFormat) -> String {
+        let rows = self
+            .items
+            .values()
+            .filter(|item| item.r#type != "other")
+            .filter(|item| !item.removed || item.state.last_watched.is_some())
+            .sorted_by(|a, b| b.mtime.cmp(&a.mtime).then_with(|| a.id.cmp(&b.id)))
+            .map(LibraryExportRow::from)
+            .collect::<Vec<_>>();
+        match format {
+            LibraryExportFormat::Json => json!({
+                "version": JSON_EXPORT_VERSION,
+                "items": rows,
+            })
+            .to_string(),
+            LibraryExportFormat::Trakt => trakt_export(&rows).to_string(),
+            LibraryExportFormat::Csv => csv_export(&rows),
+        }
+    }
+}
+
+impl LibraryExportRow {
+    /// Parses an export into rows, each one with its own error.
+    /// Fails only when the content as a whole is not in the given format.
+    pub fn parse_export(
+        format: LibraryExportFormat,
+        content: &str,
+    ) -> Result<Vec<Result<LibraryExportRow, String>>, String> {
+        match format {
+            LibraryExportFormat::Json => {
+                #[derive(Deserialize)]
+                struct JsonExport {
+                    items: Vec<Value>,
+                }
+                let export = serde_json::from_str::<JsonExport>(content)
+                    .map_err(|error| error.to_string())?;
+                Ok(export
+                    .items
+                    .into_iter()
+                    .map(|item| {
+                        serde_json::from_value::<LibraryExportRow>(item)
+                            .map_err(|error| error.to_string())
+                    })
+                    .collect())
+            }
+            LibraryExportFormat::Trakt => trakt_import(content),
+            LibraryExportFormat::Csv => csv_import(content),
+        }
+    }
+}
+
+fn trakt_type(r#type: &str) -> Option<&'static str> {
+    match r#type {
+        "movie" => Some("movie"),
+        "series" => Some("show"),
+        _ => None,
+    }
+}
+
+fn trakt_export(rows: &[LibraryExportRow]) -> Value {
+    let mut watchlist = vec![];
+    let mut history = vec![];
+    let mut playback = vec![];
+    for row in rows {
+        let trakt_type = match trakt_type(&row.r#type) {
+            Some(trakt_type) => trakt_type,
+            _ => continue,
+        };
+        let ids = if row.id.starts_with("tt") {
+            json!({ "imdb": row.id })
+        } else {
+            json!({ "stremio": row.id })
+        };
+        let media = json!({ "title": row.name, "ids": ids });
+        if row.in_library {
+            watchlist.push(json!({ "type": trakt_type, trakt_type: media }));
+        };
+        if row.watched {
+            history.push(json!({
+                "action": "watch",
+                "watched_at": row.last_watched,
+                "type": trakt_type,
+                trakt_type: media,
+            }));
+        };
+        if row.time_offset > 0 && row.duration > 0 {
+            playback.push(json!({
+                "progress": row.time_offset as f64 / row.duration as f64 * 100.0,
+                "duration": row.duration,
+                "paused_at": row.last_watched,
+                "type": trakt_type,
+                trakt_type: media,
+            }));
+        };
+    }
+    json!({
+        "watchlist": watchlist,
+        "history": history,
+        "playback": playback,
+    })
+}
+
+fn trakt_import(content: &str) -> Result<Vec<Result<LibraryExportRow, String>>, String> {
+    #[derive(Deserialize)]
+    struct TraktExport {
+        #[serde(default)]
+        watchlist: Vec<Value>,
+        #[serde(default)]
+        history: Vec<Value>,
+        #[serde(default)]
+        playback: Vec<Value>,
+    }
+    #[derive(Deserialize)]
+    struct TraktIds {
+        imdb: Option<String>,
+        stremio: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct TraktMedia {
+        title: Option<String>,
+        ids: TraktIds,
+    }
+    #[derive(Deserialize)]
+    struct TraktEpisode {
+        season: u32,
+        number: u32,
+    }
+    #[derive(Deserialize)]
+    struct TraktEntry {
+        r#type: String,
+        movie: Option<TraktMedia>,
+        show: Option<TraktMedia>,
+        episode: Option<TraktEpisode>,
+        watched_at: Option<DateTime<Utc>>,
+        paused_at: Option<DateTime<Utc>>,
+        progress: Option<f64>,
+        duration: Option<u64>,
+    }
+    let export = serde_json::from_str::<TraktExport>(content).map_err(|error| error.to_string())?;
+    let entries = export
+        .watchlist
+        .into_iter()
+        .map(|entry| ("watchlist", entry))
+        .chain(export.history.into_iter().map(|entry| ("history", entry)))
+        .chain(export.playback.into_iter().map(|entry| ("playback", entry)));
+    let mut rows: Vec<Result<LibraryExportRow, String>> = vec![];
+    let mut positions = HashMap::<String, usize>::new();
+    for (list, entry) in entries {
+        let entry = match serde_json::from_value::<TraktEntry>(entry) {
+            Ok(entry) => entry,
+            Err(error) => {
+                rows.push(Err(error.to_string()));
+                continue;
+            }
+        };
+        // Episodes are mapped to their show, with the episode as the last watched video
+        let (r#type, media) = match (entry.r#type.as_str(), entry.movie, entry.show) {
+            ("movie", Some(media), _) => ("movie", media),
+            ("show", _, Some(media)) | ("episode", _, Some(media)) => ("series", media),
+            (r#type, _, _) => {
+                rows.push(Err(format!("Unsupported type {}", r#type)));
+                continue;
+            }
+        };
+        let id = match media.ids.imdb.to_owned().or(media.ids.stremio.to_owned()) {
+            Some(id) => id,
+            _ => {
+                rows.push(Err("Missing imdb id".to_owned()));
+                continue;
+            }
+        };
+        let position = *positions.entry(id.to_owned()).or_insert_with(|| {
+            rows.push(Ok(LibraryExportRow {
+                id: id.to_owned(),
+                r#type: r#type.to_owned(),
+                name: media.title.to_owned().unwrap_or_else(|| id.to_owned()),
+                in_library: false,
+                watched: false,
+                last_watched: None,
+                time_offset: 0,
+                duration: 0,
+                video_id: None,
+                progress: None,
+            }));
+            rows.len() - 1
+        });
+        let video_id = entry
+            .episode
+            .map(|episode| format!("{}:{}:{}", id, episode.season, episode.number));
+        if let Some(Ok(row)) = rows.get_mut(position) {
+            let watched_at = match list {
+                "watchlist" => {
+                    row.in_library = true;
+                    None
+                }
+                "history" => {
+                    // Watching an episode does not mean the whole show is watched
+                    row.watched = row.watched || video_id.is_none();
+                    entry.watched_at
+                }
+                _ => {
+                    match (entry.progress, entry.duration) {
+                        (Some(progress), Some(duration)) => {
+                            row.duration = duration;
+                            row.time_offset = (progress / 100.0 * duration as f64).round() as u64;
+                        }
+                        (progress, _) => row.progress = progress,
+                    };
+                    entry.paused_at
+                }
+            };
+            if video_id.is_some() && (row.video_id.is_none() || watched_at >= row.last_watched) {
+                row.video_id = video_id;
+            };
+            row.last_watched = row.last_watched.max(watched_at);
+        };
+    }
+    Ok(rows)
+}
+
+fn csv_export(rows: &[LibraryExportRow]) -> String {
+    let escape = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    };
+    let lines = rows.iter().map(|row| {
+        [
+            escape(&row.id),
+            escape(&row.r#type),
+            escape(&row.name),
+            row.in_library.to_string(),
+            row.watched.to_string(),
+            row.last_watched
+                .map(|last_watched| last_watched.to_rfc3339())
+                .unwrap_or_default(),
+            row.time_offset.to_string(),
+            row.duration.to_string(),
+            escape(row.video_id.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    });
+    std::iter::once(CSV_COLUMNS.join(","))
+        .chain(lines)
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn csv_import(content: &str) -> Result<Vec<Result<LibraryExportRow, String>>, String> {
+    let mut records = csv_records(content).into_iter();
+    let header = records.next().ok_or_else(|| "Missing header".to_owned())?;
+    let column = |name: &str| header.iter().position(|column| column.trim() == name);
+    let id_column = column("id").ok_or_else(|| "Missing id column".to_owned())?;
+    let type_column = column("type").ok_or_else(|| "Missing type column".to_owned())?;
+    let columns = CSV_COLUMNS.map(column);
+    Ok(records
+        .filter(|record| record.iter().any(|field| !field.is_empty()))
+        .map(|record| {
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| record.get(index))
+                    .map(|field| field.trim())
+                    .filter(|field| !field.is_empty())
+            };
+            let parse_bool = |index: Option<usize>| match field(index) {
+                Some("true") | Some("1") => Ok(true),
+                Some("false") | Some("0") | None => Ok(false),
+                Some(value) => Err(format!("Invalid boolean {}", value)),
+            };
+            let parse_u64 = |index: Option<usize>| {
+                field(index)
+                    .map(|value| value.parse::<u64>())
+                    .transpose()
+                    .map(Option::unwrap_or_default)
+                    .map_err(|error| error.to_string())
+            };
+            let id = field(Some(id_column)).ok_or_else(|| "Missing id".to_owned())?;
+            let r#type = field(Some(type_column)).ok_or_else(|| "Missing type".to_owned())?;
+            Ok(LibraryExportRow {
+                id: id.to_owned(),
+                r#type: r#type.to_owned(),
+                name: field(columns[2]).unwrap_or(id).to_owned(),
+                in_library: parse_bool(columns[3])?,
+                watched: parse_bool(columns[4])?,
+                last_watched: field(columns[5])
+                    .map(DateTime::parse_from_rfc3339)
+                    .transpose()
+                    .map_err(|error| error.to_string())?
+                    .map(|last_watched| last_watched.with_timezone(&Utc)),
+                time_offset: parse_u64(columns[6])?,
+                duration: parse_u64(columns[7])?,
+                video_id: field(columns[8]).map(str::to_owned),
+                progress: None,
+            })
+        })
+        .collect())
+}
+
+/// Splits RFC 4180 content into records of unquoted fields
+fn csv_records(content: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(char) = chars.next() {
+        match (quoted, char) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, char) => field.push(char),
+            (false, '"') => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, char) => field.push(char),
+        };
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
@@ -32,6 +32,10 @@ pub struct LibraryItem {
     pub state: LibraryItemState,
     #[serde(default)]
     pub behavior_hints: MetaItemBehaviorHints,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released: Option<DateTime<Utc>>,
 }
 
 impl LibraryItem {
@@ -45,6 +49,94 @@ impl LibraryItem {
     pub fn is_in_continue_watching(&self) -> bool {
         self.r#type != "other" && (!self.removed || self.temp) && self.state.time_offset > 0
     }
+    /// The first released and unwatched video after the current one, once the current one is watched.
+    /// Specials (season 0) are skipped unless the current video is a special as well.
+    pub fn up_next_video<'a>(&self, videos: &'a [Video], now: DateTime<Utc>) -> Option<&'a Video> {
+        let video_id = self.state.video_id.as_ref()?;
+        let watched = self.state.watched_bitfield(videos);
+        if !watched.get_video(video_id) {
+            return None;
+        };
+        let (position, current_video) =
+            videos.iter().find_position(|video| video.id == *video_id)?;
+        let current_season = current_video
+            .series_info
+            .as_ref()
+            .map(|info| info.season)
+            .unwrap_or_default();
+        videos[position + 1..].iter().find(|video| {
+            let season = video
+                .series_info
+                .as_ref()
+                .map(|info| info.season)
+                .unwrap_or_default();
+            (season != 0 || current_season == 0)
+                && match video.released {
+                    Some(released) => released <= now,
+                    _ => true,
+                }
+                && !watched.get_video(&video.id)
+        })
+    }
+    /// Merges two versions of the same item field by field instead of keeping only the newer one.
+    ///
+    /// The metadata and the flags come from the newer version, except that an item which is in
+    /// the library is not turned into a temporary one. Counters take the maximum, the playback
+    /// position comes from the version watched last and the watched videos are united.
+    pub fn merge(&self, other: &LibraryItem) -> LibraryItem {
+        let (newer, older) = if other.mtime > self.mtime {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let (removed, temp) = if newer.temp && !older.removed {
+            (older.removed, older.temp)
+        } else {
+            (newer.removed, newer.temp)
+        };
+        let playback = if older.state.last_watched > newer.state.last_watched {
+            &older.state
+        } else {
+            &newer.state
+        };
+        let watched = match (&newer.state.watched, &older.state.watched) {
+            (Some(newer_watched), Some(older_watched)) => Some(
+                WatchedBitField::union_serialized(newer_watched, older_watched)
+                    .unwrap_or_else(|_| newer_watched.to_owned()),
+            ),
+            (newer_watched, older_watched) => newer_watched
+                .to_owned()
+                .or_else(|| older_watched.to_owned()),
+        };
+        LibraryItem {
+            removed,
+            temp,
+            ctime: match (newer.ctime, older.ctime) {
+                (Some(newer_ctime), Some(older_ctime)) => Some(newer_ctime.min(older_ctime)),
+                (newer_ctime, older_ctime) => newer_ctime.or(older_ctime),
+            },
+            state: LibraryItemState {
+                last_watched: newer.state.last_watched.max(older.state.last_watched),
+                time_watched: playback.time_watched,
+                time_offset: playback.time_offset,
+                overall_time_watched: newer
+                    .state
+                    .overall_time_watched
+                    .max(older.state.overall_time_watched),
+                times_watched: newer.state.times_watched.max(older.state.times_watched),
+                flagged_watched: newer.state.flagged_watched,
+                duration: playback.duration,
+                video_id: playback.video_id.to_owned(),
+                watched,
+                last_vid_released: newer
+                    .state
+                    .last_vid_released
+                    .max(older.state.last_vid_released),
+                no_notif: newer.state.no_notif,
+            },
+            ..newer.to_owned()
+        }
+    }
 }
 
 impl<E: Env + 'static> From<(&MetaItemPreview, PhantomData<E>)> for LibraryItem {
@@ -61,6 +153,8 @@ impl<E: Env + 'static> From<(&MetaItemPreview, PhantomData<E>)> for LibraryItem
             poster: meta_item.poster.to_owned(),
             poster_shape: meta_item.poster_shape.to_owned(),
             behavior_hints: meta_item.behavior_hints.to_owned(),
+            genres: meta_item.genres(),
+            released: meta_item.released,
         }
     }
 }
@@ -74,6 +168,10 @@ impl From<(&MetaItemPreview, &LibraryItem)> for LibraryItem {
             poster: meta_item.poster.to_owned(),
             poster_shape: meta_item.poster_shape.to_owned(),
             behavior_hints: meta_item.behavior_hints.to_owned(),
+            genres: Some(meta_item.genres())
+                .filter(|genres| !genres.is_empty())
+                .unwrap_or_else(|| library_item.genres.to_owned()),
+            released: meta_item.released.or(library_item.released),
             removed: library_item.removed,
             temp: library_item.temp,
             ctime: library_item.ctime.to_owned(),
@@ -165,4 +263,39 @@ impl LibraryItemState {
             _ => WatchedBitField::construct_from_array(vec![], video_ids),
         }
     }
+    /// Sets the watched state of several videos at once.
+    /// Every video which changes its state is counted in `times_watched`
+    /// and `flagged_watched` follows the state of the current video.
+    /// Returns whether any of the videos changed.
+    pub fn set_videos_watched(
+        &mut self,
+        watched: &WatchedBitField,
+        video_ids: &[&str],
+        is_watched: bool,
+    ) -> bool {
+        let mut watched = watched.to_owned();
+        let changed_count = video_ids
+            .iter()
+            .filter(|video_id| {
+                let was_watched = watched.get_video(video_id);
+                watched.set_video(video_id, is_watched);
+                was_watched != watched.get_video(video_id)
+            })
+            .count() as u32;
+        if changed_count == 0 {
+            return false;
+        };
+        self.times_watched = if is_watched {
+            self.times_watched.saturating_add(changed_count)
+        } else {
+            self.times_watched.saturating_sub(changed_count)
+        };
+        if let Some(video_id) = &self.video_id {
+            if video_ids.contains(&video_id.as_str()) {
+                self.flagged_watched = is_watched as u32;
+            };
+        };
+        self.watched = Some(watched.to_string());
+        true
+    }
 }
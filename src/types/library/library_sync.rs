@@ -0,0 +1,115 @@
+use crate::constants::LIBRARY_SYNC_BATCH_SIZE;
+use serde::Serialize;
+use std::cmp;
+use std::collections::VecDeque;
+
+/// The counters of a library sync with the API, emitted as its batches complete.
+#[derive(Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LibrarySyncProgress {
+    pub pulled: usize,
+    pub pull_total: usize,
+    pub pushed: usize,
+    pub push_total: usize,
+}
+
+/// The ids left to sync in one direction, sent in batches of `LIBRARY_SYNC_BATCH_SIZE`.
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LibrarySyncQueue {
+    pub ids: VecDeque<String>,
+    /// The batch waiting for a response from the API
+    pub batch: Option<Vec<String>>,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl LibrarySyncQueue {
+    pub fn new(ids: Vec<String>) -> Self {
+        LibrarySyncQueue {
+            total: ids.len(),
+            ids: ids.into(),
+            batch: None,
+            done: 0,
+        }
+    }
+    /// Takes the next batch unless one is already waiting for a response
+    pub fn next_batch(&mut self) -> Option<Vec<String>> {
+        if self.batch.is_some() || self.ids.is_empty() {
+            return None;
+        };
+        let size = cmp::min(self.ids.len(), LIBRARY_SYNC_BATCH_SIZE);
+        let batch = self.ids.drain(..size).collect::<Vec<_>>();
+        self.batch = Some(batch.to_owned());
+        Some(batch)
+    }
+    /// Replaces the remaining ids, keeping the count of the ones already synced
+    pub fn resume(&self, ids: Vec<String>) -> Self {
+        LibrarySyncQueue {
+            total: self.done + ids.len(),
+            ids: ids.into(),
+            batch: None,
+            done: self.done,
+        }
+    }
+    /// Returns whether the ids were the pending batch
+    pub fn batch_succeeded(&mut self, ids: &[String]) -> bool {
+        match &self.batch {
+            Some(batch) if batch == ids => {
+                self.done += batch.len();
+                self.batch = None;
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Queues the pending batch again so that the sync is reported as interrupted
+    pub fn batch_failed(&mut self, ids: &[String]) {
+        if let Some(batch) = self.batch.as_ref().filter(|batch| *batch == ids) {
+            for id in batch.iter().rev() {
+                self.ids.push_front(id.to_owned());
+            }
+            self.batch = None;
+        };
+    }
+}
+
+/// A library sync with the API which pulls and pushes items in bounded batches.
+/// A sync interrupted by a failed batch is resumed from a new plan, as the remaining ids
+/// may be stale by then, while the progress made so far is kept.
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LibrarySync {
+    pub pull: LibrarySyncQueue,
+    pub push: LibrarySyncQueue,
+}
+
+impl LibrarySync {
+    pub fn new(pull_ids: Vec<String>, push_ids: Vec<String>) -> Self {
+        LibrarySync {
+            pull: LibrarySyncQueue::new(pull_ids),
+            push: LibrarySyncQueue::new(push_ids),
+        }
+    }
+    pub fn resume(&self, pull_ids: Vec<String>, push_ids: Vec<String>) -> Self {
+        LibrarySync {
+            pull: self.pull.resume(pull_ids),
+            push: self.push.resume(push_ids),
+        }
+    }
+    pub fn is_running(&self) -> bool {
+        self.pull.batch.is_some() || self.push.batch.is_some()
+    }
+    pub fn is_interrupted(&self) -> bool {
+        !self.is_running() && (!self.pull.ids.is_empty() || !self.push.ids.is_empty())
+    }
+    pub fn progress(&self) -> LibrarySyncProgress {
+        LibrarySyncProgress {
+            pulled: self.pull.done,
+            pull_total: self.pull.total,
+            pushed: self.push.done,
+            push_total: self.push.total,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::types::library::LibraryBucket;
+use crate::types::profile::UID;
+use crate::types::resource::Video;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The videos of the last fetched meta of the series in the library, by library item id.
+///
+/// Only the id, the season, the episode and the release date of the videos are kept.
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct LibraryVideosBucket {
+    pub uid: UID,
+    pub videos: HashMap<String, Vec<Video>>,
+}
+
+impl LibraryVideosBucket {
+    pub fn new(uid: UID) -> Self {
+        LibraryVideosBucket {
+            uid,
+            videos: HashMap::new(),
+        }
+    }
+    /// Drops the videos of the items which are no longer in the library
+    pub fn retain_library(&mut self, library: &LibraryBucket) {
+        self.videos.retain(|id, _| library.items.contains_key(id));
+    }
+}
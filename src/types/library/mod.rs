@@ -1,5 +1,14 @@
 mod library_bucket;
 pub use library_bucket::*;
 
+mod library_export;
+pub use library_export::*;
+
 mod library_item;
 pub use library_item::*;
+
+mod library_sync;
+pub use library_sync::*;
+
+mod library_videos_bucket;
+pub use library_videos_bucket::*;
@@ -0,0 +1,44 @@
+use crate::types::lists::UserList;
+use crate::types::profile::UID;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ListsBucket {
+    pub uid: UID,
+    pub lists: HashMap<String, UserList>,
+}
+
+impl ListsBucket {
+    pub fn new(uid: UID, lists: Vec<UserList>) -> Self {
+        ListsBucket {
+            uid,
+            lists: lists
+                .into_iter()
+                .map(|list| (list.id.to_owned(), list))
+                .collect(),
+        }
+    }
+    /// Keeps the newer version of each list
+    pub fn merge_lists(&mut self, lists: Vec<UserList>) {
+        for new_list in lists.into_iter() {
+            match self.lists.get_mut(&new_list.id) {
+                Some(list) if list.mtime >= new_list.mtime => {}
+                _ => {
+                    self.lists.insert(new_list.id.to_owned(), new_list);
+                }
+            }
+        }
+    }
+    /// The lists which are not removed, oldest first
+    pub fn active_lists(&self) -> Vec<&UserList> {
+        let mut lists = self
+            .lists
+            .values()
+            .filter(|list| !list.removed)
+            .collect::<Vec<_>>();
+        lists.sort_by(|a, b| a.ctime.cmp(&b.ctime).then_with(|| a.id.cmp(&b.id)));
+        lists
+    }
+}
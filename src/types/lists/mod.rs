@@ -0,0 +1,5 @@
+mod lists_bucket;
+pub use lists_bucket::*;
+
+mod user_list;
+pub use user_list::*;
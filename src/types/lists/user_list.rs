@@ -0,0 +1,68 @@
+use crate::runtime::Env;
+use crate::types::resource::{MetaItemPreview, PosterShape};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DefaultOnError, NoneAsEmptyString};
+use std::marker::PhantomData;
+use url::Url;
+
+/// A named list of meta items created by the user, synced as a whole like a library item.
+/// Deleted lists are only flagged as removed so that the deletion is synced as well.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct UserList {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub items: Vec<UserListItem>,
+    #[serde(default)]
+    pub removed: bool,
+    #[serde(rename = "_ctime")]
+    pub ctime: DateTime<Utc>,
+    #[serde(rename = "_mtime")]
+    pub mtime: DateTime<Utc>,
+}
+
+impl UserList {
+    pub fn new<E: Env + 'static>(id: String, name: String) -> Self {
+        UserList {
+            id,
+            name,
+            items: vec![],
+            removed: false,
+            ctime: E::now(),
+            mtime: E::now(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct UserListItem {
+    pub id: String,
+    pub r#type: String,
+    pub name: String,
+    #[serde(default)]
+    #[serde_as(deserialize_as = "DefaultOnError<NoneAsEmptyString>")]
+    pub poster: Option<Url>,
+    #[serde(default)]
+    pub poster_shape: PosterShape,
+    pub added: DateTime<Utc>,
+}
+
+impl<E: Env + 'static> From<(&MetaItemPreview, PhantomData<E>)> for UserListItem {
+    fn from((meta_item, _): (&MetaItemPreview, PhantomData<E>)) -> Self {
+        UserListItem {
+            id: meta_item.id.to_owned(),
+            r#type: meta_item.r#type.to_owned(),
+            name: meta_item.name.to_owned(),
+            poster: meta_item.poster.to_owned(),
+            poster_shape: meta_item.poster_shape.to_owned(),
+            added: E::now(),
+        }
+    }
+}
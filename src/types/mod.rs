@@ -1,8 +1,14 @@
 pub mod addon;
 pub mod api;
+pub mod history;
 pub mod library;
+pub mod lists;
 pub mod profile;
 pub mod resource;
+pub mod undo;
+
+mod language;
+pub use language::*;
 
 mod serde_as_ext;
 pub use serde_as_ext::*;
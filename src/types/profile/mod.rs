@@ -7,5 +7,11 @@ pub use profile::*;
 mod settings;
 pub use settings::*;
 
+mod stream_preferences;
+pub use stream_preferences::*;
+
+mod subtitles_preferences;
+pub use subtitles_preferences::*;
+
 mod user;
 pub use user::*;
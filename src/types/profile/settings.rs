@@ -1,4 +1,5 @@
 use crate::constants::STREAMING_SERVER_URL;
+use crate::types::profile::{StreamPreferences, SubtitlesPreferences};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -25,6 +26,10 @@ pub struct Settings {
     pub subtitles_outline_color: String,
     pub seek_time_duration: u32,
     pub streaming_server_warning_dismissed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stream_preferences: StreamPreferences,
+    #[serde(default)]
+    pub subtitles_preferences: SubtitlesPreferences,
 }
 
 impl Default for Settings {
@@ -48,6 +53,8 @@ impl Default for Settings {
             subtitles_outline_color: "#00000000".to_owned(),
             seek_time_duration: 20000,
             streaming_server_warning_dismissed: None,
+            stream_preferences: StreamPreferences::default(),
+            subtitles_preferences: SubtitlesPreferences::default(),
         }
     }
 }
@@ -0,0 +1,27 @@
+use crate::types::resource::StreamResolution;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub enum StreamDelivery {
+    /// Streams which are played directly from a url, like debrid cached streams
+    Cached,
+    /// Streams which are played through the streaming server torrent client
+    #[serde(rename = "p2p")]
+    P2P,
+}
+
+/// Preferences used to rank the streams of all addons
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct StreamPreferences {
+    /// Streams above this resolution are ranked after all the others
+    pub max_resolution: Option<StreamResolution>,
+    /// ISO 639-2 codes ordered by preference
+    pub preferred_languages: Vec<String>,
+    pub delivery: Option<StreamDelivery>,
+    /// Streams above this size in bytes are ranked after all the others
+    pub max_size: Option<u64>,
+}
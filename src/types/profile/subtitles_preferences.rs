@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Preferences used to select the subtitles track in the player
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitlesPreferences {
+    /// ISO 639-2 code used when there are no subtitles in the subtitles language
+    pub secondary_language: Option<String>,
+    /// Prefer subtitles for the deaf and hard of hearing
+    pub hearing_impaired: bool,
+    /// Languages chosen manually by the user, keyed by the meta id of the series.
+    /// `None` means that the subtitles were turned off for the series.
+    pub series_overrides: HashMap<String, Option<String>>,
+}
@@ -87,6 +87,18 @@ pub struct MetaItemPreview {
     pub behavior_hints: MetaItemBehaviorHints,
 }
 
+impl MetaItemPreview {
+    /// The names of the links in the genres category
+    pub fn genres(&self) -> Vec<String> {
+        self.links
+            .iter()
+            .filter(|link| link.category == GENRES_LINK_CATEGORY)
+            .map(|link| link.name.to_owned())
+            .unique()
+            .collect()
+    }
+}
+
 impl From<MetaItemPreviewLegacy> for MetaItemPreview {
     fn from(legacy_item: MetaItemPreviewLegacy) -> Self {
         let links = match legacy_item.links {
@@ -6,3 +6,9 @@ pub use stream::*;
 
 mod subtitles;
 pub use subtitles::*;
+
+mod stream_info;
+pub use stream_info::*;
+
+mod torrent;
+pub use torrent::*;
@@ -0,0 +1,524 @@
+use boolinator::Boolinator;
+use serde::{Deserialize, Serialize};
+
+const SIZE_UNITS: &[(&str, i32)] = &[
+    ("kb", 1),
+    ("kib", 1),
+    ("mb", 2),
+    ("mib", 2),
+    ("gb", 3),
+    ("gib", 3),
+    ("tb", 4),
+    ("tib", 4),
+];
+const SEEDERS_MARKERS: &[&str] = &["seeders", "seeder", "seeds", "seed"];
+const SEEDERS_EMOJIS: &[char] = &['👤', '👥', '🌱'];
+const VIDEO_EXTENSIONS: &[&str] = &[".mkv", ".mp4", ".avi", ".m4v", ".ts", ".webm"];
+// Suffixes of dash separated keywords which are not release groups
+const RELEASE_GROUP_STOP_WORDS: &[&str] = &["dl", "rip", "hd", "ma", "ray", "x", "sub", "subs"];
+
+/// Quality metadata which addons put as free text in `Stream.name` and `Stream.description`
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[serde(rename_all = "camelCase")]
+pub struct StreamInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<StreamResolution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<StreamReleaseSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<VideoCodec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hdr: Vec<HdrFormat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audio: Vec<AudioFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_channels: Option<String>,
+    /// ISO 639-2 codes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeders: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_group: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum StreamResolution {
+    #[serde(rename = "360p")]
+    P360,
+    #[serde(rename = "480p")]
+    P480,
+    #[serde(rename = "576p")]
+    P576,
+    #[serde(rename = "720p")]
+    P720,
+    #[serde(rename = "1080p")]
+    P1080,
+    #[serde(rename = "1440p")]
+    P1440,
+    #[serde(rename = "2160p")]
+    P2160,
+    #[serde(rename = "4320p")]
+    P4320,
+}
+
+/// Ordered from the lowest to the highest quality
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum StreamReleaseSource {
+    #[serde(rename = "CAM")]
+    Cam,
+    #[serde(rename = "TS")]
+    Telesync,
+    #[serde(rename = "SCR")]
+    Screener,
+    #[serde(rename = "TV")]
+    Tv,
+    #[serde(rename = "DVD")]
+    Dvd,
+    #[serde(rename = "WEBRip")]
+    WebRip,
+    #[serde(rename = "WEB-DL")]
+    WebDl,
+    #[serde(rename = "BluRay")]
+    BluRay,
+    #[serde(rename = "Remux")]
+    Remux,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum VideoCodec {
+    #[serde(rename = "H.264")]
+    H264,
+    #[serde(rename = "H.265")]
+    H265,
+    #[serde(rename = "AV1")]
+    Av1,
+    #[serde(rename = "VP9")]
+    Vp9,
+    #[serde(rename = "XviD")]
+    Xvid,
+    #[serde(rename = "MPEG-2")]
+    Mpeg2,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum HdrFormat {
+    #[serde(rename = "HDR")]
+    Hdr,
+    #[serde(rename = "HDR10")]
+    Hdr10,
+    #[serde(rename = "HDR10+")]
+    Hdr10Plus,
+    #[serde(rename = "DV")]
+    DolbyVision,
+    #[serde(rename = "HLG")]
+    Hlg,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum AudioFormat {
+    #[serde(rename = "AAC")]
+    Aac,
+    #[serde(rename = "AC3")]
+    Ac3,
+    #[serde(rename = "EAC3")]
+    Eac3,
+    #[serde(rename = "DTS")]
+    Dts,
+    #[serde(rename = "DTS-HD")]
+    DtsHd,
+    #[serde(rename = "DTS:X")]
+    DtsX,
+    #[serde(rename = "TrueHD")]
+    TrueHd,
+    #[serde(rename = "Atmos")]
+    Atmos,
+    #[serde(rename = "FLAC")]
+    Flac,
+    #[serde(rename = "MP3")]
+    Mp3,
+    #[serde(rename = "Opus")]
+    Opus,
+    #[serde(rename = "PCM")]
+    Pcm,
+}
+
+enum Keyword {
+    Resolution(StreamResolution),
+    Source(StreamReleaseSource),
+    VideoCodec(VideoCodec),
+    Hdr(HdrFormat),
+    Audio(AudioFormat, Option<&'static str>),
+    Channels(&'static str),
+    Language(&'static str),
+}
+
+impl StreamInfo {
+    /// Extracts the quality metadata from the name and the description of a stream.
+    ///
+    /// Everything is best effort: fields which are not mentioned in the text are left empty.
+    pub fn parse(name: Option<&str>, description: Option<&str>) -> Self {
+        let text = name
+            .into_iter()
+            .chain(description)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tokens = tokenize(&text);
+        let mut info = StreamInfo::default();
+        let mut index = 0;
+        while index < tokens.len() {
+            let token = tokens[index].as_str();
+            let next = tokens.get(index + 1).map(String::as_str);
+            let consumed = if let Some(size) = parse_size(token, next) {
+                info.size.get_or_insert(size);
+                1
+            } else if let Some(keyword) =
+                next.and_then(|next| keyword(&format!("{}{}", token, next)))
+            {
+                info.apply(keyword);
+                2
+            } else if let Some(keyword) = keyword(token) {
+                info.apply(keyword);
+                1
+            } else {
+                if info.seeders.is_none() && SEEDERS_MARKERS.contains(&token) {
+                    let previous = index
+                        .checked_sub(1)
+                        .and_then(|index| tokens.get(index))
+                        .map(String::as_str);
+                    info.seeders = next
+                        .and_then(|next| next.parse().ok())
+                        .or_else(|| previous.and_then(|previous| previous.parse().ok()));
+                };
+                1
+            };
+            index += consumed;
+        }
+        // A bare `TS` is taken for telesync only within a scene style release name,
+        // elsewhere it is usually a transport stream or an abbreviation
+        if let Some((line, release_group)) = text
+            .lines()
+            .find_map(|line| release_group(line).map(|release_group| (line, release_group)))
+        {
+            if tokenize(line).iter().any(|token| token == "ts") {
+                info.apply(Keyword::Source(StreamReleaseSource::Telesync));
+            };
+            info.release_group = Some(release_group);
+        };
+        info
+    }
+    fn apply(&mut self, keyword: Keyword) {
+        match keyword {
+            Keyword::Resolution(resolution) => {
+                self.resolution = self.resolution.max(Some(resolution));
+            }
+            Keyword::Source(source) => {
+                self.source = self.source.max(Some(source));
+            }
+            Keyword::VideoCodec(video_codec) => {
+                self.video_codec.get_or_insert(video_codec);
+            }
+            Keyword::Hdr(hdr) => push_unique(&mut self.hdr, hdr),
+            Keyword::Audio(audio, channels) => {
+                push_unique(&mut self.audio, audio);
+                if let Some(channels) = channels {
+                    self.audio_channels
+                        .get_or_insert_with(|| channels.to_owned());
+                };
+            }
+            Keyword::Channels(channels) => {
+                self.audio_channels
+                    .get_or_insert_with(|| channels.to_owned());
+            }
+            Keyword::Language(language) => push_unique(&mut self.languages, language.to_owned()),
+        }
+    }
+}
+
+fn push_unique<T: PartialEq>(values: &mut Vec<T>, value: T) {
+    if !values.contains(&value) {
+        values.push(value);
+    };
+}
+
+/// Splits the text into lowercase alphanumeric tokens.
+///
+/// Decimal numbers (`5.1`, `1,5`), thousands groups (`1,234`) and a trailing `+` (`HDR10+`, `DD+`)
+/// are kept within the token. Flag emojis become `flag:xx` tokens and seeders emojis become `seeders` tokens.
+fn tokenize(text: &str) -> Vec<String> {
+    let chars = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        if char.is_alphanumeric() || char == '+' && !token.is_empty() {
+            token.push(char);
+        } else if (char == '.' || char == ',') && is_decimal_separator(&token, &chars, index) {
+            token.push('.');
+        } else if char == ',' && is_thousands_separator(&token, &chars, index) {
+            // the digits of thousands groups are kept together
+        } else {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            };
+            if let Some(country) = chars
+                .get(index + 1)
+                .and_then(|next| regional_indicators(char, *next))
+            {
+                tokens.push(format!("flag:{}", country));
+                index += 1;
+            } else if SEEDERS_EMOJIS.contains(&char) {
+                tokens.push(SEEDERS_MARKERS[0].to_owned());
+            };
+        };
+        index += 1;
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    };
+    tokens
+}
+
+fn digits_after(chars: &[char], index: usize) -> (usize, Option<char>) {
+    let digits = chars[index + 1..]
+        .iter()
+        .take_while(|char| char.is_ascii_digit())
+        .count();
+    (digits, chars.get(index + 1 + digits).copied())
+}
+
+fn is_decimal_separator(token: &str, chars: &[char], index: usize) -> bool {
+    let leading_digits = token
+        .chars()
+        .rev()
+        .take_while(|char| char.is_ascii_digit())
+        .count();
+    // Either a plain number or a single digit after an audio codec, like `ddp5.1`
+    let is_number = leading_digits > 0
+        && (leading_digits == token.len()
+            || leading_digits == 1 && audio(&token[..token.len() - 1]).is_some());
+    let (digits, next) = digits_after(chars, index);
+    is_number
+        && !token.contains('.')
+        && (1..=2).contains(&digits)
+        && !matches!(next, Some(next) if next.is_alphanumeric() && !matches!(next, 'k' | 'm' | 'g' | 't' | 'c'))
+}
+
+fn is_thousands_separator(token: &str, chars: &[char], index: usize) -> bool {
+    let (digits, next) = digits_after(chars, index);
+    !token.is_empty()
+        && token.len() <= 3
+        && token.chars().all(|char| char.is_ascii_digit())
+        && digits == 3
+        && !matches!(next, Some(next) if next.is_ascii_digit())
+}
+
+fn regional_indicators(first: char, second: char) -> Option<String> {
+    let letter = |char: char| {
+        let offset = (char as u32).checked_sub(0x1F1E6)?;
+        (offset < 26).as_some_from(|| (b'a' + offset as u8) as char)
+    };
+    Some([letter(first)?, letter(second)?].iter().collect())
+}
+
+fn keyword(token: &str) -> Option<Keyword> {
+    let keyword = match token {
+        "360p" => Keyword::Resolution(StreamResolution::P360),
+        "480p" | "480i" | "sd" => Keyword::Resolution(StreamResolution::P480),
+        "576p" | "576i" => Keyword::Resolution(StreamResolution::P576),
+        "720p" => Keyword::Resolution(StreamResolution::P720),
+        "1080p" | "1080i" | "fhd" | "fullhd" => Keyword::Resolution(StreamResolution::P1080),
+        "1440p" | "2k" | "qhd" => Keyword::Resolution(StreamResolution::P1440),
+        "2160p" | "4k" | "uhd" => Keyword::Resolution(StreamResolution::P2160),
+        "4320p" | "8k" => Keyword::Resolution(StreamResolution::P4320),
+        "cam" | "camrip" | "hdcam" | "hqcam" => Keyword::Source(StreamReleaseSource::Cam),
+        "telesync" | "hdts" | "tsrip" | "tc" | "telecine" | "hdtc" => {
+            Keyword::Source(StreamReleaseSource::Telesync)
+        }
+        "scr" | "screener" | "dvdscr" | "bdscr" | "webscr" => {
+            Keyword::Source(StreamReleaseSource::Screener)
+        }
+        "hdtv" | "pdtv" | "sdtv" | "tvrip" | "hdtvrip" | "satrip" => {
+            Keyword::Source(StreamReleaseSource::Tv)
+        }
+        "dvd" | "dvdrip" | "dvd5" | "dvd9" | "dvdr" => Keyword::Source(StreamReleaseSource::Dvd),
+        "webrip" | "hdrip" => Keyword::Source(StreamReleaseSource::WebRip),
+        "web" | "webdl" => Keyword::Source(StreamReleaseSource::WebDl),
+        "bluray" | "bdrip" | "brrip" | "bd25" | "bd50" | "bdmv" => {
+            Keyword::Source(StreamReleaseSource::BluRay)
+        }
+        "remux" | "bdremux" => Keyword::Source(StreamReleaseSource::Remux),
+        "x264" | "h264" | "avc" => Keyword::VideoCodec(VideoCodec::H264),
+        "x265" | "h265" | "hevc" => Keyword::VideoCodec(VideoCodec::H265),
+        "av1" => Keyword::VideoCodec(VideoCodec::Av1),
+        "vp9" => Keyword::VideoCodec(VideoCodec::Vp9),
+        "xvid" | "divx" => Keyword::VideoCodec(VideoCodec::Xvid),
+        "mpeg2" => Keyword::VideoCodec(VideoCodec::Mpeg2),
+        "hdr" => Keyword::Hdr(HdrFormat::Hdr),
+        "hdr10" => Keyword::Hdr(HdrFormat::Hdr10),
+        "hdr10+" | "hdr10plus" => Keyword::Hdr(HdrFormat::Hdr10Plus),
+        "dv" | "dovi" | "dolbyvision" => Keyword::Hdr(HdrFormat::DolbyVision),
+        "hlg" => Keyword::Hdr(HdrFormat::Hlg),
+        "5.1" | "5.1ch" | "6ch" => Keyword::Channels("5.1"),
+        "7.1" | "7.1ch" | "8ch" => Keyword::Channels("7.1"),
+        "2.0" | "2.0ch" | "2ch" | "stereo" => Keyword::Channels("2.0"),
+        "1.0" | "1.0ch" | "mono" => Keyword::Channels("1.0"),
+        _ => match audio(token) {
+            Some(audio) => Keyword::Audio(audio, None),
+            None => match language(token) {
+                Some(language) => Keyword::Language(language),
+                None => return audio_with_channels(token).or_else(|| dimensions(token)),
+            },
+        },
+    };
+    Some(keyword)
+}
+
+fn audio(token: &str) -> Option<AudioFormat> {
+    let audio = match token {
+        "aac" => AudioFormat::Aac,
+        "ac3" | "dd" => AudioFormat::Ac3,
+        "eac3" | "ddp" | "dd+" => AudioFormat::Eac3,
+        "dts" => AudioFormat::Dts,
+        "dtshd" | "dtshdma" | "dtsma" => AudioFormat::DtsHd,
+        "dtsx" => AudioFormat::DtsX,
+        "truehd" => AudioFormat::TrueHd,
+        "atmos" => AudioFormat::Atmos,
+        "flac" => AudioFormat::Flac,
+        "mp3" => AudioFormat::Mp3,
+        "opus" => AudioFormat::Opus,
+        "pcm" | "lpcm" => AudioFormat::Pcm,
+        _ => return None,
+    };
+    Some(audio)
+}
+
+/// Handles audio codecs glued to the channels, like `ddp5.1` or `aac2.0`
+fn audio_with_channels(token: &str) -> Option<Keyword> {
+    let split = token.find(|char: char| char.is_ascii_digit())?;
+    let audio = audio(&token[..split])?;
+    match keyword(&token[split..])? {
+        Keyword::Channels(channels) => Some(Keyword::Audio(audio, Some(channels))),
+        _ => None,
+    }
+}
+
+/// Handles resolutions written as dimensions, like `1920x1080`
+fn dimensions(token: &str) -> Option<Keyword> {
+    let (width, height) = token.split_once('x')?;
+    width.parse::<u32>().ok()?;
+    keyword(&format!("{}p", height.parse::<u32>().ok()?))
+}
+
+fn language(token: &str) -> Option<&'static str> {
+    let language = match token.strip_prefix("flag:") {
+        Some(country) => match country {
+            "gb" | "us" | "au" => "eng",
+            "es" | "mx" | "ar" => "spa",
+            "fr" => "fre",
+            "de" => "ger",
+            "it" => "ita",
+            "pt" | "br" => "por",
+            "ru" => "rus",
+            "jp" => "jpn",
+            "kr" => "kor",
+            "cn" | "tw" | "hk" => "chi",
+            "in" => "hin",
+            "nl" => "dut",
+            "pl" => "pol",
+            "tr" => "tur",
+            "ua" => "ukr",
+            "bg" => "bul",
+            "se" => "swe",
+            "gr" => "gre",
+            "cz" => "cze",
+            "hu" => "hun",
+            "ro" => "rum",
+            _ => return None,
+        },
+        None => match token {
+            "english" | "eng" => "eng",
+            "spanish" | "spa" | "esp" | "español" | "castellano" | "latino" => "spa",
+            "french" | "fre" | "fra" | "vf" | "vff" | "truefrench" => "fre",
+            "german" | "ger" | "deu" => "ger",
+            "italian" | "ita" => "ita",
+            "portuguese" | "por" => "por",
+            "russian" | "rus" => "rus",
+            "japanese" | "jpn" => "jpn",
+            "korean" | "kor" => "kor",
+            "chinese" | "chi" | "zho" => "chi",
+            "hindi" | "hin" => "hin",
+            "dutch" | "dut" | "nld" => "dut",
+            "polish" | "pol" => "pol",
+            "turkish" | "tur" => "tur",
+            "ukrainian" | "ukr" => "ukr",
+            "bulgarian" | "bul" => "bul",
+            "swedish" | "swe" => "swe",
+            "greek" | "gre" | "ell" => "gre",
+            "czech" | "cze" | "ces" => "cze",
+            "hungarian" | "hun" => "hun",
+            "romanian" | "rum" | "ron" => "rum",
+            "arabic" | "ara" => "ara",
+            _ => return None,
+        },
+    };
+    Some(language)
+}
+
+fn parse_size(token: &str, next: Option<&str>) -> Option<u64> {
+    let split = token
+        .find(|char: char| !char.is_ascii_digit() && char != '.')
+        .unwrap_or(token.len());
+    let (value, unit) = token.split_at(split);
+    let unit = match unit {
+        "" => next?,
+        unit => unit,
+    };
+    let value = value.parse::<f64>().ok()?;
+    let exponent = SIZE_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, exponent)| *exponent)?;
+    Some((value * 1024_f64.powi(exponent)).round() as u64)
+}
+
+/// Finds the group in scene style release names, like `Movie.2019.1080p.WEB-DL.x264-GROUP.mkv`
+///
+/// The group is expected at the end of the line and the rest of the line should contain
+/// some quality keywords, so that titles like `Spider-Man` are not mistaken for a release.
+fn release_group(line: &str) -> Option<String> {
+    let word = line.split_whitespace().last()?;
+    let word = VIDEO_EXTENSIONS
+        .iter()
+        .find_map(|extension| {
+            word.len()
+                .checked_sub(extension.len())
+                .filter(|split| word.is_char_boundary(*split))
+                .filter(|split| word[*split..].eq_ignore_ascii_case(extension))
+                .map(|split| &word[..split])
+        })
+        .unwrap_or(word);
+    let (release, group) = word.rsplit_once('-')?;
+    let lowercase = group.to_lowercase();
+    let is_group = !release.is_empty()
+        && (2..=20).contains(&group.len())
+        && group.chars().all(|char| char.is_ascii_alphanumeric())
+        && !group.chars().all(|char| char.is_ascii_digit())
+        && !RELEASE_GROUP_STOP_WORDS.contains(&lowercase.as_str())
+        && keyword(&lowercase).is_none()
+        && tokenize(line).iter().any(|token| keyword(token).is_some());
+    is_group.as_some_from(|| group.to_owned())
+}
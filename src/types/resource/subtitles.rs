@@ -1,3 +1,4 @@
+use crate::types::is_same_language;
 #[cfg(test)]
 use derivative::Derivative;
 use serde::{Deserialize, Serialize};
@@ -15,3 +16,33 @@ pub struct Subtitles {
     )]
     pub url: Url,
 }
+
+impl Subtitles {
+    /// Compares the language of the subtitles by its canonical ISO 639 code
+    pub fn is_language(&self, language: &str) -> bool {
+        is_same_language(&self.lang, language)
+    }
+    /// Detects subtitles for the deaf and hard of hearing, like `eng (SDH)` or `English [CC]`.
+    /// Addons have no dedicated field for it, so the flag is looked up in the language label
+    /// after the language itself and in the file name of the url.
+    pub fn is_hearing_impaired(&self) -> bool {
+        let lang = self.lang.to_lowercase();
+        let label_flag = lang
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .skip(1)
+            .any(|token| matches!(token, "sdh" | "hi" | "cc" | "hearing"));
+        let file_name_flag = self
+            .url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .map(|file_name| {
+                file_name
+                    .to_lowercase()
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|token| token == "sdh")
+            })
+            .unwrap_or_default();
+        label_flag || file_name_flag
+    }
+}
@@ -0,0 +1,420 @@
+use crate::types::resource::{SeriesInfo, Stream, StreamSource};
+use boolinator::Boolinator;
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use std::fmt;
+use url::Url;
+
+const VIDEO_EXTENSIONS: &[&str] = &[
+    "mkv", "mp4", "avi", "mov", "wmv", "m4v", "webm", "ts", "m2ts", "mpg", "mpeg", "flv", "ogm",
+];
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// Nesting limit of bencoded lists and dictionaries, real torrents nest only a few levels
+const BENCODE_MAX_DEPTH: usize = 32;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum TorrentError {
+    NotAMagnet,
+    InvalidInfoHash,
+    /// Malformed bencode, with the byte offset where decoding failed
+    InvalidBencode(usize),
+    /// Lists and dictionaries nested deeper than the limit, with the byte offset where it is exceeded
+    BencodeTooDeep(usize),
+    MissingInfo,
+}
+
+impl fmt::Display for TorrentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorrentError::NotAMagnet => write!(f, "Not a magnet link"),
+            TorrentError::InvalidInfoHash => write!(f, "Invalid torrent info hash"),
+            TorrentError::InvalidBencode(position) => {
+                write!(f, "Invalid bencode at byte {}", position)
+            }
+            TorrentError::BencodeTooDeep(position) => {
+                write!(f, "Bencode nested too deep at byte {}", position)
+            }
+            TorrentError::MissingInfo => write!(f, "Torrent info dictionary is missing"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct TorrentFile {
+    /// Path inside the torrent, with the components joined by `/`
+    pub path: String,
+    /// Size in bytes
+    pub length: u64,
+}
+
+impl TorrentFile {
+    pub fn is_video(&self) -> bool {
+        self.path
+            .rsplit_once('.')
+            .map(|(_, extension)| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+            .unwrap_or_default()
+    }
+}
+
+/// A torrent described by a magnet link or a `.torrent` file
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Torrent {
+    pub info_hash: [u8; 20],
+    /// Display name of the magnet link or name of the torrent
+    pub name: Option<String>,
+    /// Tracker urls, without duplicates
+    pub trackers: Vec<String>,
+    /// Files of the torrent, empty for magnet links
+    pub files: Vec<TorrentFile>,
+}
+
+impl Torrent {
+    /// Parses a `magnet:?xt=urn:btih:...` link with a hex or base32 info hash
+    pub fn from_magnet(magnet: &str) -> Result<Self, TorrentError> {
+        let url = Url::parse(magnet.trim()).map_err(|_| TorrentError::NotAMagnet)?;
+        if url.scheme() != "magnet" {
+            return Err(TorrentError::NotAMagnet);
+        };
+        let mut info_hash = None;
+        let mut name = None;
+        let mut trackers = Vec::<String>::new();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "xt" => {
+                    if let Some(hash) = value.strip_prefix("urn:btih:") {
+                        info_hash = Some(parse_info_hash(hash)?);
+                    };
+                }
+                "dn" => name = Some(value.into_owned()),
+                "tr" if !trackers.iter().any(|tracker| *tracker == value) => {
+                    trackers.push(value.into_owned())
+                }
+                _ => {}
+            };
+        }
+        Ok(Torrent {
+            info_hash: info_hash.ok_or(TorrentError::InvalidInfoHash)?,
+            name,
+            trackers,
+            files: vec![],
+        })
+    }
+    /// Parses the content of a bencoded `.torrent` file.
+    /// The info hash is the SHA-1 of the raw `info` dictionary.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TorrentError> {
+        let (root, end) = Value::decode(bytes, 0, 0)?;
+        if end != bytes.len() {
+            return Err(TorrentError::InvalidBencode(end));
+        };
+        let info = root.get(b"info").ok_or(TorrentError::MissingInfo)?;
+        let info_raw = match info {
+            Value::Dict { raw, .. } => raw,
+            _ => return Err(TorrentError::MissingInfo),
+        };
+        let info_hash = sha1_smol::Sha1::from(info_raw).digest().bytes();
+        let name = info
+            .get(b"name.utf-8")
+            .or_else(|| info.get(b"name"))
+            .and_then(Value::as_string);
+        let trackers = root
+            .get(b"announce")
+            .and_then(Value::as_string)
+            .into_iter()
+            .chain(
+                root.get(b"announce-list")
+                    .and_then(Value::as_list)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_list)
+                    .flatten()
+                    .filter_map(Value::as_string),
+            )
+            .fold(Vec::<String>::new(), |mut trackers, tracker| {
+                if !trackers.contains(&tracker) {
+                    trackers.push(tracker);
+                };
+                trackers
+            });
+        let files = match info.get(b"files").and_then(Value::as_list) {
+            Some(files) => files
+                .iter()
+                .map(|file| {
+                    let path = file
+                        .get(b"path.utf-8")
+                        .or_else(|| file.get(b"path"))
+                        .and_then(Value::as_list)
+                        .map(|path| {
+                            path.iter()
+                                .filter_map(Value::as_string)
+                                .collect::<Vec<_>>()
+                                .join("/")
+                        })
+                        .unwrap_or_default();
+                    let length = file
+                        .get(b"length")
+                        .and_then(Value::as_u64)
+                        .unwrap_or_default();
+                    TorrentFile { path, length }
+                })
+                .collect(),
+            None => vec![TorrentFile {
+                path: name.to_owned().unwrap_or_default(),
+                length: info
+                    .get(b"length")
+                    .and_then(Value::as_u64)
+                    .unwrap_or_default(),
+            }],
+        };
+        Ok(Torrent {
+            info_hash,
+            name,
+            trackers,
+            files,
+        })
+    }
+    /// Index of the file to play.
+    ///
+    /// With a season and episode hint the largest video file named after the episode is chosen,
+    /// otherwise or when there is no such file the largest video file is chosen.
+    pub fn file_idx(&self, series_info: Option<&SeriesInfo>) -> Option<u16> {
+        let videos = self
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.is_video())
+            .collect::<Vec<_>>();
+        let largest = |videos: &mut dyn Iterator<Item = &(usize, &TorrentFile)>| {
+            videos
+                .max_by_key(|(index, file)| (file.length, Reverse(*index)))
+                .map(|(index, _)| *index)
+        };
+        series_info
+            .and_then(|series_info| {
+                largest(&mut videos.iter().filter(|(_, file)| {
+                    episode_numbers(&file.path).contains(&(series_info.season, series_info.episode))
+                }))
+            })
+            .or_else(|| largest(&mut videos.iter()))
+            .and_then(|index| u16::try_from(index).ok())
+    }
+    pub fn stream(&self, series_info: Option<&SeriesInfo>) -> Stream {
+        let file_idx = self.file_idx(series_info);
+        Stream {
+            source: StreamSource::Torrent {
+                info_hash: self.info_hash,
+                file_idx,
+                announce: self
+                    .trackers
+                    .iter()
+                    .map(|tracker| format!("tracker:{}", tracker))
+                    .collect(),
+            },
+            name: self.name.to_owned(),
+            description: file_idx
+                .and_then(|file_idx| self.files.get(file_idx as usize))
+                .map(|file| file.path.to_owned()),
+            thumbnail: None,
+            subtitles: vec![],
+            behavior_hints: Default::default(),
+        }
+    }
+}
+
+impl Stream {
+    /// Builds a torrent stream from a pasted magnet link
+    pub fn from_magnet(magnet: &str) -> Result<Self, TorrentError> {
+        Torrent::from_magnet(magnet).map(|torrent| torrent.stream(None))
+    }
+    /// Builds a torrent stream from the content of a `.torrent` file,
+    /// the season and episode hint is used to pick the file of series packs
+    pub fn from_torrent(
+        bytes: &[u8],
+        series_info: Option<&SeriesInfo>,
+    ) -> Result<Self, TorrentError> {
+        Torrent::from_bytes(bytes).map(|torrent| torrent.stream(series_info))
+    }
+}
+
+fn parse_info_hash(hash: &str) -> Result<[u8; 20], TorrentError> {
+    let bytes = match hash.len() {
+        40 => hex::decode(hash).ok(),
+        32 => decode_base32(hash),
+        _ => None,
+    };
+    bytes
+        .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+        .ok_or(TorrentError::InvalidInfoHash)
+}
+
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in value.to_ascii_uppercase().bytes() {
+        let digit = BASE32_ALPHABET.iter().position(|a| *a == c)?;
+        buffer = (buffer << 5) | digit as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        };
+    }
+    Some(bytes)
+}
+
+/// Season and episode numbers in a file path, like `S01E02`, `s1.e2` or `1x02`
+fn episode_numbers(path: &str) -> Vec<(u32, u32)> {
+    let chars = path.to_lowercase().chars().collect::<Vec<_>>();
+    let number = |start: usize, max_len: usize| {
+        let len = chars[start..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        (len > 0 && len <= max_len).as_option().and_then(|_| {
+            chars[start..start + len]
+                .iter()
+                .collect::<String>()
+                .parse::<u32>()
+                .ok()
+                .map(|number| (number, start + len))
+        })
+    };
+    let mut numbers = vec![];
+    for start in 0..chars.len() {
+        if start > 0 && chars[start - 1].is_alphanumeric() {
+            continue;
+        };
+        let episode = if chars[start] == 's' {
+            number(start + 1, 2).and_then(|(season, end)| {
+                let end = match chars.get(end) {
+                    Some('.' | '_' | '-' | ' ') => end + 1,
+                    _ => end,
+                };
+                (chars.get(end) == Some(&'e'))
+                    .as_option()
+                    .and_then(|_| number(end + 1, 3))
+                    .map(|(episode, _)| (season, episode))
+            })
+        } else {
+            number(start, 2).and_then(|(season, end)| {
+                (chars.get(end) == Some(&'x'))
+                    .as_option()
+                    .and_then(|_| number(end + 1, 3))
+                    .filter(|(_, end)| !matches!(chars.get(*end), Some(c) if c.is_alphanumeric()))
+                    .map(|(episode, _)| (season, episode))
+            })
+        };
+        numbers.extend(episode);
+    }
+    numbers
+}
+
+enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    Dict {
+        entries: Vec<(&'a [u8], Value<'a>)>,
+        /// The encoded dictionary, needed for the info hash
+        raw: &'a [u8],
+    },
+}
+
+impl<'a> Value<'a> {
+    fn decode(bytes: &'a [u8], start: usize, depth: usize) -> Result<(Self, usize), TorrentError> {
+        match bytes.get(start) {
+            Some(b'l' | b'd') if depth >= BENCODE_MAX_DEPTH => {
+                Err(TorrentError::BencodeTooDeep(start))
+            }
+            Some(b'i') => {
+                let end = find(bytes, start + 1, b'e')?;
+                std::str::from_utf8(&bytes[start + 1..end])
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                    .map(|value| (Value::Int(value), end + 1))
+                    .ok_or(TorrentError::InvalidBencode(start))
+            }
+            Some(b'l') => {
+                let mut values = vec![];
+                let mut position = start + 1;
+                while bytes.get(position) != Some(&b'e') {
+                    let (value, end) = Value::decode(bytes, position, depth + 1)?;
+                    values.push(value);
+                    position = end;
+                }
+                Ok((Value::List(values), position + 1))
+            }
+            Some(b'd') => {
+                let mut entries = vec![];
+                let mut position = start + 1;
+                while bytes.get(position) != Some(&b'e') {
+                    let (key, end) = match Value::decode(bytes, position, depth + 1)? {
+                        (Value::Bytes(key), end) => (key, end),
+                        _ => return Err(TorrentError::InvalidBencode(position)),
+                    };
+                    let (value, end) = Value::decode(bytes, end, depth + 1)?;
+                    entries.push((key, value));
+                    position = end;
+                }
+                Ok((
+                    Value::Dict {
+                        entries,
+                        raw: &bytes[start..position + 1],
+                    },
+                    position + 1,
+                ))
+            }
+            Some(b'0'..=b'9') => {
+                let colon = find(bytes, start, b':')?;
+                let len = std::str::from_utf8(&bytes[start..colon])
+                    .ok()
+                    .and_then(|len| len.parse::<usize>().ok())
+                    .ok_or(TorrentError::InvalidBencode(start))?;
+                let end = colon
+                    .checked_add(1 + len)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or(TorrentError::InvalidBencode(colon))?;
+                Ok((Value::Bytes(&bytes[colon + 1..end]), end))
+            }
+            _ => Err(TorrentError::InvalidBencode(start)),
+        }
+    }
+    fn get(&self, key: &[u8]) -> Option<&Value<'a>> {
+        match self {
+            Value::Dict { entries, .. } => entries
+                .iter()
+                .find(|(entry_key, _)| *entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+    fn as_list(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+    fn as_string(&self) -> Option<String> {
+        match self {
+            Value::Bytes(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        }
+    }
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Int(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn find(bytes: &[u8], start: usize, byte: u8) -> Result<usize, TorrentError> {
+    bytes[start..]
+        .iter()
+        .position(|b| *b == byte)
+        .map(|position| start + position)
+        .ok_or(TorrentError::InvalidBencode(start))
+}
@@ -0,0 +1,2 @@
+mod undo_stack;
+pub use undo_stack::*;
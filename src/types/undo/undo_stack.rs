@@ -0,0 +1,97 @@
+use crate::constants::{UNDO_EXPIRY, UNDO_STACK_SIZE};
+use crate::runtime::Env;
+use crate::types::addon::Descriptor;
+use crate::types::library::LibraryItem;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Serializer};
+
+/// The state from before a destructive action.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum UndoSnapshot {
+    LibraryItem {
+        library_item: LibraryItem,
+    },
+    /// An uninstalled addon with its position in the installed addons
+    Addon {
+        addon: Descriptor,
+        position: usize,
+    },
+}
+
+impl UndoSnapshot {
+    pub fn id(&self) -> &str {
+        match self {
+            UndoSnapshot::LibraryItem { library_item } => &library_item.id,
+            UndoSnapshot::Addon { addon, .. } => &addon.manifest.id,
+        }
+    }
+}
+
+/// Only the kind and the id of the snapshot are serialized, the state itself stays in the core.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct UndoEntry {
+    pub snapshot: UndoSnapshot,
+    pub expires: DateTime<Utc>,
+}
+
+impl Serialize for UndoEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        enum Kind {
+            LibraryItem,
+            Addon,
+        }
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Helper<'a> {
+            r#type: Kind,
+            id: &'a str,
+            expires: &'a DateTime<Utc>,
+        }
+
+        Helper {
+            r#type: match self.snapshot {
+                UndoSnapshot::LibraryItem { .. } => Kind::LibraryItem,
+                UndoSnapshot::Addon { .. } => Kind::Addon,
+            },
+            id: self.snapshot.id(),
+            expires: &self.expires,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The latest destructive actions which can still be undone, most recent last.
+#[derive(Default, Clone, PartialEq, Serialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct UndoStack {
+    pub entries: Vec<UndoEntry>,
+}
+
+impl UndoStack {
+    /// Adds a snapshot, dropping the expired ones
+    pub fn push<E: Env + 'static>(&mut self, snapshot: UndoSnapshot) {
+        let now = E::now();
+        self.entries.retain(|entry| entry.expires > now);
+        self.entries.push(UndoEntry {
+            snapshot,
+            expires: now + Duration::seconds(UNDO_EXPIRY),
+        });
+        if self.entries.len() > UNDO_STACK_SIZE {
+            let overflow = self.entries.len() - UNDO_STACK_SIZE;
+            self.entries.drain(..overflow);
+        };
+    }
+    /// Takes the most recent snapshot, dropping the expired ones
+    pub fn pop<E: Env + 'static>(&mut self) -> Option<UndoSnapshot> {
+        let now = E::now();
+        self.entries.retain(|entry| entry.expires > now);
+        self.entries.pop().map(|entry| entry.snapshot)
+    }
+}
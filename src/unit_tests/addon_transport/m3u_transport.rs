@@ -0,0 +1,204 @@
+use crate::addon_transport::{m3u_playlist_storage_key, M3UError, M3UPlaylist};
+use crate::runtime::Env;
+use crate::types::addon::{ExtraValue, ResourcePath, ResourceResponse};
+use crate::types::resource::StreamSource;
+use crate::unit_tests::TestEnv;
+use futures::FutureExt;
+use url::Url;
+
+const PLAYLIST: &str = r#"#EXTM3U x-tvg-url="https://epg.com/guide.xml"
+#EXTINF:-1 tvg-id="news.one" tvg-logo="https://logos.com/news.png" group-title="News, Weather",News One HD
+https://iptv.com/news-one-hd.m3u8
+#EXTINF:-1 tvg-id="news.one" group-title="News, Weather",News One SD
+#EXTVLCOPT:http-user-agent=Player
+https://iptv.com/news-one-sd.m3u8
+#EXTINF:0,Movies 24
+#EXTGRP:Movies
+http://iptv.com/movies-24.ts
+#EXTINF:-1 group-title="Movies",Broken
+not a url
+https://iptv.com/streams/sports.ts
+"#;
+
+#[test]
+fn m3u_playlist_parse() {
+    let playlist = M3UPlaylist::parse("My IPTV: Europe", PLAYLIST).unwrap();
+    assert_eq!(playlist.id, "my-iptv-europe-522eaff1");
+    assert_eq!(
+        playlist.transport_url(),
+        Url::parse("m3u://my-iptv-europe-522eaff1").unwrap()
+    );
+    assert_eq!(
+        playlist
+            .entries
+            .iter()
+            .map(|entry| (
+                entry.title.as_str(),
+                entry.tvg_id.as_deref(),
+                entry.group_title.as_deref(),
+                entry.url.as_str()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "News One HD",
+                Some("news.one"),
+                Some("News, Weather"),
+                "https://iptv.com/news-one-hd.m3u8"
+            ),
+            (
+                "News One SD",
+                Some("news.one"),
+                Some("News, Weather"),
+                "https://iptv.com/news-one-sd.m3u8"
+            ),
+            (
+                "Movies 24",
+                None,
+                Some("Movies"),
+                "http://iptv.com/movies-24.ts"
+            ),
+            (
+                "sports.ts",
+                None,
+                None,
+                "https://iptv.com/streams/sports.ts"
+            ),
+        ],
+        "Entries without a valid url are skipped"
+    );
+    assert_eq!(
+        playlist.entries[0].tvg_logo,
+        Some(Url::parse("https://logos.com/news.png").unwrap())
+    );
+    assert_eq!(
+        M3UPlaylist::parse("Empty", "#EXTM3U\n#EXTINF:-1,Nothing\n"),
+        Err(M3UError::NoEntries)
+    );
+}
+
+#[test]
+fn m3u_playlist_manifest() {
+    let manifest = M3UPlaylist::parse("IPTV", PLAYLIST).unwrap().manifest();
+    assert_eq!(manifest.id, "local.m3u.iptv-87e7785b");
+    assert_eq!(manifest.types, vec!["tv".to_owned()]);
+    assert_eq!(
+        manifest.id_prefixes,
+        Some(vec!["m3u:iptv-87e7785b:".to_owned()])
+    );
+    assert_eq!(manifest.catalogs.len(), 1);
+    assert_eq!(
+        manifest.catalogs[0]
+            .extra
+            .iter()
+            .find(|extra| extra.name == "genre")
+            .map(|extra| extra.options.to_owned()),
+        Some(vec!["News, Weather".to_owned(), "Movies".to_owned()])
+    );
+    assert!(manifest.is_resource_supported(&ResourcePath::without_extra(
+        "stream",
+        "tv",
+        "m3u:iptv-87e7785b:news.one"
+    )));
+}
+
+#[test]
+fn m3u_transport_resources() {
+    let _env_mutex = TestEnv::reset();
+    let playlist = M3UPlaylist::parse("IPTV", PLAYLIST).unwrap();
+    TestEnv::set_storage(&m3u_playlist_storage_key(&playlist.id), Some(&playlist))
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+    let transport = TestEnv::addon_transport(&playlist.transport_url());
+    let resource = |path: ResourcePath| {
+        transport
+            .resource(&path)
+            .now_or_never()
+            .expect("resource is ready")
+            .map(|response| response.content)
+    };
+    assert_eq!(
+        transport.manifest().now_or_never().unwrap(),
+        Ok(playlist.manifest())
+    );
+    match resource(ResourcePath::without_extra("catalog", "tv", "m3u")) {
+        Ok(Some(ResourceResponse::Metas { metas })) => assert_eq!(
+            metas
+                .iter()
+                .map(|meta| meta.id.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "m3u:iptv-87e7785b:news.one",
+                "m3u:iptv-87e7785b:Movies%2024",
+                "m3u:iptv-87e7785b:sports.ts"
+            ],
+            "Entries with the same tvg-id are one channel"
+        ),
+        response => panic!("unexpected catalog response: {:?}", response),
+    };
+    match resource(ResourcePath::with_extra(
+        "catalog",
+        "tv",
+        "m3u",
+        &[ExtraValue {
+            name: "genre".to_owned(),
+            value: "Movies".to_owned(),
+        }],
+    )) {
+        Ok(Some(ResourceResponse::Metas { metas })) => {
+            assert_eq!(metas.len(), 1);
+            assert_eq!(metas[0].name, "Movies 24");
+        }
+        response => panic!("unexpected catalog response: {:?}", response),
+    };
+    match resource(ResourcePath::without_extra(
+        "meta",
+        "tv",
+        "m3u:iptv-87e7785b:news.one",
+    )) {
+        Ok(Some(ResourceResponse::Meta { meta })) => {
+            assert_eq!(meta.preview.name, "News One HD");
+            assert_eq!(
+                meta.preview.logo,
+                Some(Url::parse("https://logos.com/news.png").unwrap())
+            );
+        }
+        response => panic!("unexpected meta response: {:?}", response),
+    };
+    match resource(ResourcePath::without_extra(
+        "stream",
+        "tv",
+        "m3u:iptv-87e7785b:news.one",
+    )) {
+        Ok(Some(ResourceResponse::Streams { streams })) => assert_eq!(
+            streams
+                .iter()
+                .map(|stream| stream.source.to_owned())
+                .collect::<Vec<_>>(),
+            vec![
+                StreamSource::Url {
+                    url: Url::parse("https://iptv.com/news-one-hd.m3u8").unwrap()
+                },
+                StreamSource::Url {
+                    url: Url::parse("https://iptv.com/news-one-sd.m3u8").unwrap()
+                },
+            ]
+        ),
+        response => panic!("unexpected stream response: {:?}", response),
+    };
+    assert!(resource(ResourcePath::without_extra(
+        "meta",
+        "tv",
+        "m3u:iptv-87e7785b:missing"
+    ))
+    .is_err());
+    assert!(
+        TestEnv::addon_transport(&Url::parse("m3u://missing").unwrap())
+            .manifest()
+            .now_or_never()
+            .unwrap()
+            .is_err(),
+        "Playlist not found in storage"
+    );
+}
@@ -0,0 +1,2 @@
+mod m3u_transport;
+mod scheduled_transport;
@@ -0,0 +1,220 @@
+use crate::addon_transport::{reset_addon_scheduler, AddonRequestPermit, AddonRequestPriority};
+use crate::constants::{ADDON_CONCURRENCY, ADDON_HOST_CONCURRENCY};
+use crate::runtime::{Env, EnvError, EnvFutureExt, TryEnvFuture};
+use crate::types::addon::ResourcePath;
+use crate::unit_tests::{Request, TestEnv, FETCH_HANDLER, NOW, REQUESTS};
+use chrono::Duration;
+use futures::executor::block_on;
+use futures::{future, FutureExt};
+use std::any::Any;
+use url::Url;
+
+fn acquire(
+    transport_url: &str,
+    priority: AddonRequestPriority,
+) -> TryEnvFuture<AddonRequestPermit> {
+    AddonRequestPermit::acquire::<TestEnv>(&Url::parse(transport_url).unwrap(), priority)
+}
+
+#[test]
+fn host_concurrency() {
+    let _env_mutex = TestEnv::reset();
+    let permits = (0..ADDON_HOST_CONCURRENCY)
+        .map(|index| {
+            acquire(
+                &format!("https://host-concurrency.com/{}/manifest.json", index),
+                AddonRequestPriority::Normal,
+            )
+            .now_or_never()
+            .expect("permit is not ready")
+            .expect("permit is not acquired")
+        })
+        .collect::<Vec<_>>();
+    let mut queued = acquire(
+        "https://host-concurrency.com/queued/manifest.json",
+        AddonRequestPriority::Normal,
+    );
+    assert!(
+        (&mut queued).now_or_never().is_none(),
+        "Request is queued while the host is busy"
+    );
+    assert!(
+        acquire(
+            "https://other-host-concurrency.com/manifest.json",
+            AddonRequestPriority::Normal
+        )
+        .now_or_never()
+        .and_then(Result::ok)
+        .is_some(),
+        "Request to another host is not queued"
+    );
+    drop(permits);
+    assert!(
+        queued.now_or_never().and_then(Result::ok).is_some(),
+        "Queued request is started when a slot is released"
+    );
+}
+
+#[test]
+fn addon_concurrency() {
+    let _env_mutex = TestEnv::reset();
+    let transport_url = "https://addon-concurrency.com/manifest.json";
+    let mut permits = (0..ADDON_CONCURRENCY)
+        .map(|_| {
+            acquire(transport_url, AddonRequestPriority::Normal)
+                .now_or_never()
+                .expect("permit is not ready")
+                .expect("permit is not acquired")
+        })
+        .collect::<Vec<_>>();
+    let mut queued = acquire(transport_url, AddonRequestPriority::Normal);
+    assert!(
+        (&mut queued).now_or_never().is_none(),
+        "Request is queued while the addon is busy"
+    );
+    permits.pop();
+    assert!(
+        queued.now_or_never().and_then(Result::ok).is_some(),
+        "Queued request is started when a slot is released"
+    );
+}
+
+#[test]
+fn priority() {
+    let _env_mutex = TestEnv::reset();
+    let mut permits = (0..ADDON_HOST_CONCURRENCY)
+        .map(|index| {
+            acquire(
+                &format!("https://priority.com/{}/manifest.json", index),
+                AddonRequestPriority::Normal,
+            )
+            .now_or_never()
+            .expect("permit is not ready")
+            .expect("permit is not acquired")
+        })
+        .collect::<Vec<_>>();
+    let mut low = acquire(
+        "https://priority.com/low/manifest.json",
+        AddonRequestPriority::Low,
+    );
+    let mut high = acquire(
+        "https://priority.com/high/manifest.json",
+        AddonRequestPriority::High,
+    );
+    assert!((&mut low).now_or_never().is_none());
+    assert!((&mut high).now_or_never().is_none());
+    permits.pop();
+    assert!(
+        (&mut low).now_or_never().is_none(),
+        "Low priority request is still queued"
+    );
+    assert!(
+        high.now_or_never().and_then(Result::ok).is_some(),
+        "High priority request is started first"
+    );
+    permits.pop();
+    assert!(
+        low.now_or_never().and_then(Result::ok).is_some(),
+        "Low priority request is started next"
+    );
+}
+
+#[test]
+fn rate_limit() {
+    let _env_mutex = TestEnv::reset();
+    let permits = (0..ADDON_HOST_CONCURRENCY)
+        .map(|index| {
+            acquire(
+                &format!("https://rate-limit.com/{}/manifest.json", index),
+                AddonRequestPriority::Normal,
+            )
+            .now_or_never()
+            .expect("permit is not ready")
+            .expect("permit is not acquired")
+        })
+        .collect::<Vec<_>>();
+    let mut queued = acquire(
+        "https://rate-limit.com/queued/manifest.json",
+        AddonRequestPriority::Normal,
+    );
+    assert!((&mut queued).now_or_never().is_none());
+    permits[0].rate_limit(TestEnv::now() + Duration::seconds(60));
+    assert_eq!(
+        queued.now_or_never().and_then(|result| result.err()),
+        Some(EnvError::TooManyRequests(None)),
+        "Queued request is rejected"
+    );
+    drop(permits);
+    assert_eq!(
+        acquire(
+            "https://rate-limit.com/manifest.json",
+            AddonRequestPriority::High
+        )
+        .now_or_never()
+        .and_then(|result| result.err()),
+        Some(EnvError::TooManyRequests(Some(60))),
+        "Request fails fast while the host is rate limited"
+    );
+    *NOW.write().unwrap() = TestEnv::now() + Duration::seconds(61);
+    assert!(
+        acquire(
+            "https://rate-limit.com/manifest.json",
+            AddonRequestPriority::High
+        )
+        .now_or_never()
+        .and_then(Result::ok)
+        .is_some(),
+        "Request is started after the cooldown"
+    );
+}
+
+#[test]
+fn too_many_requests_response() {
+    fn fetch_handler(_request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        future::err(EnvError::TooManyRequests(Some(30))).boxed_env()
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let transport_url = Url::parse("https://too-many-requests.com/manifest.json").unwrap();
+    let path = ResourcePath::without_extra("catalog", "movie", "top");
+    assert_eq!(
+        block_on(TestEnv::addon_transport(&transport_url).resource(&path)).err(),
+        Some(EnvError::TooManyRequests(Some(30))),
+        "Response error is returned"
+    );
+    assert_eq!(
+        block_on(TestEnv::addon_transport(&transport_url).resource(&path)).err(),
+        Some(EnvError::TooManyRequests(Some(30))),
+        "Second request fails while the host is rate limited"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().len(),
+        1,
+        "Second request has not been sent"
+    );
+}
+
+#[test]
+fn reset() {
+    let _env_mutex = TestEnv::reset();
+    let permit = acquire(
+        "https://reset.com/manifest.json",
+        AddonRequestPriority::Normal,
+    )
+    .now_or_never()
+    .expect("permit is not ready")
+    .expect("permit is not acquired");
+    permit.rate_limit(TestEnv::now() + Duration::seconds(60));
+    drop(permit);
+    reset_addon_scheduler::<TestEnv>();
+    assert!(
+        acquire(
+            "https://reset.com/manifest.json",
+            AddonRequestPriority::Normal
+        )
+        .now_or_never()
+        .and_then(Result::ok)
+        .is_some(),
+        "Rate limit is dropped by the reset"
+    );
+}
@@ -3,7 +3,7 @@ use crate::models::common::{Loadable, ResourceLoadable};
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::{Action, ActionLoad};
 use crate::runtime::{EnvFutureExt, Runtime, RuntimeAction, RuntimeEvent, TryEnvFuture};
-use crate::types::addon::ResourceResponse;
+use crate::types::addon::{ResourceResponse, ResourceResponseWithMetadata};
 use crate::types::resource::MetaItemPreview;
 use crate::unit_tests::{
     default_fetch_handler, Request, TestEnv, EVENTS, FETCH_HANDLER, REQUESTS, STATES,
@@ -29,9 +29,11 @@ fn load_action() {
                 if url == "https://v3-cinemeta.strem.io/catalog/movie/top.json"
                     && method == "GET" =>
             {
-                future::ok(Box::new(ResourceResponse::Metas {
-                    metas: vec![MetaItemPreview::default()],
-                }) as Box<dyn Any + Send>)
+                future::ok(Box::new(ResourceResponseWithMetadata::from(
+                    ResourceResponse::Metas {
+                        metas: vec![MetaItemPreview::default()],
+                    },
+                )) as Box<dyn Any + Send>)
                 .boxed_env()
             }
             _ => default_fetch_handler(request),
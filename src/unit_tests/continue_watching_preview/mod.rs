@@ -0,0 +1 @@
+mod up_next;
@@ -0,0 +1,310 @@
+use crate::constants::LIBRARY_VIDEOS_STORAGE_KEY;
+use crate::deep_links::LibraryItemDeepLinks;
+use crate::models::continue_watching_preview::ContinueWatchingPreview;
+use crate::models::ctx::Ctx;
+use crate::models::meta_details::{MetaDetails, Selected};
+use crate::models::notifications::Notifications;
+use crate::runtime::msg::{Action, ActionLoad};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{
+    Descriptor, Manifest, ManifestCatalog, ManifestExtra, ManifestResource, ResourcePath,
+    ResourceResponse, ResourceResponseWithMetadata,
+};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState, LibraryVideosBucket};
+use crate::types::profile::Profile;
+use crate::types::resource::{MetaItem, MetaItemPreview, SeriesInfo, Video};
+use crate::unit_tests::{default_fetch_handler, Request, TestEnv, FETCH_HANDLER, NOW, STORAGE};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use futures::future;
+use semver::Version;
+use std::any::Any;
+use std::collections::HashMap;
+use stremio_derive::Model;
+use url::Url;
+
+#[derive(Model, Default)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+    meta_details: MetaDetails,
+    notifications: Notifications,
+    continue_watching_preview: ContinueWatchingPreview,
+}
+
+fn video(season: u32, episode: u32, released_day: u32) -> Video {
+    Video {
+        id: format!("tt1:{}:{}", season, episode),
+        title: String::default(),
+        released: Some(
+            Utc.with_ymd_and_hms(2020, 1, released_day, 0, 0, 0)
+                .unwrap(),
+        ),
+        overview: None,
+        thumbnail: None,
+        streams: vec![],
+        series_info: Some(SeriesInfo { season, episode }),
+        trailer_streams: vec![],
+    }
+}
+
+fn videos() -> Vec<Video> {
+    vec![
+        video(1, 1, 1),
+        video(0, 1, 3),
+        video(1, 2, 5),
+        video(1, 3, 20),
+    ]
+}
+
+// The videos as returned by the addons, with the details which are not cached
+fn meta_item(videos: Vec<Video>) -> MetaItem {
+    MetaItem {
+        preview: MetaItemPreview {
+            id: "tt1".to_owned(),
+            r#type: "series".to_owned(),
+            name: "Series".to_owned(),
+            ..MetaItemPreview::default()
+        },
+        videos: videos
+            .into_iter()
+            .map(|video| Video {
+                title: "Episode".to_owned(),
+                overview: Some("Overview".to_owned()),
+                thumbnail: Some("https://thumbnail".to_owned()),
+                ..video
+            })
+            .collect(),
+    }
+}
+
+fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+    match request {
+        Request { url, method, .. }
+            if url == "https://v3-cinemeta.strem.io/meta/series/tt1.json" && method == "GET" =>
+        {
+            future::ok(
+                Box::new(ResourceResponseWithMetadata::from(ResourceResponse::Meta {
+                    meta: meta_item(videos()),
+                })) as Box<dyn Any + Send>,
+            )
+            .boxed_env()
+        }
+        Request { url, method, .. }
+            if url == "https://notifications/catalog/series/lastvideos/lastVideosIds=tt1.json"
+                && method == "GET" =>
+        {
+            future::ok(Box::new(ResourceResponseWithMetadata::from(
+                ResourceResponse::MetasDetailed {
+                    metas_detailed: vec![meta_item(vec![video(1, 3, 8), video(1, 4, 9)])],
+                },
+            )) as Box<dyn Any + Send>)
+            .boxed_env()
+        }
+        _ => default_fetch_handler(request),
+    }
+}
+
+fn library_item(time_offset: u64, watched: bool) -> LibraryItem {
+    let mut state = LibraryItemState {
+        video_id: Some("tt1:1:1".to_owned()),
+        time_offset,
+        ..Default::default()
+    };
+    if watched {
+        state.set_videos_watched(&state.watched_bitfield(&videos()), &["tt1:1:1"], true);
+    };
+    LibraryItem {
+        id: "tt1".to_owned(),
+        name: "Series".to_owned(),
+        r#type: "series".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap(),
+        state,
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    }
+}
+
+fn load_meta_details(library_item: LibraryItem) -> Runtime<TestEnv, TestModel> {
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 10, 0, 0, 0).unwrap();
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item]),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::MetaDetails(Selected {
+                meta_path: ResourcePath::without_extra("meta", "series", "tt1"),
+                stream_path: None,
+            })),
+        })
+    });
+    runtime
+}
+
+#[test]
+fn up_next_resurfaces_watched_series() {
+    let _env_mutex = TestEnv::reset();
+    let library_item = library_item(0, true);
+    let runtime = load_meta_details(library_item.to_owned());
+    let model = runtime.model().unwrap();
+    assert_eq!(
+        model.ctx.library_videos.videos.get("tt1"),
+        Some(&videos()),
+        "Meta videos cached"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(LIBRARY_VIDEOS_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<LibraryVideosBucket>(data).unwrap()),
+        Some(model.ctx.library_videos.to_owned()),
+        "Cached videos persisted in storage"
+    );
+    assert_eq!(
+        model.continue_watching_preview.library_items,
+        vec![library_item.to_owned()],
+        "Series with a newly released episode resurfaced"
+    );
+    let up_next_video = model.continue_watching_preview.up_next_videos.get("tt1");
+    assert_eq!(
+        up_next_video,
+        Some(&video(1, 2, 5)),
+        "Special and unreleased episodes skipped"
+    );
+    assert_eq!(
+        LibraryItemDeepLinks::from((&library_item, up_next_video)).meta_details_streams,
+        Some("stremio:///detail/series/tt1/tt1%3A1%3A2".to_owned()),
+        "Deep link targets the up next episode"
+    );
+}
+
+#[test]
+fn up_next_requires_watched_episode() {
+    let _env_mutex = TestEnv::reset();
+    let library_item = library_item(1000, false);
+    let runtime = load_meta_details(library_item.to_owned());
+    let model = runtime.model().unwrap();
+    assert_eq!(
+        model.continue_watching_preview.library_items,
+        vec![library_item],
+        "Item in progress listed"
+    );
+    assert!(
+        model.continue_watching_preview.up_next_videos.is_empty(),
+        "No up next episode while the current one is not watched"
+    );
+}
+
+#[test]
+fn up_next_refreshed_from_notifications() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 10, 0, 0, 0).unwrap();
+    let cached_videos = vec![video(1, 1, 1), video(1, 2, 5), video(1, 3, 20)];
+    let mut library_item = library_item(0, false);
+    library_item.state.video_id = Some("tt1:1:2".to_owned());
+    library_item.state.set_videos_watched(
+        &library_item.state.watched_bitfield(&cached_videos),
+        &["tt1:1:1", "tt1:1:2"],
+        true,
+    );
+    let notifications_addon = Descriptor {
+        manifest: Manifest {
+            id: "notifications".to_owned(),
+            version: Version::new(0, 0, 1),
+            name: "Notifications".to_owned(),
+            contact_email: None,
+            description: None,
+            logo: None,
+            background: None,
+            types: vec!["series".to_owned()],
+            resources: vec![ManifestResource::Short("meta".to_owned())],
+            id_prefixes: None,
+            catalogs: vec![ManifestCatalog {
+                id: "lastvideos".to_owned(),
+                r#type: "series".to_owned(),
+                name: None,
+                extra: ManifestExtra::Short {
+                    required: vec![],
+                    supported: vec!["lastVideosIds".to_owned()],
+                },
+            }],
+            addon_catalogs: vec![],
+            behavior_hints: Default::default(),
+        },
+        transport_url: Url::parse("https://notifications/manifest.json").unwrap(),
+        flags: Default::default(),
+    };
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![notifications_addon],
+                    ..Default::default()
+                },
+                library: LibraryBucket::new(None, vec![library_item.to_owned()]),
+                library_videos: LibraryVideosBucket {
+                    uid: None,
+                    videos: HashMap::from([("tt1".to_owned(), cached_videos)]),
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Notifications),
+        })
+    });
+    let model = runtime.model().unwrap();
+    assert_eq!(
+        model.ctx.library_videos.videos.get("tt1"),
+        Some(&vec![
+            video(1, 1, 1),
+            video(1, 2, 5),
+            video(1, 3, 8),
+            video(1, 4, 9),
+        ]),
+        "Latest videos merged into the cached ones"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(LIBRARY_VIDEOS_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<LibraryVideosBucket>(data).unwrap()),
+        Some(model.ctx.library_videos.to_owned()),
+        "Refreshed videos persisted in storage"
+    );
+    assert_eq!(
+        model.continue_watching_preview.library_items,
+        vec![library_item],
+        "Series with a newly released episode resurfaced"
+    );
+    assert_eq!(
+        model.continue_watching_preview.up_next_videos.get("tt1"),
+        Some(&video(1, 3, 8)),
+        "Up next episode refreshed"
+    );
+}
@@ -67,6 +67,8 @@ fn actionctx_addtolibrary() {
         poster: None,
         poster_shape: Default::default(),
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let _env_mutex = TestEnv::reset();
     *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
@@ -200,6 +202,8 @@ fn actionctx_addtolibrary_already_added() {
             has_scheduled_videos: false,
             other: Default::default(),
         },
+        genres: vec![],
+        released: None,
     };
     let _env_mutex = TestEnv::reset();
     *NOW.write().unwrap() = Utc.ymd(2020, 1, 2).and_hms_milli(0, 0, 0, 0);
@@ -225,6 +229,8 @@ fn actionctx_addtolibrary_already_added() {
                                 ..LibraryItemState::default()
                             },
                             behavior_hints: Default::default(),
+                            genres: vec![],
+                            released: None,
                         },
                     )]
                     .into_iter()
@@ -4,8 +4,10 @@ use crate::runtime::msg::{Action, ActionCtx};
 use crate::runtime::{Effects, Env, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
 use crate::types::api::{
     APIResult, AuthRequest, AuthResponse, CollectionResponse, GDPRConsentRequest,
+    LibraryItemModified,
 };
-use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::library::LibraryBucket;
+use crate::types::lists::UserList;
 use crate::types::profile::{Auth, AuthKey, GDPRConsent, Profile, User};
 use crate::unit_tests::{
     default_fetch_handler, Request, TestEnv, FETCH_HANDLER, REQUESTS, STORAGE,
@@ -62,14 +64,31 @@ fn actionctx_authenticate_login() {
                     },
                 }) as Box<dyn Any + Send>).boxed_env()
             }
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/datastoreMeta"
+                && method == "POST"
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: Vec::<LibraryItemModified>::new(),
+                }) as Box<dyn Any + Send>).boxed_env()
+            }
             Request {
                 url, method, body, ..
             } if url == "https://api.strem.io/api/datastoreGet"
                 && method == "POST"
-                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}" =>
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}" =>
             {
                 future::ok(Box::new(APIResult::Ok {
-                    result: Vec::<LibraryItem>::new(),
+                    result: vec![UserList {
+                        id: "list".to_owned(),
+                        name: "List".to_owned(),
+                        items: vec![],
+                        removed: false,
+                        ctime: TestEnv::now(),
+                        mtime: TestEnv::now(),
+                    }],
                 }) as Box<dyn Any + Send>).boxed_env()
             }
             _ => default_fetch_handler(request),
@@ -121,6 +140,16 @@ fn actionctx_authenticate_login() {
         },
         "library updated successfully in memory"
     );
+    assert!(
+        runtime
+            .model()
+            .unwrap()
+            .ctx
+            .lists
+            .lists
+            .contains_key("list"),
+        "lists pulled successfully"
+    );
     assert_eq!(
         serde_json::from_str::<Profile>(&STORAGE.read().unwrap().get(PROFILE_STORAGE_KEY).unwrap())
             .unwrap(),
@@ -168,8 +197,8 @@ fn actionctx_authenticate_login() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().len(),
-        3,
-        "Three requests have been sent"
+        4,
+        "Four requests have been sent"
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(0).unwrap().to_owned(),
@@ -194,15 +223,24 @@ fn actionctx_authenticate_login() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(2).unwrap().to_owned(),
+        Request {
+            url: "https://api.strem.io/api/datastoreMeta".to_owned(),
+            method: "POST".to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}".to_owned(),
+            ..Default::default()
+        },
+        "DatastoreMeta request has been sent"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().get(3).unwrap().to_owned(),
         Request {
             url: "https://api.strem.io/api/datastoreGet".to_owned(),
             method: "POST".to_owned(),
-            body:
-                "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}"
-                    .to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}"
+                .to_owned(),
             ..Default::default()
         },
-        "DatastoreGet request has been sent"
+        "Lists DatastoreGet request has been sent"
     );
 }
 
@@ -253,14 +291,24 @@ fn actionctx_authenticate_login_with_token() {
                     },
                 }) as Box<dyn Any + Send>).boxed_env()
             }
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/datastoreMeta"
+                && method == "POST"
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: Vec::<LibraryItemModified>::new(),
+                }) as Box<dyn Any + Send>).boxed_env()
+            }
             Request {
                 url, method, body, ..
             } if url == "https://api.strem.io/api/datastoreGet"
                 && method == "POST"
-                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}" =>
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}" =>
             {
                 future::ok(Box::new(APIResult::Ok {
-                    result: Vec::<LibraryItem>::new(),
+                    result: Vec::<UserList>::new(),
                 }) as Box<dyn Any + Send>).boxed_env()
             }
             _ => default_fetch_handler(request),
@@ -357,8 +405,8 @@ fn actionctx_authenticate_login_with_token() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().len(),
-        3,
-        "Three requests have been sent"
+        4,
+        "Four requests have been sent"
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(0).unwrap().to_owned(),
@@ -384,15 +432,24 @@ fn actionctx_authenticate_login_with_token() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(2).unwrap().to_owned(),
+        Request {
+            url: "https://api.strem.io/api/datastoreMeta".to_owned(),
+            method: "POST".to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}".to_owned(),
+            ..Default::default()
+        },
+        "DatastoreMeta request has been sent"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().get(3).unwrap().to_owned(),
         Request {
             url: "https://api.strem.io/api/datastoreGet".to_owned(),
             method: "POST".to_owned(),
-            body:
-                "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}"
-                    .to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}"
+                .to_owned(),
             ..Default::default()
         },
-        "DatastoreGet request has been sent"
+        "Lists DatastoreGet request has been sent"
     );
 }
 
@@ -443,14 +500,24 @@ fn actionctx_authenticate_register() {
                     },
                 }) as Box<dyn Any + Send>).boxed_env()
             }
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/datastoreMeta"
+                && method == "POST"
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: Vec::<LibraryItemModified>::new(),
+                }) as Box<dyn Any + Send>).boxed_env()
+            }
             Request {
                 url, method, body, ..
             } if url == "https://api.strem.io/api/datastoreGet"
                 && method == "POST"
-                && body == "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}" =>
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}" =>
             {
                 future::ok(Box::new(APIResult::Ok {
-                    result: Vec::<LibraryItem>::new(),
+                    result: Vec::<UserList>::new(),
                 }) as Box<dyn Any + Send>).boxed_env()
             }
             _ => default_fetch_handler(request),
@@ -557,8 +624,8 @@ fn actionctx_authenticate_register() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().len(),
-        3,
-        "Three requests have been sent"
+        4,
+        "Four requests have been sent"
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(0).unwrap().to_owned(),
@@ -583,14 +650,23 @@ fn actionctx_authenticate_register() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(2).unwrap().to_owned(),
+        Request {
+            url: "https://api.strem.io/api/datastoreMeta".to_owned(),
+            method: "POST".to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\"}".to_owned(),
+            ..Default::default()
+        },
+        "DatastoreMeta request has been sent"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().get(3).unwrap().to_owned(),
         Request {
             url: "https://api.strem.io/api/datastoreGet".to_owned(),
             method: "POST".to_owned(),
-            body:
-                "{\"authKey\":\"auth_key\",\"collection\":\"libraryItem\",\"ids\":[],\"all\":true}"
-                    .to_owned(),
+            body: "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[],\"all\":true}"
+                .to_owned(),
             ..Default::default()
         },
-        "DatastoreGet request has been sent"
+        "Lists DatastoreGet request has been sent"
     );
 }
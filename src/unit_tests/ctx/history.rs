@@ -0,0 +1,110 @@
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Action, ActionCtx, Internal, Msg};
+use crate::runtime::{Env, EnvFutureExt, TryEnvFuture, Update};
+use crate::types::api::AuthRequest;
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::types::profile::{Auth, AuthKey, GDPRConsent, User};
+use crate::unit_tests::{Request, TestEnv, FETCH_HANDLER};
+use chrono::prelude::{TimeZone, Utc};
+use chrono::Duration;
+use futures::future;
+use std::any::Any;
+
+fn history_entry() -> HistoryEntry {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    HistoryEntry {
+        id: "tt1".to_owned(),
+        r#type: "movie".to_owned(),
+        name: "Movie".to_owned(),
+        video_id: "tt1".to_owned(),
+        start,
+        end: start + Duration::minutes(90),
+        duration: 5400000,
+    }
+}
+
+// The effects are not run, only the state of the history is checked
+fn fetch_handler(_request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+    future::pending().boxed_env()
+}
+
+fn login(ctx: &mut Ctx, user_id: &str) {
+    let auth_request = AuthRequest::Login {
+        email: "user_email".into(),
+        password: "user_password".into(),
+        facebook: false,
+    };
+    let auth = Auth {
+        key: AuthKey("auth_key".to_owned()),
+        user: User {
+            id: user_id.to_owned(),
+            email: "user_email".to_owned(),
+            fb_id: None,
+            avatar: None,
+            last_modified: TestEnv::now(),
+            date_registered: TestEnv::now(),
+            gdpr_consent: GDPRConsent {
+                tos: true,
+                privacy: true,
+                marketing: true,
+            },
+        },
+    };
+    Update::<TestEnv>::update(
+        ctx,
+        &Msg::Action(Action::Ctx(ActionCtx::Authenticate(
+            auth_request.to_owned(),
+        ))),
+    );
+    Update::<TestEnv>::update(
+        ctx,
+        &Msg::Internal(Internal::CtxAuthResult(
+            auth_request,
+            Ok((auth, vec![], vec![])),
+        )),
+    );
+}
+
+#[test]
+fn ctx_login_keeps_anonymous_history() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let mut ctx = Ctx {
+        history: HistoryBucket::new(None, vec![history_entry()]),
+        ..Default::default()
+    };
+    login(&mut ctx, "user_id");
+    assert_eq!(
+        ctx.history,
+        HistoryBucket::new(Some("user_id".to_owned()), vec![history_entry()]),
+        "Anonymous history is taken over by the user"
+    );
+}
+
+#[test]
+fn ctx_login_keeps_history_of_same_user() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let mut ctx = Ctx {
+        history: HistoryBucket::new(Some("user_id".to_owned()), vec![history_entry()]),
+        ..Default::default()
+    };
+    Update::<TestEnv>::update(&mut ctx, &Msg::Action(Action::Ctx(ActionCtx::Logout)));
+    assert_eq!(
+        ctx.history,
+        HistoryBucket::new(Some("user_id".to_owned()), vec![history_entry()]),
+        "History is kept on logout"
+    );
+    login(&mut ctx, "user_id");
+    assert_eq!(
+        ctx.history,
+        HistoryBucket::new(Some("user_id".to_owned()), vec![history_entry()]),
+        "History is kept on login of the same user"
+    );
+    login(&mut ctx, "other_user_id");
+    assert_eq!(
+        ctx.history,
+        HistoryBucket::new(Some("other_user_id".to_owned()), vec![]),
+        "History of another user is not taken over"
+    );
+}
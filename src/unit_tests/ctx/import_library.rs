@@ -0,0 +1,228 @@
+use crate::constants::LIBRARY_RECENT_STORAGE_KEY;
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Action, ActionCtx, Event};
+use crate::runtime::{Effects, Runtime, RuntimeAction, RuntimeEvent};
+use crate::types::library::{
+    LibraryBucket, LibraryExportFormat, LibraryImportRowReport, LibraryImportStatus, LibraryItem,
+    LibraryItemState,
+};
+use crate::unit_tests::{TestEnv, EVENTS, REQUESTS, STORAGE};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use enclose::enclose;
+use std::sync::{Arc, RwLock};
+use stremio_derive::Model;
+
+#[test]
+fn actionctx_import_library() {
+    #[derive(Model, Default, Clone)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let library_item = LibraryItem {
+        id: "tt1".to_owned(),
+        name: "Movie".to_owned(),
+        r#type: "movie".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state: LibraryItemState {
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            time_offset: 100,
+            duration: 1000,
+            no_notif: true,
+            last_vid_released: Some(Utc.with_ymd_and_hms(2019, 12, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        },
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    };
+    let _env_mutex = TestEnv::reset();
+    let (runtime, rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item.to_owned()]),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    let runtime = Arc::new(RwLock::new(runtime));
+    TestEnv::run_with_runtime(
+        rx,
+        runtime.clone(),
+        enclose!((runtime) move || {
+            let runtime = runtime.read().unwrap();
+            runtime.dispatch(RuntimeAction {
+                field: None,
+                action: Action::Ctx(ActionCtx::ImportLibrary {
+                    format: LibraryExportFormat::Csv,
+                    content: "id,type,name,in_library,watched,last_watched,time_offset,duration\n\
+                              tt1,movie,Movie,false,true,2020-01-02T00:00:00Z,500,1000\n\
+                              tt2,series,Series,true,false,,0,0\n\
+                              tt1,movie,Movie,false,true,,0,0\n\
+                              ,movie,Nothing,true,false,,0,0\n"
+                        .to_owned(),
+                }),
+            });
+        }),
+    );
+    let runtime = runtime.read().unwrap();
+    let library = runtime.model().unwrap().ctx.library.to_owned();
+    let imported_movie = library.items.get("tt1").expect("movie is in the library");
+    assert!(!imported_movie.removed, "import does not remove items");
+    assert_eq!(imported_movie.state.flagged_watched, 1);
+    assert_eq!(imported_movie.state.time_offset, 500);
+    assert_eq!(
+        imported_movie.state.last_watched,
+        Some(Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap())
+    );
+    assert_eq!(imported_movie.ctime, library_item.ctime);
+    assert!(imported_movie.state.no_notif, "notifications stay disabled");
+    assert_eq!(
+        imported_movie.state.last_vid_released,
+        library_item.state.last_vid_released
+    );
+    let imported_series = library.items.get("tt2").expect("series is added");
+    assert!(!imported_series.removed && !imported_series.temp);
+    assert_eq!(imported_series.name, "Series");
+    assert!(
+        EVENTS
+            .read()
+            .unwrap()
+            .contains(&RuntimeEvent::CoreEvent(Event::LibraryImported {
+                report: vec![
+                    LibraryImportRowReport {
+                        row: 0,
+                        id: Some("tt1".to_owned()),
+                        status: LibraryImportStatus::Updated,
+                    },
+                    LibraryImportRowReport {
+                        row: 1,
+                        id: Some("tt2".to_owned()),
+                        status: LibraryImportStatus::Added,
+                    },
+                    LibraryImportRowReport {
+                        row: 2,
+                        id: Some("tt1".to_owned()),
+                        status: LibraryImportStatus::Unchanged,
+                    },
+                    LibraryImportRowReport {
+                        row: 3,
+                        id: None,
+                        status: LibraryImportStatus::Invalid("Missing id".to_owned()),
+                    },
+                ]
+            })),
+        "import report emitted"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(LIBRARY_RECENT_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<LibraryBucket>(data).unwrap()),
+        Some(library),
+        "library updated successfully in storage"
+    );
+    assert!(
+        REQUESTS.read().unwrap().is_empty(),
+        "No requests have been sent"
+    );
+}
+
+#[test]
+fn actionctx_import_library_trakt() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let library_item = LibraryItem {
+        id: "tt1".to_owned(),
+        name: "Series".to_owned(),
+        r#type: "series".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state: LibraryItemState {
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            video_id: Some("tt1:1:1".to_owned()),
+            time_offset: 100,
+            duration: 1000,
+            ..Default::default()
+        },
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    };
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item]),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportLibrary {
+                format: LibraryExportFormat::Trakt,
+                content: r#"{
+                    "playback": [
+                        { "type": "episode", "progress": 50.0, "paused_at": "2020-01-02T00:00:00Z", "episode": { "season": 1, "number": 2 }, "show": { "title": "Series", "ids": { "imdb": "tt1" } } }
+                    ]
+                }"#
+                .to_owned(),
+            }),
+        })
+    });
+    let library = runtime.model().unwrap().ctx.library.to_owned();
+    let imported_series = library.items.get("tt1").expect("series is in the library");
+    assert_eq!(imported_series.state.video_id, Some("tt1:1:2".to_owned()));
+    assert_eq!(
+        imported_series.state.time_offset, 500,
+        "progress is resolved against the known duration"
+    );
+    assert_eq!(imported_series.state.duration, 1000);
+    assert_eq!(
+        imported_series.state.flagged_watched, 0,
+        "an episode does not flag the series as watched"
+    );
+}
+
+#[test]
+fn actionctx_import_library_invalid() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) =
+        Runtime::<TestEnv, _>::new(TestModel::default(), Effects::none().unchanged(), 1000);
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportLibrary {
+                format: LibraryExportFormat::Json,
+                content: "id,type\ntt1,movie\n".to_owned(),
+            }),
+        })
+    });
+    assert!(runtime.model().unwrap().ctx.library.items.is_empty());
+    assert!(STORAGE.read().unwrap().is_empty(), "Nothing is stored");
+}
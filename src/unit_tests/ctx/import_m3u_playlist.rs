@@ -0,0 +1,273 @@
+use crate::addon_transport::{m3u_playlist_storage_key, M3UPlaylist};
+use crate::constants::PROFILE_STORAGE_KEY;
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Action, ActionCtx};
+use crate::runtime::{Effects, Env, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::api::{APIResult, SuccessResponse};
+use crate::types::profile::{Auth, AuthKey, GDPRConsent, Profile, User};
+use crate::types::True;
+use crate::unit_tests::{
+    default_fetch_handler, Request, TestEnv, FETCH_HANDLER, REQUESTS, STORAGE,
+};
+use futures::future;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+const PLAYLIST_CONTENT: &str =
+    "#EXTM3U\n#EXTINF:-1 group-title=\"News\",News\nhttps://iptv.com/news.m3u8\n";
+
+#[test]
+fn actionctx_import_m3u_playlist() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportM3UPlaylist {
+                name: "Local TV".to_owned(),
+                content: PLAYLIST_CONTENT.to_owned(),
+            }),
+        })
+    });
+    let addons = runtime.model().unwrap().ctx.profile.addons.to_owned();
+    assert_eq!(addons.len(), 1, "playlist installed as an addon");
+    assert_eq!(
+        addons[0].transport_url,
+        Url::parse("m3u://local-tv-dfc293c3").unwrap()
+    );
+    assert_eq!(addons[0].manifest.name, "Local TV");
+    let storage = STORAGE.read().unwrap();
+    assert_eq!(
+        storage
+            .get(&m3u_playlist_storage_key("local-tv-dfc293c3"))
+            .map(|data| serde_json::from_str::<M3UPlaylist>(data)
+                .unwrap()
+                .entries
+                .len()),
+        Some(1),
+        "playlist stored"
+    );
+    assert_eq!(
+        storage
+            .get(PROFILE_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<Profile>(data).unwrap().addons),
+        Some(addons),
+        "addon updated successfully in storage"
+    );
+    assert!(
+        REQUESTS.read().unwrap().is_empty(),
+        "No requests have been sent"
+    );
+}
+
+#[test]
+fn actionctx_import_m3u_playlist_invalid() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportM3UPlaylist {
+                name: "Local TV".to_owned(),
+                content: "<html></html>".to_owned(),
+            }),
+        })
+    });
+    assert!(runtime.model().unwrap().ctx.profile.addons.is_empty());
+    assert!(STORAGE.read().unwrap().is_empty(), "Nothing is stored");
+}
+
+#[test]
+fn actionctx_import_m3u_playlist_with_user() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        match request {
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/addonCollectionSet"
+                && method == "POST"
+                && body == "{\"type\":\"AddonCollectionSet\",\"authKey\":\"auth_key\",\"addons\":[]}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: SuccessResponse { success: True {} },
+                }) as Box<dyn Any + Send>).boxed_env()
+            }
+            Request { url, method, .. }
+                if url == "https://api.strem.io/api/logout" && method == "POST" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: SuccessResponse { success: True {} },
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    auth: Some(Auth {
+                        key: AuthKey("auth_key".to_owned()),
+                        user: User {
+                            id: "user_id".to_owned(),
+                            email: "user_email".to_owned(),
+                            fb_id: None,
+                            avatar: None,
+                            last_modified: TestEnv::now(),
+                            date_registered: TestEnv::now(),
+                            gdpr_consent: GDPRConsent {
+                                tos: true,
+                                privacy: true,
+                                marketing: true,
+                            },
+                        },
+                    }),
+                    addons: vec![],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportM3UPlaylist {
+                name: "Local TV".to_owned(),
+                content: PLAYLIST_CONTENT.to_owned(),
+            }),
+        })
+    });
+    assert_eq!(
+        runtime.model().unwrap().ctx.profile.addons.len(),
+        1,
+        "playlist installed as an addon"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().len(),
+        1,
+        "One request has been sent"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().first().unwrap().body,
+        "{\"type\":\"AddonCollectionSet\",\"authKey\":\"auth_key\",\"addons\":[]}",
+        "playlist addon is not pushed to the API"
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::Logout),
+        })
+    });
+    assert!(
+        !STORAGE
+            .read()
+            .unwrap()
+            .contains_key(&m3u_playlist_storage_key("local-tv-dfc293c3")),
+        "playlist removed from storage on logout"
+    );
+}
+
+#[test]
+fn actionctx_uninstall_m3u_playlist() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::ImportM3UPlaylist {
+                name: "Local TV".to_owned(),
+                content: PLAYLIST_CONTENT.to_owned(),
+            }),
+        })
+    });
+    let addon = runtime.model().unwrap().ctx.profile.addons[0].to_owned();
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::UninstallAddon(addon)),
+        })
+    });
+    assert!(runtime.model().unwrap().ctx.profile.addons.is_empty());
+    assert!(
+        !STORAGE
+            .read()
+            .unwrap()
+            .contains_key(&m3u_playlist_storage_key("local-tv-dfc293c3")),
+        "playlist removed from storage"
+    );
+    assert!(
+        runtime.model().unwrap().ctx.undo.entries.is_empty(),
+        "uninstalled playlist can't be undone"
+    );
+}
+
+#[test]
+fn m3u_playlist_ids_are_unique() {
+    let playlist = |name: &str| M3UPlaylist::parse(name, PLAYLIST_CONTENT).unwrap().id;
+    assert_eq!(playlist("Local TV"), "local-tv-dfc293c3");
+    assert_eq!(playlist("Local TV"), playlist("Local TV"));
+    assert_ne!(playlist("Local TV"), playlist("local tv"));
+    assert_ne!(playlist("Local TV!"), playlist("Local TV?"));
+}
@@ -0,0 +1,334 @@
+use crate::constants::LISTS_STORAGE_KEY;
+use crate::models::ctx::{Ctx, CtxError, OtherError};
+use crate::runtime::msg::{Action, ActionCtx, Event};
+use crate::runtime::{
+    Effects, Env, EnvFutureExt, Runtime, RuntimeAction, RuntimeEvent, TryEnvFuture,
+};
+use crate::types::api::{APIResult, LibraryItemModified, SuccessResponse};
+use crate::types::lists::{ListsBucket, UserList, UserListItem};
+use crate::types::profile::{Auth, AuthKey, GDPRConsent, Profile, User};
+use crate::types::resource::MetaItemPreview;
+use crate::types::True;
+use crate::unit_tests::{
+    default_fetch_handler, Request, TestEnv, EVENTS, FETCH_HANDLER, NOW, REQUESTS, STORAGE,
+};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use enclose::enclose;
+use futures::future;
+use std::any::Any;
+use std::sync::{Arc, RwLock};
+use stremio_derive::Model;
+
+#[derive(Model, Default, Clone)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+}
+
+fn meta_preview(id: &str) -> MetaItemPreview {
+    MetaItemPreview {
+        id: id.to_owned(),
+        r#type: "movie".to_owned(),
+        name: id.to_owned(),
+        poster: None,
+        background: None,
+        logo: None,
+        description: None,
+        release_info: None,
+        runtime: None,
+        released: None,
+        poster_shape: Default::default(),
+        links: vec![],
+        trailer_streams: vec![],
+        behavior_hints: Default::default(),
+    }
+}
+
+fn user_list(id: &str, mtime_day: u32) -> UserList {
+    UserList {
+        id: id.to_owned(),
+        name: id.to_owned(),
+        items: vec![],
+        removed: false,
+        ctime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        mtime: Utc.with_ymd_and_hms(2020, 1, mtime_day, 0, 0, 0).unwrap(),
+    }
+}
+
+fn dispatch(runtime: &Runtime<TestEnv, TestModel>, action: ActionCtx) {
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(action),
+        })
+    });
+}
+
+fn stored_lists() -> Option<ListsBucket> {
+    STORAGE
+        .read()
+        .unwrap()
+        .get(LISTS_STORAGE_KEY)
+        .map(|data| serde_json::from_str::<ListsBucket>(data).unwrap())
+}
+
+#[test]
+fn actionctx_lists_lifecycle() {
+    let _env_mutex = TestEnv::reset();
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let (runtime, _rx) =
+        Runtime::<TestEnv, _>::new(TestModel::default(), Effects::none().unchanged(), 1000);
+    dispatch(&runtime, ActionCtx::CreateList(" Favourites ".to_owned()));
+    let id = Utc
+        .with_ymd_and_hms(2020, 1, 1, 0, 0, 0)
+        .unwrap()
+        .timestamp_millis()
+        .to_string();
+    assert_eq!(
+        runtime.model().unwrap().ctx.lists.lists.get(&id),
+        Some(&UserList::new::<TestEnv>(
+            id.to_owned(),
+            "Favourites".to_owned()
+        )),
+        "List created in memory"
+    );
+    dispatch(&runtime, ActionCtx::CreateList("Second".to_owned()));
+    assert_eq!(
+        runtime.model().unwrap().ctx.lists.active_lists().len(),
+        2,
+        "List with a colliding timestamp created"
+    );
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap();
+    dispatch(
+        &runtime,
+        ActionCtx::AddToList {
+            id: id.to_owned(),
+            meta_item: meta_preview("tt1"),
+        },
+    );
+    dispatch(
+        &runtime,
+        ActionCtx::AddToList {
+            id: id.to_owned(),
+            meta_item: meta_preview("tt1"),
+        },
+    );
+    dispatch(
+        &runtime,
+        ActionCtx::AddToList {
+            id: id.to_owned(),
+            meta_item: meta_preview("tt2"),
+        },
+    );
+    dispatch(
+        &runtime,
+        ActionCtx::RenameList {
+            id: id.to_owned(),
+            name: "Watch later".to_owned(),
+        },
+    );
+    {
+        let model = runtime.model().unwrap();
+        let list = model.ctx.lists.lists.get(&id).unwrap();
+        assert_eq!(list.name, "Watch later", "List renamed");
+        assert_eq!(
+            list.items,
+            vec![
+                UserListItem::from((&meta_preview("tt1"), std::marker::PhantomData::<TestEnv>)),
+                UserListItem::from((&meta_preview("tt2"), std::marker::PhantomData::<TestEnv>)),
+            ],
+            "Items added once"
+        );
+        assert_eq!(list.mtime, TestEnv::now(), "List mtime updated");
+    }
+    dispatch(
+        &runtime,
+        ActionCtx::RemoveFromList {
+            id: id.to_owned(),
+            item_id: "tt1".to_owned(),
+        },
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.lists.lists[&id]
+            .items
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["tt2"],
+        "Item removed from list"
+    );
+    assert_eq!(
+        stored_lists().as_ref(),
+        Some(&runtime.model().unwrap().ctx.lists),
+        "Lists updated successfully in storage"
+    );
+    dispatch(&runtime, ActionCtx::DeleteList(id.to_owned()));
+    {
+        let model = runtime.model().unwrap();
+        let list = model.ctx.lists.lists.get(&id).unwrap();
+        assert!(list.removed, "List flagged as removed");
+        assert!(list.items.is_empty(), "Items of removed list cleared");
+        assert_eq!(model.ctx.lists.active_lists().len(), 1, "One list left");
+    }
+    assert_eq!(
+        stored_lists().map(|lists| lists.lists[&id].removed),
+        Some(true),
+        "Removed list persisted in storage"
+    );
+    assert!(
+        REQUESTS.read().unwrap().is_empty(),
+        "No requests have been sent"
+    );
+}
+
+#[test]
+fn actionctx_lists_not_found() {
+    let _env_mutex = TestEnv::reset();
+    let (runtime, rx) =
+        Runtime::<TestEnv, _>::new(TestModel::default(), Effects::none().unchanged(), 1000);
+    let runtime = Arc::new(RwLock::new(runtime));
+    TestEnv::run_with_runtime(
+        rx,
+        runtime.clone(),
+        enclose!((runtime) move || {
+            let runtime = runtime.read().unwrap();
+            runtime.dispatch(RuntimeAction {
+                field: None,
+                action: Action::Ctx(ActionCtx::RenameList {
+                    id: "missing".to_owned(),
+                    name: "name".to_owned(),
+                }),
+            });
+        }),
+    );
+    assert!(
+        EVENTS
+            .read()
+            .unwrap()
+            .contains(&RuntimeEvent::CoreEvent(Event::Error {
+                error: CtxError::from(OtherError::ListNotFound),
+                source: Box::new(Event::ListRenamed {
+                    id: "missing".to_owned(),
+                }),
+            })),
+        "List not found error emitted"
+    );
+    assert!(STORAGE.read().unwrap().get(LISTS_STORAGE_KEY).is_none());
+}
+
+#[test]
+fn actionctx_sync_lists_with_api() {
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        match request {
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/datastoreMeta"
+                && method == "POST"
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"userList\"}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: vec![
+                        LibraryItemModified(
+                            "remote".to_owned(),
+                            Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap(),
+                        ),
+                        LibraryItemModified(
+                            "shared".to_owned(),
+                            Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+                        ),
+                    ],
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request {
+                url, method, body, ..
+            } if url == "https://api.strem.io/api/datastoreGet"
+                && method == "POST"
+                && body == "{\"authKey\":\"auth_key\",\"collection\":\"userList\",\"ids\":[\"remote\"],\"all\":false}" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: vec![user_list("remote", 2)],
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, method, .. }
+                if url == "https://api.strem.io/api/datastorePut" && method == "POST" =>
+            {
+                future::ok(Box::new(APIResult::Ok {
+                    result: SuccessResponse { success: True {} },
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    auth: Some(Auth {
+                        key: AuthKey("auth_key".to_owned()),
+                        user: User {
+                            id: "user_id".to_owned(),
+                            email: "user_email".to_owned(),
+                            fb_id: None,
+                            avatar: None,
+                            last_modified: TestEnv::now(),
+                            date_registered: TestEnv::now(),
+                            gdpr_consent: GDPRConsent {
+                                tos: true,
+                                privacy: true,
+                                marketing: true,
+                            },
+                        },
+                    }),
+                    ..Default::default()
+                },
+                lists: ListsBucket::new(
+                    Some("user_id".to_owned()),
+                    vec![user_list("local", 1), user_list("shared", 3)],
+                ),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    dispatch(&runtime, ActionCtx::SyncListsWithAPI);
+    assert_eq!(
+        runtime.model().unwrap().ctx.lists.lists.get("remote"),
+        Some(&user_list("remote", 2)),
+        "Remote list pulled"
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.lists.lists.len(),
+        3,
+        "Local lists kept"
+    );
+    let requests = REQUESTS.read().unwrap();
+    let push_request = requests
+        .iter()
+        .find(|request| request.url == "https://api.strem.io/api/datastorePut")
+        .expect("Lists pushed to API");
+    let pushed = serde_json::from_str::<serde_json::Value>(&push_request.body).unwrap();
+    let mut pushed_ids = pushed["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|list| list["_id"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    pushed_ids.sort();
+    assert_eq!(
+        pushed_ids,
+        vec!["local".to_owned(), "shared".to_owned()],
+        "Local-only and locally newer lists pushed"
+    );
+    assert_eq!(
+        stored_lists().map(|lists| lists.lists.contains_key("remote")),
+        Some(true),
+        "Pulled lists persisted in storage"
+    );
+}
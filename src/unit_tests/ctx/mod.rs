@@ -1,12 +1,17 @@
 mod add_to_library;
 mod authenticate;
+mod history;
+mod import_library;
+mod import_m3u_playlist;
 mod install_addon;
+mod lists;
 mod logout;
 mod pull_addons_from_api;
 mod push_addons_to_api;
 mod remove_from_library;
 mod rewind_library_item;
 mod sync_library_with_api;
+mod undo;
 mod uninstall_addon;
 mod update_settings;
 mod upgrade_addon;
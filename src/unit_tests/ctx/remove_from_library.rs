@@ -49,6 +49,8 @@ fn actionctx_removefromlibrary() {
         poster: None,
         poster_shape: Default::default(),
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let library_item_removed = LibraryItem {
         removed: true,
@@ -163,6 +165,8 @@ fn actionctx_removefromlibrary_not_added() {
         poster: None,
         poster_shape: Default::default(),
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let _env_mutex = TestEnv::reset();
     STORAGE.write().unwrap().insert(
@@ -52,6 +52,8 @@ fn actionctx_rewindlibraryitem() {
         poster: None,
         poster_shape: Default::default(),
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let library_item_rewinded = LibraryItem {
         mtime: Utc.ymd(2020, 1, 2).and_hms_milli(0, 0, 0, 0),
@@ -172,6 +174,8 @@ fn actionctx_rewindlibraryitem_not_added() {
         poster: None,
         poster_shape: Default::default(),
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let _env_mutex = TestEnv::reset();
     STORAGE.write().unwrap().insert(
@@ -1,9 +1,9 @@
-use crate::constants::LIBRARY_RECENT_STORAGE_KEY;
+use crate::constants::{LIBRARY_RECENT_STORAGE_KEY, LIBRARY_SYNC_BATCH_SIZE};
 use crate::models::ctx::Ctx;
 use crate::runtime::msg::{Action, ActionCtx};
-use crate::runtime::{Effects, Env, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::runtime::{Effects, Env, EnvError, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
 use crate::types::api::{APIResult, LibraryItemModified, SuccessResponse};
-use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState, LibrarySyncProgress};
 use crate::types::profile::{Auth, AuthKey, GDPRConsent, Profile, User};
 use crate::types::True;
 use crate::unit_tests::{
@@ -15,6 +15,7 @@ use futures::future;
 use lazy_static::lazy_static;
 use serde::Deserialize;
 use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
 use stremio_derive::Model;
 
 #[test]
@@ -59,6 +60,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref LOCAL_NEWER_ITEM: LibraryItem = LibraryItem {
             id: "id2".to_owned(),
@@ -72,6 +75,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref REMOTE_NEWER_ITEM: LibraryItem = LibraryItem {
             id: "id3".to_owned(),
@@ -85,6 +90,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref LOCAL_ONLY_ITEM: LibraryItem = LibraryItem {
             id: "id4".to_owned(),
@@ -98,6 +105,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref LOCAL_OLD_REMOVED_ITEM: LibraryItem = LibraryItem {
             id: "id5".to_owned(),
@@ -111,6 +120,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc::now() - Duration::days(367),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref LOCAL_NEW_REMOVED_ITEM: LibraryItem = LibraryItem {
             id: "id6".to_owned(),
@@ -124,6 +135,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc::now() - Duration::days(3),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
         static ref LOCAL_OTHER_TYPE_ITEM: LibraryItem = LibraryItem {
             id: "id7".to_owned(),
@@ -137,6 +150,8 @@ fn actionctx_synclibrarywithapi_with_user() {
             mtime: Utc::now(),
             state: Default::default(),
             behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
         };
     }
     fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
@@ -179,10 +194,10 @@ fn actionctx_synclibrarywithapi_with_user() {
                     Result::Ok(body)
                         if body.auth_key == AuthKey("auth_key".to_owned())
                             && body.collection == "libraryItem"
-                            && body.changes.len() == 3
-                            && body.changes.contains(&LOCAL_NEWER_ITEM)
-                            && body.changes.contains(&LOCAL_ONLY_ITEM)
-                            && body.changes.contains(&LOCAL_NEW_REMOVED_ITEM) =>
+                            && (body.changes.len() == 2
+                                && body.changes.contains(&LOCAL_ONLY_ITEM)
+                                && body.changes.contains(&LOCAL_NEW_REMOVED_ITEM)
+                                || body.changes == vec![LOCAL_NEWER_ITEM.to_owned()]) =>
                     {
                         future::ok(Box::new(APIResult::Ok {
                             result: SuccessResponse { success: True {} },
@@ -208,12 +223,20 @@ fn actionctx_synclibrarywithapi_with_user() {
                         if body.auth_key == AuthKey("auth_key".to_owned())
                             && body.collection == "libraryItem"
                             && body.all == false
-                            && body.ids.len() == 2
+                            && body.ids.len() == 3
                             && body.ids.contains(&REMOTE_ONLY_ITEM.id)
-                            && body.ids.contains(&REMOTE_NEWER_ITEM.id) =>
+                            && body.ids.contains(&REMOTE_NEWER_ITEM.id)
+                            && body.ids.contains(&LOCAL_NEWER_ITEM.id) =>
                     {
                         future::ok(Box::new(APIResult::Ok {
-                            result: vec![REMOTE_ONLY_ITEM.to_owned(), REMOTE_NEWER_ITEM.to_owned()],
+                            result: vec![
+                                REMOTE_ONLY_ITEM.to_owned(),
+                                REMOTE_NEWER_ITEM.to_owned(),
+                                LibraryItem {
+                                    mtime: LOCAL_NEWER_ITEM.mtime - Duration::days(1),
+                                    ..LOCAL_NEWER_ITEM.to_owned()
+                                },
+                            ],
                         }) as Box<dyn Any + Send>)
                         .boxed_env()
                     }
@@ -339,8 +362,8 @@ fn actionctx_synclibrarywithapi_with_user() {
     );
     assert_eq!(
         REQUESTS.read().unwrap().len(),
-        3,
-        "Three requests have been sent"
+        4,
+        "Four requests have been sent"
     );
     assert_eq!(
         REQUESTS.read().unwrap().get(0).unwrap().url,
@@ -357,6 +380,11 @@ fn actionctx_synclibrarywithapi_with_user() {
         "https://api.strem.io/api/datastoreGet".to_owned(),
         "datastoreGet request has been sent"
     );
+    assert_eq!(
+        REQUESTS.read().unwrap().get(3).unwrap().url,
+        "https://api.strem.io/api/datastorePut".to_owned(),
+        "datastorePut request with the items merged locally has been sent"
+    );
 }
 
 #[test]
@@ -429,3 +457,314 @@ fn actionctx_synclibrarywithapi_with_user_empty_library() {
         "datastoreMeta request has been sent"
     );
 }
+
+#[test]
+fn actionctx_synclibrarywithapi_with_user_conflict() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    lazy_static! {
+        static ref LOCAL_ITEM: LibraryItem = LibraryItem {
+            id: "id1".to_owned(),
+            r#type: "type".to_owned(),
+            name: "name".to_owned(),
+            poster: None,
+            poster_shape: Default::default(),
+            removed: false,
+            temp: false,
+            ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            mtime: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap(),
+            state: LibraryItemState {
+                times_watched: 2,
+                ..Default::default()
+            },
+            behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
+        };
+        static ref REMOTE_ITEM: LibraryItem = LibraryItem {
+            mtime: Utc.with_ymd_and_hms(2020, 1, 3, 0, 0, 0).unwrap(),
+            state: LibraryItemState {
+                overall_time_watched: 1000,
+                ..Default::default()
+            },
+            ..LOCAL_ITEM.to_owned()
+        };
+        static ref MERGED_ITEM: LibraryItem = LibraryItem {
+            mtime: TestEnv::now(),
+            state: LibraryItemState {
+                times_watched: 2,
+                overall_time_watched: 1000,
+                ..Default::default()
+            },
+            ..LOCAL_ITEM.to_owned()
+        };
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        match &request {
+            Request { url, .. } if url == "https://api.strem.io/api/datastoreMeta" => {
+                future::ok(Box::new(APIResult::Ok {
+                    result: vec![LibraryItemModified(
+                        REMOTE_ITEM.id.to_owned(),
+                        REMOTE_ITEM.mtime.to_owned(),
+                    )],
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, .. } if url == "https://api.strem.io/api/datastoreGet" => {
+                future::ok(Box::new(APIResult::Ok {
+                    result: vec![REMOTE_ITEM.to_owned()],
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, body, .. } if url == "https://api.strem.io/api/datastorePut" => {
+                #[derive(Deserialize)]
+                struct Body {
+                    changes: Vec<LibraryItem>,
+                }
+                match serde_json::from_str::<Body>(body) {
+                    Result::Ok(body) if body.changes == vec![MERGED_ITEM.to_owned()] => {
+                        future::ok(Box::new(APIResult::Ok {
+                            result: SuccessResponse { success: True {} },
+                        }) as Box<dyn Any + Send>)
+                        .boxed_env()
+                    }
+                    _ => default_fetch_handler(request),
+                }
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    auth: Some(Auth {
+                        key: AuthKey("auth_key".to_owned()),
+                        user: User {
+                            id: "user_id".to_owned(),
+                            email: "user_email".to_owned(),
+                            fb_id: None,
+                            avatar: None,
+                            last_modified: TestEnv::now(),
+                            date_registered: TestEnv::now(),
+                            gdpr_consent: GDPRConsent {
+                                tos: true,
+                                privacy: true,
+                                marketing: true,
+                            },
+                        },
+                    }),
+                    ..Default::default()
+                },
+                library: LibraryBucket::new(
+                    Some("user_id".to_owned()),
+                    vec![LOCAL_ITEM.to_owned()],
+                ),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::SyncLibraryWithAPI),
+        })
+    });
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .ctx
+            .library
+            .items
+            .get(&LOCAL_ITEM.id),
+        Some(&*MERGED_ITEM),
+        "Counters of both versions are kept"
+    );
+    assert_eq!(
+        REQUESTS
+            .read()
+            .unwrap()
+            .iter()
+            .map(|request| request.url.to_owned())
+            .collect::<Vec<_>>(),
+        vec![
+            "https://api.strem.io/api/datastoreMeta".to_owned(),
+            "https://api.strem.io/api/datastoreGet".to_owned(),
+            "https://api.strem.io/api/datastorePut".to_owned(),
+        ],
+        "Merged item has been pushed back"
+    );
+}
+
+#[test]
+fn actionctx_synclibrarywithapi_with_user_batches() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+    }
+    static PULL_FAILED: AtomicBool = AtomicBool::new(false);
+    static PUSHED: AtomicBool = AtomicBool::new(false);
+    fn library_item(id: &str) -> LibraryItem {
+        LibraryItem {
+            id: id.to_owned(),
+            r#type: "type".to_owned(),
+            name: "name".to_owned(),
+            poster: None,
+            poster_shape: Default::default(),
+            removed: false,
+            temp: false,
+            ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+            mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+            state: Default::default(),
+            behavior_hints: Default::default(),
+            genres: vec![],
+            released: None,
+        }
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        #[derive(Deserialize)]
+        struct Body {
+            ids: Vec<String>,
+        }
+        match request {
+            Request { url, .. } if url == "https://api.strem.io/api/datastoreMeta" => {
+                future::ok(Box::new(APIResult::Ok {
+                    result: (0..LIBRARY_SYNC_BATCH_SIZE + 1)
+                        .map(|index| format!("id{}", index))
+                        .chain(PUSHED.load(Ordering::SeqCst).then(|| "local".to_owned()))
+                        .map(|id| {
+                            LibraryItemModified(
+                                id,
+                                Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, body, .. } if url == "https://api.strem.io/api/datastoreGet" => {
+                let Body { ids } = serde_json::from_str(&body).unwrap();
+                if ids.len() == 1 && !PULL_FAILED.swap(true, Ordering::SeqCst) {
+                    return future::err(EnvError::Fetch("timeout".to_owned())).boxed_env();
+                };
+                future::ok(Box::new(APIResult::Ok {
+                    result: ids.iter().map(|id| library_item(id)).collect::<Vec<_>>(),
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, .. } if url == "https://api.strem.io/api/datastorePut" => {
+                PUSHED.store(true, Ordering::SeqCst);
+                future::ok(Box::new(APIResult::Ok {
+                    result: SuccessResponse { success: True {} },
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    auth: Some(Auth {
+                        key: AuthKey("auth_key".to_owned()),
+                        user: User {
+                            id: "user_id".to_owned(),
+                            email: "user_email".to_owned(),
+                            fb_id: None,
+                            avatar: None,
+                            last_modified: TestEnv::now(),
+                            date_registered: TestEnv::now(),
+                            gdpr_consent: GDPRConsent {
+                                tos: true,
+                                privacy: true,
+                                marketing: true,
+                            },
+                        },
+                    }),
+                    ..Default::default()
+                },
+                library: LibraryBucket::new(
+                    Some("user_id".to_owned()),
+                    vec![library_item("local")],
+                ),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::SyncLibraryWithAPI),
+        })
+    });
+    assert_eq!(
+        runtime.model().unwrap().ctx.library.items.len(),
+        LIBRARY_SYNC_BATCH_SIZE + 1,
+        "First batch pulled"
+    );
+    assert!(
+        runtime.model().unwrap().ctx.library_sync.is_interrupted(),
+        "Sync interrupted by the failed batch"
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.library_sync.progress(),
+        LibrarySyncProgress {
+            pulled: LIBRARY_SYNC_BATCH_SIZE,
+            pull_total: LIBRARY_SYNC_BATCH_SIZE + 1,
+            pushed: 1,
+            push_total: 1,
+        },
+        "Progress tracked"
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::SyncLibraryWithAPI),
+        })
+    });
+    assert_eq!(
+        runtime.model().unwrap().ctx.library.items.len(),
+        LIBRARY_SYNC_BATCH_SIZE + 2,
+        "Sync resumed"
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.library_sync.progress(),
+        LibrarySyncProgress {
+            pulled: LIBRARY_SYNC_BATCH_SIZE + 1,
+            pull_total: LIBRARY_SYNC_BATCH_SIZE + 1,
+            pushed: 1,
+            push_total: 1,
+        },
+        "All items synced and the progress kept"
+    );
+    let requests = REQUESTS.read().unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .map(|request| request.url.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "https://api.strem.io/api/datastoreMeta",
+            "https://api.strem.io/api/datastorePut",
+            "https://api.strem.io/api/datastoreGet",
+            "https://api.strem.io/api/datastoreGet",
+            "https://api.strem.io/api/datastoreMeta",
+            "https://api.strem.io/api/datastoreGet",
+        ],
+        "Batches requested one at a time and the sync is resumed from a new plan"
+    );
+}
@@ -0,0 +1,212 @@
+use crate::constants::{LIBRARY_RECENT_STORAGE_KEY, PROFILE_STORAGE_KEY, UNDO_EXPIRY};
+use crate::models::ctx::Ctx;
+use crate::runtime::msg::{Action, ActionCtx};
+use crate::runtime::{Effects, Env, Runtime, RuntimeAction};
+use crate::types::addon::{Descriptor, Manifest};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState};
+use crate::types::profile::Profile;
+use crate::unit_tests::{TestEnv, NOW, REQUESTS, STORAGE};
+use chrono::prelude::TimeZone;
+use chrono::{Duration, Utc};
+use semver::Version;
+use serde_json::json;
+use std::collections::HashMap;
+use stremio_derive::Model;
+use url::Url;
+
+#[derive(Model, Default)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+}
+
+fn addon(id: &str) -> Descriptor {
+    Descriptor {
+        manifest: Manifest {
+            id: id.to_owned(),
+            version: Version::new(0, 0, 1),
+            name: id.to_owned(),
+            contact_email: None,
+            description: None,
+            logo: None,
+            background: None,
+            types: vec![],
+            resources: vec![],
+            id_prefixes: None,
+            catalogs: vec![],
+            addon_catalogs: vec![],
+            behavior_hints: Default::default(),
+        },
+        transport_url: Url::parse(&format!("https://{}/manifest.json", id)).unwrap(),
+        flags: Default::default(),
+    }
+}
+
+fn dispatch(runtime: &Runtime<TestEnv, TestModel>, action: ActionCtx) {
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(action),
+        })
+    });
+}
+
+#[test]
+fn actionctx_undo_library_actions() {
+    let _env_mutex = TestEnv::reset();
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap();
+    let library_item = LibraryItem {
+        id: "id".to_owned(),
+        r#type: "type".to_owned(),
+        name: "name".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        behavior_hints: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state: LibraryItemState {
+            time_offset: 1000,
+            ..Default::default()
+        },
+        genres: vec![],
+        released: None,
+    };
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item.to_owned()]),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    dispatch(&runtime, ActionCtx::RewindLibraryItem("id".to_owned()));
+    dispatch(&runtime, ActionCtx::RemoveFromLibrary("id".to_owned()));
+    assert_eq!(
+        serde_json::to_value(&runtime.model().unwrap().ctx.undo).unwrap(),
+        json!({
+            "entries": [
+                {
+                    "type": "libraryItem",
+                    "id": "id",
+                    "expires": "2020-01-02T00:01:00Z",
+                },
+                {
+                    "type": "libraryItem",
+                    "id": "id",
+                    "expires": "2020-01-02T00:01:00Z",
+                },
+            ],
+        }),
+        "Both actions can be undone and only their kind, id and expiry are serialized"
+    );
+    dispatch(&runtime, ActionCtx::Undo);
+    {
+        let model = runtime.model().unwrap();
+        let restored = model.ctx.library.items.get("id").unwrap();
+        assert!(!restored.removed, "Removed item restored");
+        assert_eq!(restored.state.time_offset, 0, "Rewind is not undone yet");
+        assert_eq!(
+            restored.mtime,
+            TestEnv::now(),
+            "Restored item mtime updated"
+        );
+    }
+    dispatch(&runtime, ActionCtx::Undo);
+    assert_eq!(
+        runtime.model().unwrap().ctx.library.items.get("id"),
+        Some(&LibraryItem {
+            mtime: TestEnv::now(),
+            ..library_item.to_owned()
+        }),
+        "Rewound item restored"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(LIBRARY_RECENT_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<LibraryBucket>(data).unwrap().items),
+        Some(HashMap::from([(
+            "id".to_owned(),
+            LibraryItem {
+                mtime: TestEnv::now(),
+                ..library_item.to_owned()
+            },
+        )])),
+        "Restored item persisted in storage"
+    );
+    assert!(
+        runtime.model().unwrap().ctx.undo.entries.is_empty(),
+        "Undo stack emptied"
+    );
+    assert!(
+        REQUESTS.read().unwrap().is_empty(),
+        "No requests have been sent"
+    );
+}
+
+#[test]
+fn actionctx_undo_uninstall_addon() {
+    let _env_mutex = TestEnv::reset();
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let addons = vec![addon("first"), addon("second"), addon("third")];
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: addons.to_owned(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    dispatch(&runtime, ActionCtx::UninstallAddon(addon("second")));
+    dispatch(&runtime, ActionCtx::Undo);
+    assert_eq!(
+        runtime.model().unwrap().ctx.profile.addons,
+        addons,
+        "Addon restored at its position"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(PROFILE_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<Profile>(data).unwrap().addons),
+        Some(addons),
+        "Restored addon persisted in storage"
+    );
+    dispatch(&runtime, ActionCtx::UninstallAddon(addon("third")));
+    *NOW.write().unwrap() += Duration::seconds(UNDO_EXPIRY);
+    dispatch(&runtime, ActionCtx::UninstallAddon(addon("first")));
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .ctx
+            .undo
+            .entries
+            .iter()
+            .map(|entry| entry.snapshot.id())
+            .collect::<Vec<_>>(),
+        vec!["first"],
+        "Expired entry dropped on push"
+    );
+    dispatch(&runtime, ActionCtx::Undo);
+    {
+        let model = runtime.model().unwrap();
+        assert_eq!(
+            model.ctx.profile.addons,
+            vec![addon("first"), addon("second")],
+            "Expired uninstall is not undone"
+        );
+        assert!(model.ctx.undo.entries.is_empty(), "Expired entry dropped");
+    }
+}
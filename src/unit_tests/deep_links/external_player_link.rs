@@ -1,3 +1,4 @@
+use crate::constants::STREAMING_SERVER_URL;
 use crate::deep_links::ExternalPlayerLink;
 use crate::types::resource::{Stream, StreamSource};
 use std::convert::TryFrom;
@@ -19,7 +20,7 @@ fn external_player_link_magnet() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(epl.href, MAGNET_STR_URL);
     assert_eq!(epl.download, None);
 }
@@ -36,7 +37,7 @@ fn external_player_link_http() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(epl.href, BASE64_HTTP_URL);
     assert_eq!(epl.download, Some("playlist.m3u".to_string()));
 }
@@ -58,7 +59,7 @@ fn external_player_link_torrent() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(epl.href, MAGNET_STR_URL);
     assert_eq!(epl.download, None);
 }
@@ -75,7 +76,7 @@ fn external_player_link_external() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(epl.href, HTTP_STR_URL);
     assert_eq!(epl.download, None);
 }
@@ -92,7 +93,7 @@ fn external_player_link_youtube() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(
         epl.href,
         "https://www.youtube.com/watch?v=aqz-KE-bpKQ".to_string()
@@ -112,7 +113,7 @@ fn external_player_link_player_frame() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let epl = ExternalPlayerLink::try_from(&stream).unwrap();
+    let epl = ExternalPlayerLink::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(epl.href, HTTP_STR_URL);
     assert_eq!(epl.download, None);
 }
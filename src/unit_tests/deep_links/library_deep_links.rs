@@ -1,5 +1,5 @@
 use crate::deep_links::LibraryDeepLinks;
-use crate::models::library_with_filters::{LibraryRequest, Sort};
+use crate::models::library_with_filters::{LibraryRequest, Sort, Status};
 use std::convert::TryFrom;
 
 #[test]
@@ -16,6 +16,8 @@ fn library_deep_links_request_type() {
         r#type: Some("movie".to_string()),
         sort: Sort::LastWatched,
         page: Default::default(),
+        search: None,
+        status: None,
     };
     let ldl = LibraryDeepLinks::try_from((&root, &request)).unwrap();
     assert_eq!(
@@ -31,6 +33,8 @@ fn library_deep_links_request_no_type() {
         r#type: None,
         sort: Sort::LastWatched,
         page: Default::default(),
+        search: None,
+        status: None,
     };
     let ldl = LibraryDeepLinks::try_from((&root, &request)).unwrap();
     assert_eq!(
@@ -38,3 +42,21 @@ fn library_deep_links_request_no_type() {
         "stremio:///library?sort=lastwatched&page=1".to_string()
     );
 }
+
+#[test]
+fn library_deep_links_request_search_and_status() {
+    let root = "library".to_string();
+    let request = LibraryRequest {
+        r#type: Some("series".to_string()),
+        sort: Sort::RecentlyUpdated,
+        page: Default::default(),
+        search: Some("Café noir".to_string()),
+        status: Some(Status::InProgress),
+    };
+    let ldl = LibraryDeepLinks::from((&root, &request));
+    assert_eq!(
+        ldl.library,
+        "stremio:///library/series?sort=recentlyupdated&page=1&search=Caf%C3%A9+noir&status=inprogress"
+            .to_string()
+    );
+}
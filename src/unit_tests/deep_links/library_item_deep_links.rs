@@ -33,6 +33,8 @@ fn library_item_deep_links() {
             no_notif: true,
         },
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let lidl = LibraryItemDeepLinks::try_from(&lib_item).unwrap();
     assert_eq!(
@@ -71,6 +73,8 @@ fn library_item_deep_links_state_video_id() {
             no_notif: true,
         },
         behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
     };
     let lidl = LibraryItemDeepLinks::try_from(&lib_item).unwrap();
     assert_eq!(
@@ -116,6 +120,8 @@ fn library_item_deep_links_behavior_hints_default_video_id() {
             has_scheduled_videos: false,
             other: Default::default(),
         },
+        genres: vec![],
+        released: None,
     };
     let lidl = LibraryItemDeepLinks::try_from(&lib_item).unwrap();
     assert_eq!(lidl.meta_details_videos, None);
@@ -158,6 +164,8 @@ fn library_item_deep_links_state_and_behavior_hints_default_video_id() {
             has_scheduled_videos: false,
             other: Default::default(),
         },
+        genres: vec![],
+        released: None,
     };
     let lidl = LibraryItemDeepLinks::try_from(&lib_item).unwrap();
     assert_eq!(lidl.meta_details_videos, None);
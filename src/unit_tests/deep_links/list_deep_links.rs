@@ -0,0 +1,41 @@
+use crate::deep_links::ListDeepLinks;
+use crate::models::list_with_filters::{ListRequest, Sort};
+use crate::types::lists::UserList;
+use crate::unit_tests::TestEnv;
+
+#[test]
+fn list_deep_links_list() {
+    let list = UserList::new::<TestEnv>("1577836800000".to_owned(), "Favourites".to_owned());
+    let ldl = ListDeepLinks::from(&list);
+    assert_eq!(ldl.list, "stremio:///list/1577836800000".to_string());
+}
+
+#[test]
+fn list_deep_links_request_type() {
+    let request = ListRequest {
+        id: "1577836800000".to_owned(),
+        r#type: Some("movie".to_owned()),
+        sort: Sort::Name,
+        page: Default::default(),
+    };
+    let ldl = ListDeepLinks::from(&request);
+    assert_eq!(
+        ldl.list,
+        "stremio:///list/1577836800000/movie?sort=name&page=1".to_string()
+    );
+}
+
+#[test]
+fn list_deep_links_request_no_type() {
+    let request = ListRequest {
+        id: "1577836800000".to_owned(),
+        r#type: None,
+        sort: Sort::Added,
+        page: Default::default(),
+    };
+    let ldl = ListDeepLinks::from(&request);
+    assert_eq!(
+        ldl.list,
+        "stremio:///list/1577836800000?sort=added&page=1".to_string()
+    );
+}
@@ -3,6 +3,8 @@ mod discover_deep_links;
 mod external_player_link;
 mod library_deep_links;
 mod library_item_deep_links;
+mod list_deep_links;
 mod meta_item_deep_links;
 mod stream_deep_links;
+mod streaming_server_url;
 mod video_deep_links;
@@ -1,3 +1,4 @@
+use crate::constants::STREAMING_SERVER_URL;
 use crate::deep_links::StreamDeepLinks;
 use crate::types::addon::{ResourcePath, ResourceRequest};
 use crate::types::resource::{Stream, StreamSource};
@@ -21,7 +22,7 @@ fn stream_deep_links_magnet() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(sdl.player, "stremio:///player/eAEBRgC5%2F3sidXJsIjoibWFnbmV0Oj94dD11cm46YnRpaDpkZDgyNTVlY2RjN2NhNTVmYjBiYmY4MTMyM2Q4NzA2MmRiMWY2ZDFjIn0%2BMhZF".to_string());
     assert_eq!(sdl.external_player.href, MAGNET_STR_URL);
     assert_eq!(sdl.external_player.download, None);
@@ -39,7 +40,7 @@ fn stream_deep_links_http() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(
         sdl.player,
         "stremio:///player/eAEBIQDe%2F3sidXJsIjoiaHR0cDovL2RvbWFpbi5yb290L3BhdGgifcEEC6w%3D"
@@ -69,7 +70,7 @@ fn stream_deep_links_torrent() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(sdl.player, "stremio:///player/eAEBVACr%2F3siaW5mb0hhc2giOiJkZDgyNTVlY2RjN2NhNTVmYjBiYmY4MTMyM2Q4NzA2MmRiMWY2ZDFjIiwiZmlsZUlkeCI6bnVsbCwiYW5ub3VuY2UiOltdfVAyGnc%3D".to_string());
     assert_eq!(sdl.external_player.href, MAGNET_STR_URL);
     assert_eq!(sdl.external_player.download, None);
@@ -87,7 +88,7 @@ fn stream_deep_links_external() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(sdl.player, "stremio:///player/eAEBKQDW%2F3siZXh0ZXJuYWxVcmwiOiJodHRwOi8vZG9tYWluLnJvb3QvcGF0aCJ9OoEO7w%3D%3D".to_string());
     assert_eq!(sdl.external_player.href, HTTP_STR_URL);
     assert_eq!(sdl.external_player.download, None);
@@ -105,7 +106,7 @@ fn stream_deep_links_youtube() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(
         sdl.player,
         "stremio:///player/eAEBFgDp%2F3sieXRJZCI6ImFxei1LRS1icEtRIn1RRQb5".to_string()
@@ -129,7 +130,7 @@ fn stream_deep_links_player_frame() {
         subtitles: vec![],
         behavior_hints: Default::default(),
     };
-    let sdl = StreamDeepLinks::try_from(&stream).unwrap();
+    let sdl = StreamDeepLinks::try_from((&stream, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(sdl.player, "stremio:///player/eAEBLADT%2F3sicGxheWVyRnJhbWVVcmwiOiJodHRwOi8vZG9tYWluLnJvb3QvcGF0aCJ9abUQBA%3D%3D".to_string());
     assert_eq!(sdl.external_player.href, HTTP_STR_URL);
     assert_eq!(sdl.external_player.download, None);
@@ -156,7 +157,13 @@ fn stream_deep_links_requests() {
         path: ResourcePath::without_extra("meta", "movie", "yt_id:aqz-KE-bpKQ"),
     };
 
-    let sdl = StreamDeepLinks::try_from((&stream, &stream_request, &meta_request)).unwrap();
+    let sdl = StreamDeepLinks::try_from((
+        &stream,
+        &stream_request,
+        &meta_request,
+        &*STREAMING_SERVER_URL,
+    ))
+    .unwrap();
     assert_eq!(sdl.player, "stremio:///player/eAEBFgDp%2F3sieXRJZCI6ImFxei1LRS1icEtRIn1RRQb5/http%3A%2F%2Fdomain.root%2F/http%3A%2F%2Fdomain.root%2F/movie/yt_id%3Aaqz-KE-bpKQ/yt_id%3Aaqz-KE-bpKQ".to_string());
     assert_eq!(
         sdl.external_player.href,
@@ -1,3 +1,4 @@
+use crate::constants::STREAMING_SERVER_URL;
 use crate::deep_links::{ExternalPlayerLink, VideoDeepLinks};
 use crate::types::addon::{ResourcePath, ResourceRequest};
 use crate::types::resource::Video;
@@ -21,7 +22,7 @@ fn video_deep_links() {
         base: Url::from_str("http://domain.root").unwrap(),
         path: ResourcePath::without_extra("meta", "movie", "yt_id:aqz-KE-bpKQ"),
     };
-    let vdl = VideoDeepLinks::try_from((&video, &request)).unwrap();
+    let vdl = VideoDeepLinks::try_from((&video, &request, &*STREAMING_SERVER_URL)).unwrap();
     assert_eq!(vdl.meta_details_streams, "stremio:///detail/movie/yt_id%3Aaqz-KE-bpKQ/yt_id%3AUCSMOQeBJ2RAnuFungnQOxLg%3Aaqz-KE-bpKQ".to_string());
     assert_eq!(vdl.player, Some("stremio:///player/eAEBFgDp%2F3sieXRJZCI6ImFxei1LRS1icEtRIn1RRQb5/http%3A%2F%2Fdomain.root%2F/http%3A%2F%2Fdomain.root%2F/movie/yt_id%3Aaqz-KE-bpKQ/yt_id%3AUCSMOQeBJ2RAnuFungnQOxLg%3Aaqz-KE-bpKQ".to_string()));
     assert_eq!(
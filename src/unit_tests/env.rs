@@ -1,6 +1,9 @@
+use crate::addon_transport::reset_addon_scheduler;
 use crate::models::ctx::Ctx;
 use crate::models::streaming_server::StreamingServer;
-use crate::runtime::{Env, EnvFuture, EnvFutureExt, Model, Runtime, RuntimeEvent, TryEnvFuture};
+use crate::runtime::{
+    Env, EnvError, EnvFuture, EnvFutureExt, FileChunk, Model, Runtime, RuntimeEvent, TryEnvFuture,
+};
 use chrono::{DateTime, Utc};
 use enclose::enclose;
 use futures::channel::mpsc::Receiver;
@@ -13,12 +16,14 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ops::Fn;
 use std::sync::{Arc, LockResult, Mutex, MutexGuard, RwLock};
+use url::Url;
 
 lazy_static! {
     pub static ref FETCH_HANDLER: RwLock<FetchHandler> =
         RwLock::new(Box::new(default_fetch_handler));
     pub static ref REQUESTS: RwLock<Vec<Request>> = Default::default();
     pub static ref STORAGE: RwLock<BTreeMap<String, String>> = Default::default();
+    pub static ref FILES: RwLock<HashMap<String, Vec<u8>>> = Default::default();
     pub static ref EVENTS: RwLock<Vec<RuntimeEvent>> = Default::default();
     pub static ref STATES: RwLock<Vec<Box<dyn Any + Send + Sync + 'static>>> = Default::default();
     pub static ref NOW: RwLock<DateTime<Utc>> = RwLock::new(Utc::now());
@@ -60,9 +65,11 @@ impl TestEnv {
         *FETCH_HANDLER.write().unwrap() = Box::new(default_fetch_handler);
         *REQUESTS.write().unwrap() = vec![];
         *STORAGE.write().unwrap() = BTreeMap::new();
+        *FILES.write().unwrap() = HashMap::new();
         *EVENTS.write().unwrap() = vec![];
         *STATES.write().unwrap() = vec![];
         *NOW.write().unwrap() = Utc::now();
+        reset_addon_scheduler::<TestEnv>();
         env_mutex
     }
     pub fn run<F: FnOnce()>(runnable: F) {
@@ -168,6 +175,34 @@ impl Env for TestEnv {
     fn log(message: String) {
         println!("{}", message)
     }
+    fn read_file(path: &str, offset: u64, length: u64) -> TryEnvFuture<FileChunk> {
+        match FILES.read().unwrap().get(path) {
+            Some(file) => {
+                let start = (offset as usize).min(file.len());
+                let end = (start + length as usize).min(file.len());
+                future::ok(FileChunk {
+                    bytes: file[start..end].to_vec(),
+                    file_size: file.len() as u64,
+                })
+                .boxed_env()
+            }
+            _ => future::err(EnvError::Other(format!("File not found: {}", path))).boxed_env(),
+        }
+    }
+    fn fetch_range(url: &Url, offset: u64, length: u64) -> TryEnvFuture<FileChunk> {
+        REQUESTS.write().unwrap().push(Request {
+            url: url.to_string(),
+            method: "GET".to_owned(),
+            headers: vec![(
+                "range".to_owned(),
+                format!("bytes={}-{}", offset, offset + length.max(1) - 1),
+            )]
+            .into_iter()
+            .collect(),
+            body: "null".to_owned(),
+        });
+        TestEnv::read_file(url.as_str(), offset, length)
+    }
 }
 
 pub fn default_fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
@@ -0,0 +1,117 @@
+use crate::constants::{PROFILE_STORAGE_KEY, SCHEMA_VERSION_STORAGE_KEY};
+use crate::runtime::Env;
+use crate::types::resource::Subtitles;
+use crate::types::{is_same_language, normalize_language, Language};
+use crate::unit_tests::{TestEnv, STORAGE};
+use futures::executor::block_on;
+use serde_json::json;
+
+#[test]
+fn find() {
+    for value in [
+        "bul",
+        "bg",
+        "BG",
+        "Bulgarian",
+        " bulgarian ",
+        "bg-BG",
+        "bg_BG",
+    ] {
+        assert_eq!(
+            Language::find(value).map(|language| language.code),
+            Some("bul"),
+            "{}",
+            value
+        );
+    }
+    assert_eq!(
+        Language::find("deu").map(|language| language.code),
+        Some("ger")
+    );
+    assert_eq!(
+        Language::find("pt-BR").map(|language| language.code),
+        Some("por")
+    );
+    assert_eq!(
+        Language::find("pob").map(|language| language.code),
+        Some("por")
+    );
+    assert_eq!(
+        Language::find("Chinese (Simplified)").map(|language| language.code),
+        Some("chi")
+    );
+    assert_eq!(
+        Language::find("Greek, Modern").map(|language| language.name),
+        Some("Greek")
+    );
+    assert_eq!(
+        Language::find("iw").map(|language| language.code),
+        Some("heb")
+    );
+    assert_eq!(Language::find("klingon"), None);
+    assert_eq!(Language::find(""), None);
+}
+
+#[test]
+fn normalize() {
+    assert_eq!(normalize_language("fr"), "fre");
+    assert_eq!(normalize_language("French"), "fre");
+    assert_eq!(normalize_language("fra"), "fre");
+    assert_eq!(normalize_language("fre"), "fre");
+    assert_eq!(normalize_language("klingon"), "klingon");
+}
+
+#[test]
+fn matching() {
+    assert!(is_same_language("en", "eng"));
+    assert!(is_same_language("English", "en-US"));
+    assert!(is_same_language("nld", "dut"));
+    assert!(!is_same_language("eng", "spa"));
+    assert!(is_same_language("Klingon", "klingon"));
+    assert!(!is_same_language("klingon", "eng"));
+    let subtitles = Subtitles {
+        lang: "pt-BR".to_owned(),
+        ..Default::default()
+    };
+    assert!(subtitles.is_language("por"));
+    assert!(!subtitles.is_language("spa"));
+}
+
+#[test]
+fn migrate_settings_languages() {
+    let _env_mutex = TestEnv::reset();
+    STORAGE
+        .write()
+        .unwrap()
+        .insert(SCHEMA_VERSION_STORAGE_KEY.to_owned(), "5".to_owned());
+    STORAGE.write().unwrap().insert(
+        PROFILE_STORAGE_KEY.to_owned(),
+        json!({
+            "settings": {
+                "interfaceLanguage": "en",
+                "audioLanguage": "Japanese",
+                "subtitlesLanguage": "pt-BR",
+                "subtitlesSize": 100,
+            }
+        })
+        .to_string(),
+    );
+    block_on(TestEnv::migrate_storage_schema()).unwrap();
+    let storage = STORAGE.read().unwrap();
+    assert_eq!(
+        storage.get(SCHEMA_VERSION_STORAGE_KEY),
+        Some(&"6".to_owned())
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(storage.get(PROFILE_STORAGE_KEY).unwrap())
+            .unwrap(),
+        json!({
+            "settings": {
+                "interfaceLanguage": "en",
+                "audioLanguage": "jpn",
+                "subtitlesLanguage": "por",
+                "subtitlesSize": 100,
+            }
+        })
+    );
+}
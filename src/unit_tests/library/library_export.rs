@@ -0,0 +1,208 @@
+use crate::types::library::{
+    LibraryBucket, LibraryExportFormat, LibraryExportRow, LibraryItem, LibraryItemState,
+};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+
+fn library_item(id: &str, r#type: &str, name: &str, removed: bool) -> LibraryItem {
+    LibraryItem {
+        id: id.to_owned(),
+        name: name.to_owned(),
+        r#type: r#type.to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed,
+        temp: removed,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap(),
+        state: Default::default(),
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    }
+}
+
+fn library() -> LibraryBucket {
+    LibraryBucket::new(
+        None,
+        vec![
+            LibraryItem {
+                state: LibraryItemState {
+                    last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 5, 10, 0, 0).unwrap()),
+                    flagged_watched: 1,
+                    times_watched: 1,
+                    ..Default::default()
+                },
+                ..library_item("tt1", "movie", "Movie, \"quoted\"", false)
+            },
+            LibraryItem {
+                state: LibraryItemState {
+                    last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 3, 10, 0, 0).unwrap()),
+                    time_offset: 1500,
+                    duration: 6000,
+                    ..Default::default()
+                },
+                ..library_item("tt2", "series", "Series", true)
+            },
+            library_item("tt3", "series", "Removed", true),
+            library_item("local:1", "other", "Other", false),
+        ],
+    )
+}
+
+fn expected_rows() -> Vec<LibraryExportRow> {
+    vec![
+        LibraryExportRow {
+            id: "tt1".to_owned(),
+            r#type: "movie".to_owned(),
+            name: "Movie, \"quoted\"".to_owned(),
+            in_library: true,
+            watched: true,
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 5, 10, 0, 0).unwrap()),
+            time_offset: 0,
+            duration: 0,
+            video_id: None,
+            progress: None,
+        },
+        LibraryExportRow {
+            id: "tt2".to_owned(),
+            r#type: "series".to_owned(),
+            name: "Series".to_owned(),
+            in_library: false,
+            watched: false,
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 3, 10, 0, 0).unwrap()),
+            time_offset: 1500,
+            duration: 6000,
+            video_id: None,
+            progress: None,
+        },
+    ]
+}
+
+#[test]
+fn library_export_round_trip() {
+    for format in [
+        LibraryExportFormat::Json,
+        LibraryExportFormat::Trakt,
+        LibraryExportFormat::Csv,
+    ] {
+        let export = library().export(format);
+        let rows = LibraryExportRow::parse_export(format, &export)
+            .expect("export parsed")
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("rows parsed");
+        assert_eq!(
+            rows,
+            expected_rows(),
+            "{:?} export keeps library and watched items only",
+            format
+        );
+    }
+}
+
+#[test]
+fn library_export_csv() {
+    let export = library().export(LibraryExportFormat::Csv);
+    assert_eq!(
+        export,
+        "id,type,name,in_library,watched,last_watched,time_offset,duration,video_id\n\
+         tt1,movie,\"Movie, \"\"quoted\"\"\",true,true,2020-01-05T10:00:00+00:00,0,0,\n\
+         tt2,series,Series,false,false,2020-01-03T10:00:00+00:00,1500,6000,\n"
+    );
+}
+
+#[test]
+fn library_import_csv_invalid_rows() {
+    let rows = LibraryExportRow::parse_export(
+        LibraryExportFormat::Csv,
+        "type,id,watched\r\nmovie,tt1,1\r\nmovie,,true\r\nseries,tt2,maybe\r\n",
+    )
+    .expect("export parsed");
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[0],
+        Ok(LibraryExportRow {
+            id: "tt1".to_owned(),
+            r#type: "movie".to_owned(),
+            name: "tt1".to_owned(),
+            in_library: false,
+            watched: true,
+            last_watched: None,
+            time_offset: 0,
+            duration: 0,
+            video_id: None,
+            progress: None,
+        }),
+        "columns are mapped by the header"
+    );
+    assert_eq!(rows[1], Err("Missing id".to_owned()));
+    assert_eq!(rows[2], Err("Invalid boolean maybe".to_owned()));
+    assert!(
+        LibraryExportRow::parse_export(LibraryExportFormat::Csv, "name\nMovie\n").is_err(),
+        "export without an id column is invalid"
+    );
+}
+
+#[test]
+fn library_import_trakt() {
+    let rows = LibraryExportRow::parse_export(
+        LibraryExportFormat::Trakt,
+        r#"{
+            "history": [
+                { "type": "movie", "watched_at": "2020-01-05T10:00:00Z", "movie": { "title": "Movie", "ids": { "imdb": "tt1", "trakt": 1 } } },
+                { "type": "episode", "watched_at": "2020-01-04T10:00:00Z", "episode": { "season": 1, "number": 2, "ids": { "trakt": 2 } }, "show": { "title": "Series", "ids": { "imdb": "tt2" } } },
+                { "type": "episode", "watched_at": "2020-01-03T10:00:00Z", "episode": { "season": 1, "number": 1, "ids": { "trakt": 1 } }, "show": { "title": "Series", "ids": { "imdb": "tt2" } } },
+                { "type": "episode", "episode": { "season": 1, "number": 1, "ids": { "imdb": "tt3" } } }
+            ],
+            "playback": [
+                { "type": "movie", "progress": 25.0, "duration": 6000, "paused_at": "2020-01-06T10:00:00Z", "movie": { "title": "Movie", "ids": { "imdb": "tt1" } } },
+                { "type": "movie", "progress": 50.0, "paused_at": "2020-01-06T10:00:00Z", "movie": { "title": "Other", "ids": { "imdb": "tt4" } } }
+            ]
+        }"#,
+    )
+    .expect("export parsed");
+    assert_eq!(
+        rows,
+        vec![
+            Ok(LibraryExportRow {
+                id: "tt1".to_owned(),
+                r#type: "movie".to_owned(),
+                name: "Movie".to_owned(),
+                in_library: false,
+                watched: true,
+                last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 6, 10, 0, 0).unwrap()),
+                time_offset: 1500,
+                duration: 6000,
+                video_id: None,
+                progress: None,
+            }),
+            Ok(LibraryExportRow {
+                id: "tt2".to_owned(),
+                r#type: "series".to_owned(),
+                name: "Series".to_owned(),
+                in_library: false,
+                watched: false,
+                last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 4, 10, 0, 0).unwrap()),
+                time_offset: 0,
+                duration: 0,
+                video_id: Some("tt2:1:2".to_owned()),
+                progress: None,
+            }),
+            Err("Unsupported type episode".to_owned()),
+            Ok(LibraryExportRow {
+                id: "tt4".to_owned(),
+                r#type: "movie".to_owned(),
+                name: "Other".to_owned(),
+                in_library: false,
+                watched: false,
+                last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 6, 10, 0, 0).unwrap()),
+                time_offset: 0,
+                duration: 0,
+                video_id: None,
+                progress: Some(50.0),
+            }),
+        ],
+        "entries of the same item are merged into one row and episodes into their show"
+    );
+}
@@ -0,0 +1,157 @@
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use stremio_watched_bitfield::WatchedBitField;
+
+fn library_item(id: &str, mtime_day: u32) -> LibraryItem {
+    LibraryItem {
+        id: id.to_owned(),
+        name: "name".to_owned(),
+        r#type: "series".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, mtime_day, 0, 0, 0).unwrap(),
+        state: Default::default(),
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    }
+}
+
+fn watched(video_ids: &[String], indexes: &[usize]) -> String {
+    let mut watched = WatchedBitField::construct_from_array(vec![], video_ids.to_vec());
+    for index in indexes {
+        watched.set(*index, true);
+    }
+    watched.to_string()
+}
+
+#[test]
+fn merge_state() {
+    let video_ids = (1..=10)
+        .map(|episode| format!("tt1:1:{}", episode))
+        .collect::<Vec<_>>();
+    let local = LibraryItem {
+        name: "local name".to_owned(),
+        state: LibraryItemState {
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 5, 0, 0, 0).unwrap()),
+            time_offset: 100,
+            time_watched: 10,
+            duration: 1000,
+            video_id: Some("tt1:1:3".to_owned()),
+            overall_time_watched: 5000,
+            times_watched: 1,
+            watched: Some(watched(&video_ids, &[0, 1])),
+            ..Default::default()
+        },
+        ..library_item("tt1", 6)
+    };
+    let remote = LibraryItem {
+        name: "remote name".to_owned(),
+        state: LibraryItemState {
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, 3, 0, 0, 0).unwrap()),
+            time_offset: 500,
+            time_watched: 50,
+            duration: 2000,
+            video_id: Some("tt1:1:5".to_owned()),
+            overall_time_watched: 3000,
+            times_watched: 3,
+            watched: Some(watched(&video_ids, &[4])),
+            ..Default::default()
+        },
+        ..library_item("tt1", 7)
+    };
+    let merged = local.merge(&remote);
+    assert_eq!(merged, remote.merge(&local), "Merge is symmetric");
+    assert_eq!(merged.name, "remote name", "Metadata of the newer item");
+    assert_eq!(merged.mtime, remote.mtime);
+    assert_eq!(merged.state.last_watched, local.state.last_watched);
+    assert_eq!(
+        (
+            merged.state.time_offset,
+            merged.state.time_watched,
+            merged.state.duration,
+            merged.state.video_id.as_deref()
+        ),
+        (100, 10, 1000, Some("tt1:1:3")),
+        "Playback position of the item watched last"
+    );
+    assert_eq!(merged.state.overall_time_watched, 5000);
+    assert_eq!(merged.state.times_watched, 3);
+    let merged_watched =
+        WatchedBitField::construct_and_resize(merged.state.watched.as_ref().unwrap(), video_ids)
+            .unwrap();
+    assert_eq!(
+        (0..10)
+            .filter(|i| merged_watched.get(*i))
+            .collect::<Vec<_>>(),
+        vec![0, 1, 4],
+        "Watched videos are united"
+    );
+}
+
+#[test]
+fn merge_removed_and_temp() {
+    let in_library = library_item("tt1", 1);
+    let temp = LibraryItem {
+        removed: true,
+        temp: true,
+        ..library_item("tt1", 2)
+    };
+    let removed = LibraryItem {
+        removed: true,
+        temp: false,
+        ..library_item("tt1", 3)
+    };
+    let merged = in_library.merge(&temp);
+    assert!(
+        !merged.removed && !merged.temp,
+        "Newer temporary item does not remove the item from the library"
+    );
+    let merged = in_library.merge(&removed);
+    assert!(merged.removed && !merged.temp, "Newer removal is kept");
+    let merged = removed.merge(&LibraryItem {
+        removed: false,
+        ..library_item("tt1", 4)
+    });
+    assert!(!merged.removed, "Newer addition is kept");
+}
+
+#[test]
+fn merge_items_conflicts() {
+    let mut bucket = LibraryBucket::new(
+        None,
+        vec![
+            LibraryItem {
+                state: LibraryItemState {
+                    times_watched: 2,
+                    ..Default::default()
+                },
+                ..library_item("conflict", 1)
+            },
+            library_item("older", 1),
+            library_item("newer", 2),
+        ],
+    );
+    let conflicted_ids = bucket.merge_items(vec![
+        LibraryItem {
+            state: LibraryItemState {
+                overall_time_watched: 100,
+                ..Default::default()
+            },
+            ..library_item("conflict", 2)
+        },
+        library_item("older", 2),
+        library_item("newer", 1),
+        library_item("new", 1),
+    ]);
+    assert_eq!(conflicted_ids, vec!["conflict".to_owned()]);
+    assert_eq!(bucket.items["conflict"].state.times_watched, 2);
+    assert_eq!(bucket.items["conflict"].state.overall_time_watched, 100);
+    assert_eq!(bucket.items["older"], library_item("older", 2));
+    assert_eq!(bucket.items["newer"], library_item("newer", 2));
+    assert!(bucket.items.contains_key("new"));
+}
@@ -0,0 +1,210 @@
+use crate::models::ctx::Ctx;
+use crate::models::library_statistics::{
+    ActivityPeriod, GenreStatistics, LibraryStatistics, TypeStatistics,
+};
+use crate::runtime::msg::{Internal, Msg};
+use crate::runtime::UpdateWithCtx;
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState};
+use crate::unit_tests::{TestEnv, NOW};
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use stremio_watched_bitfield::WatchedBitField;
+
+fn library_item(id: &str, r#type: &str, day: u32, genres: &[&str]) -> LibraryItem {
+    LibraryItem {
+        id: id.to_owned(),
+        name: id.to_owned(),
+        r#type: r#type.to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, day, 0, 0, 0).unwrap(),
+        state: LibraryItemState {
+            last_watched: Some(Utc.with_ymd_and_hms(2020, 1, day, 20, 0, 0).unwrap()),
+            overall_time_watched: 1000,
+            ..Default::default()
+        },
+        behavior_hints: Default::default(),
+        genres: genres.iter().map(|genre| genre.to_string()).collect(),
+        released: None,
+    }
+}
+
+fn library() -> LibraryBucket {
+    let video_ids = (1..=10)
+        .map(|episode| format!("tt3:1:{}", episode))
+        .collect::<Vec<_>>();
+    let mut watched = WatchedBitField::construct_from_array(vec![], video_ids);
+    watched.set(0, true);
+    watched.set(1, true);
+    watched.set(5, true);
+    LibraryBucket::new(
+        None,
+        vec![
+            LibraryItem {
+                state: LibraryItemState {
+                    flagged_watched: 1,
+                    ..library_item("tt1", "movie", 27, &[]).state
+                },
+                ..library_item("tt1", "movie", 27, &["Drama", "Comedy"])
+            },
+            library_item("tt2", "movie", 28, &["Drama"]),
+            LibraryItem {
+                state: LibraryItemState {
+                    overall_time_watched: 5000,
+                    watched: Some(watched.to_string()),
+                    ..library_item("tt3", "series", 30, &[]).state
+                },
+                ..library_item("tt3", "series", 30, &["Drama", "Action"])
+            },
+            library_item("tt4", "series", 3, &["Action"]),
+            LibraryItem {
+                state: Default::default(),
+                ..library_item("tt5", "movie", 3, &["Horror"])
+            },
+            library_item("local:1", "other", 3, &["Drama"]),
+        ],
+    )
+}
+
+fn history_entry(id: &str, day: u32, hour: u32) -> HistoryEntry {
+    HistoryEntry {
+        id: id.to_owned(),
+        r#type: "movie".to_owned(),
+        name: id.to_owned(),
+        video_id: id.to_owned(),
+        start: Utc.with_ymd_and_hms(2020, 1, day, hour, 0, 0).unwrap(),
+        end: Utc.with_ymd_and_hms(2020, 1, day, hour, 30, 0).unwrap(),
+        duration: 1000,
+    }
+}
+
+fn history() -> HistoryBucket {
+    HistoryBucket::new(
+        None,
+        vec![
+            history_entry("tt4", 3, 20),
+            history_entry("tt1", 27, 20),
+            history_entry("tt2", 28, 20),
+            history_entry("tt3", 30, 20),
+            history_entry("tt3", 30, 22),
+        ],
+    )
+}
+
+#[test]
+fn library_statistics() {
+    let _env_mutex = TestEnv::reset();
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 2, 4, 12, 0, 0).unwrap();
+    let (statistics, _) = LibraryStatistics::new::<TestEnv>(&library(), &history());
+    assert_eq!(statistics.time_watched, 8000);
+    assert_eq!(
+        statistics.types,
+        vec![
+            TypeStatistics {
+                r#type: "movie".to_owned(),
+                time_watched: 2000,
+                titles: 2,
+                completed: 1,
+            },
+            TypeStatistics {
+                r#type: "series".to_owned(),
+                time_watched: 6000,
+                titles: 2,
+                completed: 0,
+            },
+        ],
+        "watched titles only, other type excluded"
+    );
+    assert_eq!(statistics.titles, 4);
+    assert_eq!(statistics.completed, 1);
+    assert_eq!(statistics.watched_episodes, 3);
+    assert_eq!(
+        statistics.weekly_activity,
+        vec![
+            ActivityPeriod {
+                start: Utc.with_ymd_and_hms(2019, 12, 30, 0, 0, 0).unwrap(),
+                titles: 1,
+            },
+            ActivityPeriod {
+                start: Utc.with_ymd_and_hms(2020, 1, 27, 0, 0, 0).unwrap(),
+                titles: 3,
+            },
+        ],
+        "sessions of the same title are counted once"
+    );
+    assert_eq!(
+        statistics.monthly_activity,
+        vec![ActivityPeriod {
+            start: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+            titles: 4,
+        }]
+    );
+    assert_eq!(statistics.longest_streak, 2);
+    assert_eq!(statistics.current_streak, 0, "last activity is too old");
+    assert_eq!(
+        statistics.top_genres,
+        vec![
+            GenreStatistics {
+                genre: "Drama".to_owned(),
+                titles: 3,
+            },
+            GenreStatistics {
+                genre: "Action".to_owned(),
+                titles: 2,
+            },
+            GenreStatistics {
+                genre: "Comedy".to_owned(),
+                titles: 1,
+            },
+        ]
+    );
+}
+
+#[test]
+fn library_statistics_library_changed() {
+    let _env_mutex = TestEnv::reset();
+    *NOW.write().unwrap() = Utc.with_ymd_and_hms(2020, 1, 31, 12, 0, 0).unwrap();
+    let mut ctx = Ctx {
+        library: library(),
+        history: history(),
+        ..Default::default()
+    };
+    let (mut statistics, _) = LibraryStatistics::new::<TestEnv>(&ctx.library, &ctx.history);
+    assert_eq!(statistics.current_streak, 1);
+    let effects = UpdateWithCtx::<TestEnv>::update(
+        &mut statistics,
+        &Msg::Internal(Internal::LibraryChanged(true)),
+        &ctx,
+    );
+    assert!(!effects.has_changed, "statistics are unchanged");
+    ctx.library.items.insert(
+        "tt6".to_owned(),
+        library_item("tt6", "movie", 31, &["Comedy"]),
+    );
+    ctx.library.items.remove("tt4");
+    let effects = UpdateWithCtx::<TestEnv>::update(
+        &mut statistics,
+        &Msg::Internal(Internal::LibraryChanged(true)),
+        &ctx,
+    );
+    assert!(effects.has_changed);
+    assert_eq!(statistics.titles, 4);
+    assert_eq!(statistics.types[0].titles, 3);
+    assert_eq!(statistics.types[1].time_watched, 5000);
+    assert_eq!(statistics.current_streak, 1);
+    ctx.history.record(history_entry("tt6", 31, 10));
+    let effects = UpdateWithCtx::<TestEnv>::update(
+        &mut statistics,
+        &Msg::Internal(Internal::HistoryChanged(false)),
+        &ctx,
+    );
+    assert!(effects.has_changed);
+    assert_eq!(
+        statistics.current_streak, 2,
+        "streak is updated with the history"
+    );
+}
@@ -0,0 +1,3 @@
+mod library_export;
+mod library_item_merge;
+mod library_statistics;
@@ -0,0 +1,204 @@
+use crate::constants::CATALOG_PAGE_SIZE;
+use crate::models::ctx::Ctx;
+use crate::models::library_with_filters::{
+    LibraryRequest, LibraryRequestPage, LibraryWithFilters, NotRemovedFilter, Selected, Sort,
+    Status,
+};
+use crate::runtime::msg::{Action, ActionLoad, Msg};
+use crate::runtime::UpdateWithCtx;
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState};
+use crate::unit_tests::TestEnv;
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+
+fn library_item(id: &str, name: &str, state: LibraryItemState) -> LibraryItem {
+    LibraryItem {
+        id: id.to_owned(),
+        name: name.to_owned(),
+        r#type: "movie".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state,
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    }
+}
+
+fn request(search: Option<&str>, status: Option<Status>, sort: Sort) -> LibraryRequest {
+    LibraryRequest {
+        r#type: None,
+        sort,
+        page: LibraryRequestPage::default(),
+        search: search.map(|search| search.to_owned()),
+        status,
+    }
+}
+
+fn load(
+    library_with_filters: &mut LibraryWithFilters<NotRemovedFilter>,
+    ctx: &Ctx,
+    request: LibraryRequest,
+) {
+    UpdateWithCtx::<TestEnv>::update(
+        library_with_filters,
+        &Msg::Action(Action::Load(ActionLoad::LibraryWithFilters(Selected {
+            request,
+        }))),
+        ctx,
+    );
+}
+
+fn catalog_ids(library_with_filters: &LibraryWithFilters<NotRemovedFilter>) -> Vec<&str> {
+    library_with_filters
+        .catalog
+        .iter()
+        .map(|library_item| library_item.id.as_str())
+        .collect()
+}
+
+#[test]
+fn load_library_with_filters_search_and_status() {
+    let ctx = Ctx {
+        library: LibraryBucket::new(
+            None,
+            vec![
+                library_item(
+                    "tt1",
+                    "Café Society",
+                    LibraryItemState {
+                        flagged_watched: 1,
+                        ..Default::default()
+                    },
+                ),
+                library_item(
+                    "tt2",
+                    "CAFE noir",
+                    LibraryItemState {
+                        time_offset: 900,
+                        duration: 1000,
+                        ..Default::default()
+                    },
+                ),
+                library_item(
+                    "tt3",
+                    "The Cafeteria",
+                    LibraryItemState {
+                        time_offset: 100,
+                        duration: 1000,
+                        ..Default::default()
+                    },
+                ),
+                library_item("tt4", "Noir", Default::default()),
+            ],
+        ),
+        ..Default::default()
+    };
+    let (mut library_with_filters, _) = LibraryWithFilters::<NotRemovedFilter>::new(&ctx.library);
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(Some(" cafe "), None, Sort::Name),
+    );
+    assert_eq!(
+        catalog_ids(&library_with_filters),
+        vec!["tt2", "tt1", "tt3"],
+        "search ignores case and diacritics"
+    );
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(Some("cafe"), None, Sort::Progress),
+    );
+    assert_eq!(
+        catalog_ids(&library_with_filters),
+        vec!["tt2", "tt3", "tt1"]
+    );
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(Some("cafe"), Some(Status::InProgress), Sort::Name),
+    );
+    assert_eq!(catalog_ids(&library_with_filters), vec!["tt2", "tt3"]);
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(None, Some(Status::Unwatched), Sort::Name),
+    );
+    assert_eq!(catalog_ids(&library_with_filters), vec!["tt4"]);
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(None, Some(Status::Watched), Sort::Name),
+    );
+    assert_eq!(catalog_ids(&library_with_filters), vec!["tt1"]);
+    let statuses = &library_with_filters.selectable.statuses;
+    assert_eq!(statuses.len(), 4);
+    assert!(statuses[1].selected && statuses[1].status == Some(Status::Watched));
+    assert!(
+        statuses[0].request == request(None, None, Sort::Name),
+        "status request keeps the search and sort"
+    );
+    assert!(library_with_filters
+        .selectable
+        .sorts
+        .iter()
+        .all(|sort| sort.request.status == Some(Status::Watched)));
+}
+
+#[test]
+fn load_library_with_filters_search_pages() {
+    let ctx = Ctx {
+        library: LibraryBucket::new(
+            None,
+            (0..CATALOG_PAGE_SIZE + 10)
+                .map(|index| {
+                    library_item(
+                        &format!("tt{}", index),
+                        &format!("Série {}", index),
+                        Default::default(),
+                    )
+                })
+                .chain((0..CATALOG_PAGE_SIZE).map(|index| {
+                    library_item(
+                        &format!("other{}", index),
+                        &format!("Movie {}", index),
+                        Default::default(),
+                    )
+                }))
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let (mut library_with_filters, _) = LibraryWithFilters::<NotRemovedFilter>::new(&ctx.library);
+    load(
+        &mut library_with_filters,
+        &ctx,
+        request(Some("serie"), None, Sort::LastWatched),
+    );
+    assert_eq!(library_with_filters.catalog.len(), CATALOG_PAGE_SIZE);
+    let next_page = library_with_filters
+        .selectable
+        .next_page
+        .as_ref()
+        .expect("next page")
+        .request
+        .to_owned();
+    assert_eq!(next_page.search, Some("serie".to_owned()));
+    load(&mut library_with_filters, &ctx, next_page);
+    assert_eq!(library_with_filters.catalog.len(), 10);
+    assert!(library_with_filters.selectable.next_page.is_none());
+    assert_eq!(
+        library_with_filters
+            .selectable
+            .prev_page
+            .as_ref()
+            .map(|page| page.request.page.get()),
+        Some(1),
+        "previous page is the first one"
+    );
+}
@@ -0,0 +1,134 @@
+use crate::models::ctx::Ctx;
+use crate::models::list_with_filters::{ListRequest, ListWithFilters, Selected, Sort};
+use crate::runtime::msg::{Action, ActionLoad, Internal, Msg};
+use crate::runtime::UpdateWithCtx;
+use crate::types::lists::{ListsBucket, UserList, UserListItem};
+use crate::unit_tests::TestEnv;
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+
+fn list_item(id: &str, r#type: &str, name: &str, added_day: u32) -> UserListItem {
+    UserListItem {
+        id: id.to_owned(),
+        r#type: r#type.to_owned(),
+        name: name.to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        added: Utc.with_ymd_and_hms(2020, 1, added_day, 0, 0, 0).unwrap(),
+    }
+}
+
+fn ctx() -> Ctx {
+    Ctx {
+        lists: ListsBucket::new(
+            None,
+            vec![UserList {
+                id: "list".to_owned(),
+                name: "List".to_owned(),
+                items: vec![
+                    list_item("tt1", "movie", "Beta", 1),
+                    list_item("tt2", "series", "alpha", 2),
+                    list_item("tt3", "movie", "Gamma", 3),
+                    list_item("tt0", "movie", "gamma", 3),
+                ],
+                removed: false,
+                ctime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+                mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+            }],
+        ),
+        ..Default::default()
+    }
+}
+
+fn load(list_with_filters: &mut ListWithFilters, ctx: &Ctx, request: ListRequest) {
+    UpdateWithCtx::<TestEnv>::update(
+        list_with_filters,
+        &Msg::Action(Action::Load(ActionLoad::ListWithFilters(Selected {
+            request,
+        }))),
+        ctx,
+    );
+}
+
+fn catalog_ids(list_with_filters: &ListWithFilters) -> Vec<&str> {
+    list_with_filters
+        .catalog
+        .iter()
+        .map(|item| item.id.as_str())
+        .collect()
+}
+
+#[test]
+fn load_list_with_filters() {
+    let ctx = ctx();
+    let (mut list_with_filters, _) = ListWithFilters::new(&ctx.lists);
+    assert_eq!(list_with_filters.selectable.lists.len(), 1);
+    load(
+        &mut list_with_filters,
+        &ctx,
+        ListRequest {
+            id: "list".to_owned(),
+            r#type: None,
+            sort: Sort::Added,
+            page: Default::default(),
+        },
+    );
+    assert_eq!(
+        catalog_ids(&list_with_filters),
+        vec!["tt0", "tt3", "tt2", "tt1"],
+        "ties are ordered by id"
+    );
+    assert!(list_with_filters.selectable.lists[0].selected);
+    assert_eq!(
+        list_with_filters
+            .selectable
+            .types
+            .iter()
+            .map(|selectable_type| selectable_type.r#type.as_deref())
+            .collect::<Vec<_>>(),
+        vec![None, Some("movie"), Some("series")]
+    );
+    load(
+        &mut list_with_filters,
+        &ctx,
+        ListRequest {
+            id: "list".to_owned(),
+            r#type: Some("movie".to_owned()),
+            sort: Sort::Name,
+            page: Default::default(),
+        },
+    );
+    assert_eq!(catalog_ids(&list_with_filters), vec!["tt1", "tt0", "tt3"]);
+    assert!(list_with_filters.selectable.next_page.is_none());
+}
+
+#[test]
+fn list_with_filters_lists_changed() {
+    let mut ctx = ctx();
+    let (mut list_with_filters, _) = ListWithFilters::new(&ctx.lists);
+    load(
+        &mut list_with_filters,
+        &ctx,
+        ListRequest {
+            id: "list".to_owned(),
+            r#type: None,
+            sort: Sort::Name,
+            page: Default::default(),
+        },
+    );
+    assert_eq!(
+        catalog_ids(&list_with_filters),
+        vec!["tt2", "tt1", "tt0", "tt3"]
+    );
+    ctx.lists.lists.get_mut("list").unwrap().removed = true;
+    UpdateWithCtx::<TestEnv>::update(
+        &mut list_with_filters,
+        &Msg::Internal(Internal::ListsChanged(true)),
+        &ctx,
+    );
+    assert!(
+        list_with_filters.catalog.is_empty(),
+        "removed list is empty"
+    );
+    assert!(list_with_filters.selectable.lists.is_empty());
+}
@@ -0,0 +1,190 @@
+use crate::models::common::{Loadable, ResourceLoadable};
+use crate::models::ctx::Ctx;
+use crate::models::meta_details::{MetaDetails, Selected};
+use crate::runtime::msg::{Action, ActionMetaDetails};
+use crate::runtime::{Effects, Runtime, RuntimeAction, RuntimeEvent};
+use crate::types::addon::{ResourcePath, ResourceRequest};
+use crate::types::library::{LibraryBucket, LibraryItem, LibraryItemState};
+use crate::types::resource::{MetaItem, MetaItemPreview, SeriesInfo, Video};
+use crate::unit_tests::TestEnv;
+use chrono::prelude::TimeZone;
+use chrono::Utc;
+use futures::channel::mpsc::Receiver;
+use stremio_derive::Model;
+use url::Url;
+
+#[derive(Model, Default)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+    meta_details: MetaDetails,
+}
+
+fn video(season: u32, episode: u32) -> Video {
+    Video {
+        id: format!("tt1:{}:{}", season, episode),
+        title: String::default(),
+        released: None,
+        overview: None,
+        thumbnail: None,
+        streams: vec![],
+        series_info: Some(SeriesInfo { season, episode }),
+        trailer_streams: vec![],
+    }
+}
+
+fn runtime(state: LibraryItemState) -> (Runtime<TestEnv, TestModel>, Receiver<RuntimeEvent>) {
+    let videos = vec![
+        video(0, 1),
+        video(1, 1),
+        video(1, 2),
+        video(2, 1),
+        video(2, 2),
+    ];
+    let library_item = LibraryItem {
+        id: "tt1".to_owned(),
+        name: "Series".to_owned(),
+        r#type: "series".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state,
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    };
+    Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item.to_owned()]),
+                ..Default::default()
+            },
+            meta_details: MetaDetails {
+                selected: Some(Selected {
+                    meta_path: ResourcePath::without_extra("meta", "series", "tt1"),
+                    stream_path: None,
+                }),
+                meta_items: vec![ResourceLoadable {
+                    request: ResourceRequest::new(
+                        Url::parse("https://addon.com/manifest.json").unwrap(),
+                        ResourcePath::without_extra("meta", "series", "tt1"),
+                    ),
+                    content: Some(Loadable::Ready(MetaItem {
+                        preview: MetaItemPreview {
+                            id: "tt1".to_owned(),
+                            r#type: "series".to_owned(),
+                            name: "Series".to_owned(),
+                            ..MetaItemPreview::default()
+                        },
+                        videos: videos.to_owned(),
+                    })),
+                    metadata: None,
+                }],
+                watched: Some(library_item.state.watched_bitfield(&videos)),
+                library_item: Some(library_item),
+                ..Default::default()
+            },
+        },
+        Effects::none().unchanged(),
+        1000,
+    )
+}
+
+fn dispatch(runtime: &Runtime<TestEnv, TestModel>, action: ActionMetaDetails) {
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::MetaDetails(action),
+        })
+    });
+}
+
+fn watched_videos(runtime: &Runtime<TestEnv, TestModel>) -> Vec<String> {
+    let model = runtime.model().unwrap();
+    let watched = model.ctx.library.items["tt1"].state.watched_bitfield(
+        &model.meta_details.meta_items[0].content.as_ref().map_or(
+            vec![],
+            |content| match content {
+                Loadable::Ready(meta_item) => meta_item.videos.to_owned(),
+                _ => vec![],
+            },
+        ),
+    );
+    ["tt1:0:1", "tt1:1:1", "tt1:1:2", "tt1:2:1", "tt1:2:2"]
+        .iter()
+        .filter(|video_id| watched.get_video(video_id))
+        .map(|video_id| video_id.to_string())
+        .collect()
+}
+
+#[test]
+fn mark_season_as_watched() {
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = runtime(LibraryItemState {
+        video_id: Some("tt1:2:1".to_owned()),
+        ..Default::default()
+    });
+    dispatch(&runtime, ActionMetaDetails::MarkSeasonAsWatched(2, true));
+    assert_eq!(watched_videos(&runtime), vec!["tt1:2:1", "tt1:2:2"]);
+    let state = runtime.model().unwrap().ctx.library.items["tt1"]
+        .state
+        .to_owned();
+    assert_eq!(
+        state.times_watched, 2,
+        "each newly watched video is counted"
+    );
+    assert_eq!(
+        state.flagged_watched, 1,
+        "current video is flagged as watched"
+    );
+}
+
+#[test]
+fn mark_as_watched_until() {
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = runtime(LibraryItemState::default());
+    dispatch(
+        &runtime,
+        ActionMetaDetails::MarkAsWatchedUntil("tt1:2:1".to_owned(), true),
+    );
+    assert_eq!(
+        watched_videos(&runtime),
+        vec!["tt1:1:1", "tt1:1:2", "tt1:2:1"],
+        "specials are not marked"
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.library.items["tt1"]
+            .state
+            .times_watched,
+        3
+    );
+}
+
+#[test]
+fn mark_series_as_unwatched() {
+    let _env_mutex = TestEnv::reset();
+    let (runtime, _rx) = runtime(LibraryItemState {
+        video_id: Some("tt1:1:2".to_owned()),
+        flagged_watched: 1,
+        ..Default::default()
+    });
+    dispatch(&runtime, ActionMetaDetails::MarkSeriesAsWatched(true));
+    assert_eq!(watched_videos(&runtime).len(), 5);
+    let mtime = runtime.model().unwrap().ctx.library.items["tt1"].mtime;
+    dispatch(&runtime, ActionMetaDetails::MarkSeriesAsWatched(true));
+    assert_eq!(
+        runtime.model().unwrap().ctx.library.items["tt1"].mtime,
+        mtime,
+        "nothing is updated when no video changes"
+    );
+    dispatch(&runtime, ActionMetaDetails::MarkSeriesAsWatched(false));
+    assert!(watched_videos(&runtime).is_empty());
+    let state = runtime.model().unwrap().ctx.library.items["tt1"]
+        .state
+        .to_owned();
+    assert_eq!(state.times_watched, 0);
+    assert_eq!(state.flagged_watched, 0);
+}
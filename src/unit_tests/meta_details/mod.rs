@@ -0,0 +1,3 @@
+mod mark_as_watched;
+mod ranked_streams;
+mod redirect;
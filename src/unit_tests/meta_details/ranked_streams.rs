@@ -0,0 +1,212 @@
+use crate::models::ctx::Ctx;
+use crate::models::meta_details::{MetaDetails, Selected};
+use crate::runtime::msg::{Action, ActionCtx, ActionLoad};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{
+    Descriptor, Manifest, ManifestResource, ResourcePath, ResourceResponse,
+    ResourceResponseWithMetadata,
+};
+use crate::types::profile::{Profile, Settings, StreamPreferences};
+use crate::types::resource::{Stream, StreamResolution, StreamSource, Subtitles};
+use crate::unit_tests::{default_fetch_handler, Request, TestEnv, FETCH_HANDLER};
+use futures::future;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+fn descriptor(id: &str, transport_url: &str) -> Descriptor {
+    Descriptor {
+        manifest: Manifest {
+            id: id.to_owned(),
+            types: vec!["movie".to_owned()],
+            resources: vec![ManifestResource::Short("stream".to_owned())],
+            id_prefixes: Some(vec!["tt".to_owned()]),
+            ..Default::default()
+        },
+        transport_url: Url::parse(transport_url).unwrap(),
+        flags: Default::default(),
+    }
+}
+
+fn torrent(name: &str, description: &str, tracker: &str, subtitles_url: &str) -> Stream {
+    Stream {
+        source: StreamSource::Torrent {
+            info_hash: [1; 20],
+            file_idx: Some(0),
+            announce: vec![tracker.to_owned()],
+        },
+        name: Some(name.to_owned()),
+        description: Some(description.to_owned()),
+        thumbnail: None,
+        subtitles: vec![Subtitles {
+            lang: "eng".to_owned(),
+            url: Url::parse(subtitles_url).unwrap(),
+        }],
+        behavior_hints: Default::default(),
+    }
+}
+
+fn url(url: &str, description: &str) -> Stream {
+    Stream {
+        source: StreamSource::Url {
+            url: Url::parse(url).unwrap(),
+        },
+        name: None,
+        description: Some(description.to_owned()),
+        thumbnail: None,
+        subtitles: vec![],
+        behavior_hints: Default::default(),
+    }
+}
+
+#[test]
+fn ranked_streams() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        meta_details: MetaDetails,
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        let streams = match request.url.as_str() {
+            "https://a.com/stream/movie/tt1.json" => vec![
+                torrent(
+                    "A\n720p",
+                    "Movie.2020.720p.WEB-DL.x264-GRP",
+                    "tracker:udp://a.com",
+                    "https://a.com/subtitles.srt",
+                ),
+                url(
+                    "https://cdn.com/2160p.mkv",
+                    "Movie.2020.2160p.BluRay.x265-GRP",
+                ),
+            ],
+            "https://b.com/stream/movie/tt1.json" => vec![
+                torrent(
+                    "B",
+                    "Movie 720p",
+                    "tracker:udp://b.com",
+                    "https://b.com/subtitles.srt",
+                ),
+                url(
+                    "https://cdn.com/1080p.mp4",
+                    "Movie.2020.ITA.1080p.BluRay.x264-GRP",
+                ),
+            ],
+            _ => return default_fetch_handler(request),
+        };
+        future::ok(Box::new(ResourceResponseWithMetadata::from(
+            ResourceResponse::Streams { streams },
+        )) as Box<dyn Any + Send>)
+        .boxed_env()
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let settings = Settings {
+        stream_preferences: StreamPreferences {
+            max_resolution: Some(StreamResolution::P1080),
+            preferred_languages: vec!["ita".to_owned()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![
+                        descriptor("a", "https://a.com/manifest.json"),
+                        descriptor("b", "https://b.com/manifest.json"),
+                    ],
+                    settings: settings.to_owned(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::MetaDetails(Selected {
+                meta_path: ResourcePath::without_extra("meta", "movie", "tt1"),
+                stream_path: Some(ResourcePath::without_extra("stream", "movie", "tt1")),
+            })),
+        })
+    });
+    let ranked_streams = runtime
+        .model()
+        .unwrap()
+        .meta_details
+        .ranked_streams
+        .to_owned();
+    assert_eq!(
+        runtime.model().unwrap().meta_details.streams.len(),
+        2,
+        "Raw streams of both addons are kept"
+    );
+    assert_eq!(ranked_streams.len(), 3, "Duplicated torrent merged");
+    assert_eq!(
+        ranked_streams
+            .iter()
+            .map(|ranked_stream| ranked_stream.info.resolution)
+            .collect::<Vec<_>>(),
+        vec![
+            Some(StreamResolution::P1080),
+            Some(StreamResolution::P720),
+            Some(StreamResolution::P2160),
+        ],
+        "Preferred language first and above max resolution last"
+    );
+    let torrent = &ranked_streams[1];
+    assert_eq!(torrent.stream.name, Some("A\n720p".to_owned()));
+    assert_eq!(
+        torrent.addons,
+        vec![
+            Url::parse("https://a.com/manifest.json").unwrap(),
+            Url::parse("https://b.com/manifest.json").unwrap(),
+        ]
+    );
+    assert_eq!(
+        torrent
+            .stream
+            .subtitles
+            .iter()
+            .map(|subtitles| subtitles.url.as_str())
+            .collect::<Vec<_>>(),
+        vec!["https://a.com/subtitles.srt", "https://b.com/subtitles.srt"]
+    );
+    assert!(matches!(
+        &torrent.stream.source,
+        StreamSource::Torrent { announce, .. }
+            if *announce == vec!["tracker:udp://a.com".to_owned(), "tracker:udp://b.com".to_owned()]
+    ));
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Ctx(ActionCtx::UpdateSettings(Settings {
+                stream_preferences: StreamPreferences::default(),
+                ..settings
+            })),
+        })
+    });
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .meta_details
+            .ranked_streams
+            .iter()
+            .map(|ranked_stream| ranked_stream.info.resolution)
+            .collect::<Vec<_>>(),
+        vec![
+            Some(StreamResolution::P2160),
+            Some(StreamResolution::P1080),
+            Some(StreamResolution::P720),
+        ],
+        "Ranked again when the preferences change"
+    );
+}
@@ -0,0 +1,170 @@
+use crate::models::common::{Loadable, ResourceLoadable};
+use crate::models::ctx::Ctx;
+use crate::models::meta_details::{MetaDetails, Selected};
+use crate::runtime::msg::{Action, ActionLoad};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{
+    ResourcePath, ResourceResponse, ResourceResponseMetadata, ResourceResponseWithMetadata,
+};
+use crate::types::resource::{MetaItem, MetaItemPreview};
+use crate::unit_tests::{default_fetch_handler, Request, TestEnv, FETCH_HANDLER, REQUESTS};
+use assert_matches::assert_matches;
+use futures::future;
+use std::any::Any;
+use stremio_derive::Model;
+
+#[test]
+fn redirect() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        meta_details: MetaDetails,
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        match request {
+            Request { url, method, .. }
+                if url == "https://v3-cinemeta.strem.io/meta/movie/tt1.json" && method == "GET" =>
+            {
+                future::ok(Box::new(ResourceResponseWithMetadata {
+                    content: None,
+                    metadata: ResourceResponseMetadata {
+                        redirect: Some("tt2".to_owned()),
+                        ..ResourceResponseMetadata::default()
+                    },
+                }) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            Request { url, method, .. }
+                if url == "https://v3-cinemeta.strem.io/meta/movie/tt2.json" && method == "GET" =>
+            {
+                future::ok(
+                    Box::new(ResourceResponseWithMetadata::from(ResourceResponse::Meta {
+                        meta: MetaItem {
+                            preview: MetaItemPreview {
+                                id: "tt2".to_owned(),
+                                r#type: "movie".to_owned(),
+                                name: "name".to_owned(),
+                                ..MetaItemPreview::default()
+                            },
+                            videos: vec![],
+                        },
+                    })) as Box<dyn Any + Send>,
+                )
+                .boxed_env()
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) =
+        Runtime::<TestEnv, _>::new(TestModel::default(), Effects::none().unchanged(), 1000);
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::MetaDetails(Selected {
+                meta_path: ResourcePath::without_extra("meta", "movie", "tt1"),
+                stream_path: None,
+            })),
+        })
+    });
+    let model = runtime.model().unwrap();
+    assert_eq!(
+        model
+            .meta_details
+            .selected
+            .as_ref()
+            .map(|selected| &selected.meta_path),
+        Some(&ResourcePath::without_extra("meta", "movie", "tt2")),
+        "Selected meta path is redirected"
+    );
+    assert_eq!(model.meta_details.meta_items.len(), 1);
+    assert_matches!(
+        &model.meta_details.meta_items[0],
+        ResourceLoadable {
+            request,
+            content: Some(Loadable::Ready(meta_item)),
+            ..
+        } if request.path.id == "tt2" && meta_item.preview.id == "tt2",
+        "Redirected meta item loaded"
+    );
+    let requests = REQUESTS.read().unwrap();
+    assert_eq!(requests.len(), 2, "Two requests have been sent");
+    assert_eq!(
+        requests[1].url,
+        "https://v3-cinemeta.strem.io/meta/movie/tt2.json".to_owned(),
+        "Redirect has been followed"
+    );
+}
+
+#[test]
+fn redirect_cycle() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        meta_details: MetaDetails,
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        let redirect = match request.url.as_str() {
+            "https://v3-cinemeta.strem.io/meta/movie/tt1.json" => "tt2",
+            "https://v3-cinemeta.strem.io/meta/movie/tt2.json" => "tt1",
+            url if url.contains("/stream/movie/") => {
+                return future::ok(Box::new(ResourceResponseWithMetadata::from(
+                    ResourceResponse::Streams { streams: vec![] },
+                )) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            _ => return default_fetch_handler(request),
+        };
+        future::ok(Box::new(ResourceResponseWithMetadata {
+            content: None,
+            metadata: ResourceResponseMetadata {
+                redirect: Some(redirect.to_owned()),
+                ..ResourceResponseMetadata::default()
+            },
+        }) as Box<dyn Any + Send>)
+        .boxed_env()
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) =
+        Runtime::<TestEnv, _>::new(TestModel::default(), Effects::none().unchanged(), 1000);
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::MetaDetails(Selected {
+                meta_path: ResourcePath::without_extra("meta", "movie", "tt1"),
+                stream_path: Some(ResourcePath::without_extra("stream", "movie", "tt1")),
+            })),
+        })
+    });
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .meta_details
+            .selected
+            .as_ref()
+            .map(|selected| (
+                selected.meta_path.to_owned(),
+                selected.stream_path.to_owned()
+            )),
+        Some((
+            ResourcePath::without_extra("meta", "movie", "tt2"),
+            Some(ResourcePath::without_extra("stream", "movie", "tt2"))
+        )),
+        "Stream path of the movie is redirected as well"
+    );
+    assert_eq!(
+        REQUESTS
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.contains("/meta/"))
+            .count(),
+        2,
+        "Redirect back to the first meta is not followed"
+    );
+}
@@ -1,10 +1,23 @@
 mod env;
 use env::*;
 
+mod addon_transport;
 mod catalog_with_filters;
+mod continue_watching_preview;
 mod ctx;
+mod library;
+mod library_with_filters;
+mod list_with_filters;
+mod meta_details;
+mod player;
+mod remote_addons;
 mod serde;
+mod stream_info;
+mod subtitles;
+mod torrent;
+mod watch_history;
 
+mod language;
 mod link;
 
 mod deep_links;
@@ -0,0 +1,3 @@
+mod subtitles_request;
+mod subtitles_selection;
+mod watch_history;
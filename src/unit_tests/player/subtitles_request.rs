@@ -0,0 +1,113 @@
+use crate::models::ctx::Ctx;
+use crate::models::player::{Player, Selected};
+use crate::runtime::msg::{Action, ActionLoad};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{
+    Descriptor, ExtraValue, Manifest, ManifestResource, ResourcePath, ResourceResponse,
+    ResourceResponseWithMetadata,
+};
+use crate::types::profile::Profile;
+use crate::types::resource::{Stream, StreamBehaviorHints, StreamSource};
+use crate::unit_tests::{Request, TestEnv, FETCH_HANDLER, REQUESTS};
+use futures::future;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+#[test]
+fn subtitles_request_with_behavior_hints() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        player: Player,
+    }
+    fn fetch_handler(_request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        future::ok(Box::new(ResourceResponseWithMetadata::from(
+            ResourceResponse::Subtitles { subtitles: vec![] },
+        )) as Box<dyn Any + Send>)
+        .boxed_env()
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![Descriptor {
+                        manifest: Manifest {
+                            id: "subs".to_owned(),
+                            types: vec!["movie".to_owned()],
+                            resources: vec![ManifestResource::Short("subtitles".to_owned())],
+                            ..Default::default()
+                        },
+                        transport_url: Url::parse("https://subs.com/manifest.json").unwrap(),
+                        flags: Default::default(),
+                    }],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(Selected {
+                stream: Stream {
+                    source: StreamSource::Url {
+                        url: Url::parse("https://cdn.com/video.mkv").unwrap(),
+                    },
+                    name: None,
+                    description: None,
+                    thumbnail: None,
+                    subtitles: vec![],
+                    behavior_hints: StreamBehaviorHints {
+                        filename: Some("Movie.2020.1080p.mkv".to_owned()),
+                        video_hash: Some("8e245d9679d31e12".to_owned()),
+                        video_size: Some(12909756),
+                        ..Default::default()
+                    },
+                },
+                stream_request: None,
+                meta_request: None,
+                subtitles_path: Some(ResourcePath::with_extra(
+                    "subtitles",
+                    "movie",
+                    "tt1",
+                    &[ExtraValue {
+                        name: "videoSize".to_owned(),
+                        value: "1".to_owned(),
+                    }],
+                )),
+            })),
+        })
+    });
+    let subtitles = &runtime.model().unwrap().player.subtitles;
+    assert_eq!(subtitles.len(), 1);
+    assert_eq!(
+        subtitles[0].request.path.extra,
+        vec![
+            ExtraValue {
+                name: "videoSize".to_owned(),
+                value: "1".to_owned(),
+            },
+            ExtraValue {
+                name: "videoHash".to_owned(),
+                value: "8e245d9679d31e12".to_owned(),
+            },
+            ExtraValue {
+                name: "filename".to_owned(),
+                value: "Movie.2020.1080p.mkv".to_owned(),
+            },
+        ],
+        "Hints added as extra without overriding the subtitles path"
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap()[0].url,
+        "https://subs.com/subtitles/movie/tt1/videoSize=1&videoHash=8e245d9679d31e12&filename=Movie.2020.1080p.mkv.json"
+    );
+}
@@ -0,0 +1,323 @@
+use crate::models::ctx::Ctx;
+use crate::models::player::{Player, Selected, SubtitlesOrigin};
+use crate::runtime::msg::{Action, ActionLoad, ActionPlayer};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, RuntimeEvent, TryEnvFuture};
+use crate::types::addon::{
+    Descriptor, Manifest, ManifestResource, ResourcePath, ResourceRequest, ResourceResponse,
+    ResourceResponseWithMetadata,
+};
+use crate::types::profile::{Profile, Settings, SubtitlesPreferences};
+use crate::types::resource::{MetaItem, Stream, StreamSource, Subtitles};
+use crate::unit_tests::{default_fetch_handler, Request, TestEnv, FETCH_HANDLER};
+use futures::channel::mpsc::Receiver;
+use futures::future;
+use serde_json::json;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+#[derive(Model, Default)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+    player: Player,
+}
+
+fn descriptor(id: &str, resource: &str) -> Descriptor {
+    Descriptor {
+        manifest: Manifest {
+            id: id.to_owned(),
+            types: vec!["series".to_owned()],
+            resources: vec![ManifestResource::Short(resource.to_owned())],
+            id_prefixes: Some(vec!["tt".to_owned()]),
+            ..Default::default()
+        },
+        transport_url: Url::parse(&format!("https://{}.com/manifest.json", id)).unwrap(),
+        flags: Default::default(),
+    }
+}
+
+fn subtitles(lang: &str, url: &str) -> Subtitles {
+    Subtitles {
+        lang: lang.to_owned(),
+        url: Url::parse(url).unwrap(),
+    }
+}
+
+fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+    let response = match request.url.as_str() {
+        "https://meta.com/meta/series/tt1.json" => ResourceResponse::Meta {
+            meta: serde_json::from_value::<MetaItem>(json!({
+                "id": "tt1",
+                "type": "series",
+                "name": "Series",
+                "videos": [
+                    { "id": "tt1:1:1", "title": "Episode 1", "season": 1, "episode": 1 },
+                    { "id": "tt1:1:2", "title": "Episode 2", "season": 1, "episode": 2 },
+                ],
+            }))
+            .unwrap(),
+        },
+        "https://subs.com/subtitles/series/tt1%3A1%3A1.json"
+        | "https://subs.com/subtitles/series/tt1%3A1%3A2.json" => ResourceResponse::Subtitles {
+            subtitles: vec![
+                subtitles("fre", "https://subs.com/fr.srt"),
+                subtitles("eng (SDH)", "https://subs.com/en.srt"),
+                subtitles("spa", "https://subs.com/es.srt"),
+                subtitles("en", "https://subs.com/en.sdh.srt"),
+            ],
+        },
+        _ => return default_fetch_handler(request),
+    };
+    future::ok(Box::new(ResourceResponseWithMetadata::from(response)) as Box<dyn Any + Send>)
+        .boxed_env()
+}
+
+fn selected(video_id: &str) -> Selected {
+    Selected {
+        stream: Stream {
+            source: StreamSource::Url {
+                url: Url::parse("https://cdn.com/video.mkv").unwrap(),
+            },
+            name: None,
+            description: None,
+            thumbnail: None,
+            subtitles: vec![
+                subtitles("spa", "https://cdn.com/es.vtt"),
+                subtitles("eng", "https://cdn.com/en.vtt"),
+            ],
+            behavior_hints: Default::default(),
+        },
+        stream_request: Some(ResourceRequest {
+            base: Url::parse("https://streams.com/manifest.json").unwrap(),
+            path: ResourcePath::without_extra("stream", "series", video_id),
+        }),
+        meta_request: Some(ResourceRequest {
+            base: Url::parse("https://meta.com/manifest.json").unwrap(),
+            path: ResourcePath::without_extra("meta", "series", "tt1"),
+        }),
+        subtitles_path: Some(ResourcePath::without_extra("subtitles", "series", video_id)),
+    }
+}
+
+fn runtime(settings: Settings) -> (Runtime<TestEnv, TestModel>, Receiver<RuntimeEvent>) {
+    Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![descriptor("meta", "meta"), descriptor("subs", "subtitles")],
+                    settings,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    )
+}
+
+fn selected_url(runtime: &Runtime<TestEnv, TestModel>) -> Option<String> {
+    runtime
+        .model()
+        .unwrap()
+        .player
+        .selected_subtitles
+        .as_ref()
+        .map(|candidate| candidate.subtitles.url.to_string())
+}
+
+fn alternative_urls(runtime: &Runtime<TestEnv, TestModel>) -> Vec<String> {
+    runtime
+        .model()
+        .unwrap()
+        .player
+        .subtitles_alternatives
+        .iter()
+        .map(|candidate| candidate.subtitles.url.to_string())
+        .collect()
+}
+
+#[test]
+fn automatic_selection() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = runtime(Settings {
+        subtitles_language: "eng".to_owned(),
+        subtitles_preferences: SubtitlesPreferences {
+            secondary_language: Some("spa".to_owned()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        Some("https://cdn.com/en.vtt".to_owned()),
+        "Embedded subtitles in the subtitles language selected"
+    );
+    assert_eq!(
+        alternative_urls(&runtime),
+        vec![
+            "https://subs.com/en.srt",
+            "https://subs.com/en.sdh.srt",
+            "https://cdn.com/es.vtt",
+            "https://subs.com/es.srt",
+            "https://subs.com/fr.srt",
+        ],
+        "Hearing impaired after the rest of the same language, then the secondary language"
+    );
+    let model = runtime.model().unwrap();
+    let alternatives = &model.player.subtitles_alternatives;
+    assert!(alternatives[0].hearing_impaired && alternatives[1].hearing_impaired);
+    assert_eq!(
+        alternatives[0].origin,
+        SubtitlesOrigin::Addon {
+            transport_url: Url::parse("https://subs.com/manifest.json").unwrap()
+        }
+    );
+}
+
+#[test]
+fn secondary_language() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = runtime(Settings {
+        subtitles_language: "ger".to_owned(),
+        subtitles_preferences: SubtitlesPreferences {
+            secondary_language: Some("fre".to_owned()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        Some("https://subs.com/fr.srt".to_owned())
+    );
+}
+
+#[test]
+fn no_matching_language() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = runtime(Settings {
+        subtitles_language: "ger".to_owned(),
+        ..Default::default()
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    assert_eq!(selected_url(&runtime), None);
+    assert_eq!(alternative_urls(&runtime).len(), 6);
+}
+
+#[test]
+fn hearing_impaired_preferred() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = runtime(Settings {
+        subtitles_language: "eng".to_owned(),
+        subtitles_preferences: SubtitlesPreferences {
+            hearing_impaired: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        Some("https://subs.com/en.srt".to_owned())
+    );
+}
+
+#[test]
+fn series_override() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let (runtime, _rx) = runtime(Settings {
+        subtitles_language: "eng".to_owned(),
+        ..Default::default()
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Player(ActionPlayer::SelectSubtitles(Some(
+                Url::parse("https://subs.com/es.srt").unwrap(),
+            ))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        Some("https://subs.com/es.srt".to_owned()),
+        "Manually selected subtitles kept"
+    );
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .ctx
+            .profile
+            .settings
+            .subtitles_preferences
+            .series_overrides
+            .get("tt1"),
+        Some(&Some("spa".to_owned())),
+        "Language remembered for the series"
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:2"))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        Some("https://cdn.com/es.vtt".to_owned()),
+        "Series language used for the next episode"
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Player(ActionPlayer::SelectSubtitles(None)),
+        })
+    });
+    assert_eq!(selected_url(&runtime), None);
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+        })
+    });
+    assert_eq!(
+        selected_url(&runtime),
+        None,
+        "Subtitles stay off for the series"
+    );
+    assert_eq!(alternative_urls(&runtime).len(), 6);
+}
@@ -0,0 +1,171 @@
+use crate::constants::HISTORY_STORAGE_KEY;
+use crate::models::ctx::Ctx;
+use crate::models::player::{Player, Selected};
+use crate::runtime::msg::{Action, ActionCtx, ActionLoad, ActionPlayer};
+use crate::runtime::{Effects, EnvError, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{ResourcePath, ResourceRequest};
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::types::library::{LibraryBucket, LibraryItem};
+use crate::types::resource::{Stream, StreamBehaviorHints, StreamSource};
+use crate::unit_tests::{Request, TestEnv, FETCH_HANDLER, NOW, STORAGE};
+use chrono::prelude::TimeZone;
+use chrono::{Duration, Utc};
+use futures::future;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+#[derive(Model, Default)]
+#[model(TestEnv)]
+struct TestModel {
+    ctx: Ctx,
+    player: Player,
+}
+
+fn selected(video_id: &str) -> Selected {
+    Selected {
+        stream: Stream {
+            source: StreamSource::Url {
+                url: Url::parse(&format!("https://cdn.com/{}.mkv", video_id)).unwrap(),
+            },
+            name: None,
+            description: None,
+            thumbnail: None,
+            subtitles: vec![],
+            behavior_hints: StreamBehaviorHints {
+                video_hash: Some("8e245d9679d31e12".to_owned()),
+                ..Default::default()
+            },
+        },
+        stream_request: Some(ResourceRequest::new(
+            Url::parse("https://addon.com/manifest.json").unwrap(),
+            ResourcePath::without_extra("stream", "series", video_id),
+        )),
+        meta_request: Some(ResourceRequest::new(
+            Url::parse("https://addon.com/manifest.json").unwrap(),
+            ResourcePath::without_extra("meta", "series", "tt1"),
+        )),
+        subtitles_path: None,
+    }
+}
+
+fn fetch_handler(_request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+    future::err(EnvError::Fetch("offline".to_owned())).boxed_env()
+}
+
+fn dispatch(runtime: &Runtime<TestEnv, TestModel>, action: Action) {
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action,
+        })
+    });
+}
+
+fn update_state(runtime: &Runtime<TestEnv, TestModel>, seconds: i64) {
+    *NOW.write().unwrap() =
+        Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(seconds);
+    dispatch(
+        runtime,
+        Action::Player(ActionPlayer::UpdateLibraryItemState {
+            time: seconds as u64 * 1000,
+            duration: 60000,
+        }),
+    );
+}
+
+#[test]
+fn player_records_watch_history() {
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let library_item = LibraryItem {
+        id: "tt1".to_owned(),
+        name: "Series".to_owned(),
+        r#type: "series".to_owned(),
+        poster: None,
+        poster_shape: Default::default(),
+        removed: false,
+        temp: false,
+        ctime: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        state: Default::default(),
+        behavior_hints: Default::default(),
+        genres: vec![],
+        released: None,
+    };
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                library: LibraryBucket::new(None, vec![library_item]),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    dispatch(
+        &runtime,
+        Action::Load(ActionLoad::Player(selected("tt1:1:1"))),
+    );
+    update_state(&runtime, 0);
+    update_state(&runtime, 1);
+    update_state(&runtime, 2);
+    assert!(
+        runtime.model().unwrap().ctx.history.entries.is_empty(),
+        "Session is not recorded while playing"
+    );
+    dispatch(&runtime, Action::Player(ActionPlayer::PushToLibrary));
+    dispatch(
+        &runtime,
+        Action::Load(ActionLoad::Player(selected("tt1:1:2"))),
+    );
+    update_state(&runtime, 10);
+    update_state(&runtime, 11);
+    dispatch(&runtime, Action::Unload);
+    let first_session = HistoryEntry {
+        id: "tt1".to_owned(),
+        r#type: "series".to_owned(),
+        name: "Series".to_owned(),
+        video_id: "tt1:1:1".to_owned(),
+        start: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+        end: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 2).unwrap(),
+        duration: 2000,
+    };
+    let second_session = HistoryEntry {
+        video_id: "tt1:1:2".to_owned(),
+        start: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 10).unwrap(),
+        end: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 11).unwrap(),
+        duration: 1000,
+        ..first_session.to_owned()
+    };
+    assert_eq!(
+        runtime.model().unwrap().ctx.history.entries,
+        vec![first_session.to_owned(), second_session.to_owned()],
+        "Sessions recorded once each"
+    );
+    assert_eq!(
+        STORAGE
+            .read()
+            .unwrap()
+            .get(HISTORY_STORAGE_KEY)
+            .map(|data| serde_json::from_str::<HistoryBucket>(data).unwrap()),
+        Some(HistoryBucket::new(
+            None,
+            vec![first_session.to_owned(), second_session.to_owned()]
+        )),
+        "History persisted in storage"
+    );
+    dispatch(
+        &runtime,
+        Action::Ctx(ActionCtx::RemoveHistoryEntry {
+            id: "tt1".to_owned(),
+            start: first_session.start,
+        }),
+    );
+    assert_eq!(
+        runtime.model().unwrap().ctx.history.entries,
+        vec![second_session],
+        "Entry removed from history"
+    );
+}
@@ -0,0 +1,213 @@
+use crate::models::catalog_with_filters::Selected;
+use crate::models::ctx::Ctx;
+use crate::models::remote_addons::{RemoteAddonAction, RemoteAddons};
+use crate::runtime::msg::{Action, ActionLoad, ActionRemoteAddons};
+use crate::runtime::{EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::types::addon::{
+    Descriptor, DescriptorPreview, Manifest, ManifestBehaviorHints, ManifestCatalog,
+    ManifestPreview, ResourcePath, ResourceRequest, ResourceResponse, ResourceResponseWithMetadata,
+};
+use crate::types::profile::Profile;
+use crate::unit_tests::{default_fetch_handler, Request, TestEnv, FETCH_HANDLER};
+use futures::future;
+use semver::Version;
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+fn descriptor(id: &str, transport_url: &str, version: Version) -> Descriptor {
+    Descriptor {
+        manifest: Manifest {
+            id: id.to_owned(),
+            version,
+            ..Default::default()
+        },
+        transport_url: Url::parse(transport_url).unwrap(),
+        flags: Default::default(),
+    }
+}
+
+fn descriptor_preview(
+    id: &str,
+    name: &str,
+    transport_url: &str,
+    version: Version,
+    behavior_hints: ManifestBehaviorHints,
+) -> DescriptorPreview {
+    DescriptorPreview {
+        manifest: ManifestPreview {
+            id: id.to_owned(),
+            version,
+            name: name.to_owned(),
+            behavior_hints,
+            ..Default::default()
+        },
+        transport_url: Url::parse(transport_url).unwrap(),
+    }
+}
+
+#[test]
+fn load_action() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        remote_addons: RemoteAddons,
+    }
+    fn fetch_handler(request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        match request {
+            Request { url, method, .. }
+                if url == "https://catalog.com/addon%5Fcatalog/all/community.json"
+                    && method == "GET" =>
+            {
+                future::ok(Box::new(ResourceResponseWithMetadata::from(
+                    ResourceResponse::Addons {
+                        addons: vec![
+                            descriptor_preview(
+                                "installed",
+                                "Installed",
+                                "https://installed.com/manifest.json",
+                                Version::new(1, 0, 0),
+                                ManifestBehaviorHints::default(),
+                            ),
+                            descriptor_preview(
+                                "outdated",
+                                "Outdated",
+                                "https://outdated.com/v2/manifest.json",
+                                Version::new(2, 0, 0),
+                                ManifestBehaviorHints::default(),
+                            ),
+                            descriptor_preview(
+                                "configurable",
+                                "Configurable",
+                                "https://configurable.com/manifest.json",
+                                Version::new(1, 0, 0),
+                                ManifestBehaviorHints {
+                                    configurable: true,
+                                    ..Default::default()
+                                },
+                            ),
+                            descriptor_preview(
+                                "configuration_required",
+                                "Configuration required",
+                                "https://configuration-required.com/manifest.json",
+                                Version::new(1, 0, 0),
+                                ManifestBehaviorHints {
+                                    configuration_required: true,
+                                    ..Default::default()
+                                },
+                            ),
+                        ],
+                    },
+                )) as Box<dyn Any + Send>)
+                .boxed_env()
+            }
+            _ => default_fetch_handler(request),
+        }
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    let catalog_addon = Descriptor {
+        manifest: Manifest {
+            id: "catalog".to_owned(),
+            addon_catalogs: vec![ManifestCatalog {
+                id: "community".to_owned(),
+                r#type: "all".to_owned(),
+                name: None,
+                extra: Default::default(),
+            }],
+            ..Default::default()
+        },
+        ..descriptor(
+            "catalog",
+            "https://catalog.com/manifest.json",
+            Version::new(1, 0, 0),
+        )
+    };
+    let profile = Profile {
+        addons: vec![
+            catalog_addon,
+            descriptor(
+                "installed",
+                "https://installed.com/manifest.json",
+                Version::new(1, 0, 0),
+            ),
+            descriptor(
+                "outdated",
+                "https://outdated.com/v1/manifest.json",
+                Version::new(1, 0, 0),
+            ),
+        ],
+        ..Default::default()
+    };
+    let (remote_addons, effects) = RemoteAddons::new(&profile);
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile,
+                ..Default::default()
+            },
+            remote_addons,
+        },
+        effects,
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::CatalogWithFilters(Some(Selected {
+                request: ResourceRequest {
+                    base: Url::parse("https://catalog.com/manifest.json").unwrap(),
+                    path: ResourcePath::without_extra("addon_catalog", "all", "community"),
+                },
+            }))),
+        })
+    });
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .remote_addons
+            .addons
+            .iter()
+            .map(|addon| (
+                addon.addon.manifest.id.as_str(),
+                addon.installed,
+                addon.actions.to_owned()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("installed", true, vec![]),
+            ("outdated", true, vec![RemoteAddonAction::Upgrade]),
+            (
+                "configurable",
+                false,
+                vec![RemoteAddonAction::Install, RemoteAddonAction::Configure]
+            ),
+            (
+                "configuration_required",
+                false,
+                vec![RemoteAddonAction::Configure]
+            ),
+        ],
+        "Addons joined with the installed addons"
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::RemoteAddons(ActionRemoteAddons::Search(" CONFIG ".to_owned())),
+        })
+    });
+    assert_eq!(
+        runtime
+            .model()
+            .unwrap()
+            .remote_addons
+            .addons
+            .iter()
+            .map(|addon| addon.addon.manifest.id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["configurable", "configuration_required"],
+        "Addons filtered by the search text"
+    );
+}
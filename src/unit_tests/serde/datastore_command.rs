@@ -1,11 +1,12 @@
 use crate::types::api::DatastoreCommand;
+use crate::types::library::LibraryItem;
 use serde_test::{assert_ser_tokens, Token};
 
 #[test]
 fn datastore_command() {
     assert_ser_tokens(
         &vec![
-            DatastoreCommand::Meta,
+            DatastoreCommand::<LibraryItem>::Meta,
             DatastoreCommand::Get {
                 ids: vec!["id".to_owned()],
                 all: true,
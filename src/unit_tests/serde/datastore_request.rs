@@ -1,4 +1,5 @@
 use crate::types::api::{DatastoreCommand, DatastoreRequest};
+use crate::types::library::LibraryItem;
 use crate::types::profile::AuthKey;
 use crate::unit_tests::serde::default_tokens_ext::DefaultTokens;
 use serde_test::{assert_ser_tokens, Token};
@@ -9,7 +10,7 @@ fn datastore_request() {
         &DatastoreRequest {
             auth_key: AuthKey::default(),
             collection: "collection".to_owned(),
-            command: DatastoreCommand::default(),
+            command: DatastoreCommand::<LibraryItem>::default(),
         },
         &[
             vec![Token::Map { len: None }, Token::Str("authKey")],
@@ -3,7 +3,9 @@ use crate::types::addon::{
 };
 use crate::types::api::{APIError, AuthRequest, GDPRConsentRequest};
 use crate::types::library::LibraryItemState;
-use crate::types::profile::{Auth, AuthKey, GDPRConsent, Settings, User};
+use crate::types::profile::{
+    Auth, AuthKey, GDPRConsent, Settings, StreamPreferences, SubtitlesPreferences, User,
+};
 use crate::types::resource::{
     MetaItem, MetaItemBehaviorHints, PosterShape, SeriesInfo, StreamBehaviorHints, StreamSource,
     Subtitles,
@@ -104,28 +106,33 @@ impl DefaultTokens for PosterShape {
 
 impl DefaultTokens for ManifestPreview {
     fn default_tokens() -> Vec<Token> {
-        vec![
-            Token::Struct {
-                name: "ManifestPreview",
-                len: 7,
-            },
-            Token::Str("id"),
-            Token::Str(""),
-            Token::Str("version"),
-            Token::Str("0.0.1"),
-            Token::Str("name"),
-            Token::Str(""),
-            Token::Str("description"),
-            Token::None,
-            Token::Str("logo"),
-            Token::None,
-            Token::Str("background"),
-            Token::None,
-            Token::Str("types"),
-            Token::Seq { len: Some(0) },
-            Token::SeqEnd,
-            Token::StructEnd,
+        [
+            vec![
+                Token::Struct {
+                    name: "ManifestPreview",
+                    len: 8,
+                },
+                Token::Str("id"),
+                Token::Str(""),
+                Token::Str("version"),
+                Token::Str("0.0.1"),
+                Token::Str("name"),
+                Token::Str(""),
+                Token::Str("description"),
+                Token::None,
+                Token::Str("logo"),
+                Token::None,
+                Token::Str("background"),
+                Token::None,
+                Token::Str("types"),
+                Token::Seq { len: Some(0) },
+                Token::SeqEnd,
+                Token::Str("behaviorHints"),
+            ],
+            ManifestBehaviorHints::default_tokens(),
+            vec![Token::StructEnd],
         ]
+        .concat()
     }
 }
 
@@ -356,48 +363,95 @@ impl DefaultTokens for Auth {
 }
 
 impl DefaultTokens for Settings {
+    fn default_tokens() -> Vec<Token> {
+        [
+            vec![
+                Token::Struct {
+                    name: "Settings",
+                    len: 20,
+                },
+                Token::Str("interfaceLanguage"),
+                Token::Str("eng"),
+                Token::Str("streamingServerUrl"),
+                Token::Str("http://127.0.0.1:11470/"),
+                Token::Str("bingeWatching"),
+                Token::Bool(false),
+                Token::Str("playInBackground"),
+                Token::Bool(true),
+                Token::Str("playInExternalPlayer"),
+                Token::Bool(false),
+                Token::Str("hardwareDecoding"),
+                Token::Bool(true),
+                Token::Str("audioPassthrough"),
+                Token::Bool(false),
+                Token::Str("audioLanguage"),
+                Token::Str("eng"),
+                Token::Str("subtitlesLanguage"),
+                Token::Str("eng"),
+                Token::Str("subtitlesSize"),
+                Token::U8(100),
+                Token::Str("subtitlesFont"),
+                Token::Str("Roboto"),
+                Token::Str("subtitlesBold"),
+                Token::Bool(false),
+                Token::Str("subtitlesOffset"),
+                Token::U8(5),
+                Token::Str("subtitlesTextColor"),
+                Token::Str("#FFFFFFFF"),
+                Token::Str("subtitlesBackgroundColor"),
+                Token::Str("#00000000"),
+                Token::Str("subtitlesOutlineColor"),
+                Token::Str("#00000000"),
+                Token::Str("seekTimeDuration"),
+                Token::U32(20000),
+                Token::Str("streamingServerWarningDismissed"),
+                Token::None,
+                Token::Str("streamPreferences"),
+            ],
+            StreamPreferences::default_tokens(),
+            vec![Token::Str("subtitlesPreferences")],
+            SubtitlesPreferences::default_tokens(),
+            vec![Token::StructEnd],
+        ]
+        .concat()
+    }
+}
+
+impl DefaultTokens for StreamPreferences {
     fn default_tokens() -> Vec<Token> {
         vec![
             Token::Struct {
-                name: "Settings",
-                len: 18,
+                name: "StreamPreferences",
+                len: 4,
             },
-            Token::Str("interfaceLanguage"),
-            Token::Str("eng"),
-            Token::Str("streamingServerUrl"),
-            Token::Str("http://127.0.0.1:11470/"),
-            Token::Str("bingeWatching"),
-            Token::Bool(false),
-            Token::Str("playInBackground"),
-            Token::Bool(true),
-            Token::Str("playInExternalPlayer"),
-            Token::Bool(false),
-            Token::Str("hardwareDecoding"),
-            Token::Bool(true),
-            Token::Str("audioPassthrough"),
-            Token::Bool(false),
-            Token::Str("audioLanguage"),
-            Token::Str("eng"),
-            Token::Str("subtitlesLanguage"),
-            Token::Str("eng"),
-            Token::Str("subtitlesSize"),
-            Token::U8(100),
-            Token::Str("subtitlesFont"),
-            Token::Str("Roboto"),
-            Token::Str("subtitlesBold"),
-            Token::Bool(false),
-            Token::Str("subtitlesOffset"),
-            Token::U8(5),
-            Token::Str("subtitlesTextColor"),
-            Token::Str("#FFFFFFFF"),
-            Token::Str("subtitlesBackgroundColor"),
-            Token::Str("#00000000"),
-            Token::Str("subtitlesOutlineColor"),
-            Token::Str("#00000000"),
-            Token::Str("seekTimeDuration"),
-            Token::U32(20000),
-            Token::Str("streamingServerWarningDismissed"),
+            Token::Str("maxResolution"),
+            Token::None,
+            Token::Str("preferredLanguages"),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::Str("delivery"),
+            Token::None,
+            Token::Str("maxSize"),
+            Token::None,
+            Token::StructEnd,
+        ]
+    }
+}
+
+impl DefaultTokens for SubtitlesPreferences {
+    fn default_tokens() -> Vec<Token> {
+        vec![
+            Token::Struct {
+                name: "SubtitlesPreferences",
+                len: 3,
+            },
+            Token::Str("secondaryLanguage"),
             Token::None,
+            Token::Str("hearingImpaired"),
+            Token::Bool(false),
+            Token::Str("seriesOverrides"),
+            Token::Map { len: Some(0) },
+            Token::MapEnd,
             Token::StructEnd,
         ]
     }
@@ -22,6 +22,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
             LibraryItem {
                 id: "id".to_owned(),
@@ -35,6 +37,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
         ],
         &[
@@ -120,6 +124,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
             LibraryItem {
                 id: "id".to_owned(),
@@ -133,6 +139,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
             LibraryItem {
                 id: "id".to_owned(),
@@ -146,6 +154,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
             LibraryItem {
                 id: "id".to_owned(),
@@ -159,6 +169,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
             LibraryItem {
                 id: "id".to_owned(),
@@ -172,6 +184,8 @@ fn library_item() {
                 mtime: Utc.ymd(2020, 1, 1).and_hms_milli(0, 0, 0, 0),
                 state: LibraryItemState::default(),
                 behavior_hints: MetaItemBehaviorHints::default(),
+                genres: vec![],
+                released: None,
             },
         ],
         &[
@@ -1,4 +1,5 @@
-use crate::types::addon::ManifestPreview;
+use crate::types::addon::{ManifestBehaviorHints, ManifestPreview};
+use crate::unit_tests::serde::default_tokens_ext::DefaultTokens;
 use semver::Version;
 use serde_test::{assert_de_tokens, assert_tokens, Token};
 
@@ -14,6 +15,7 @@ fn manifest_preview() {
                 logo: Some("logo".to_owned()),
                 background: Some("background".to_owned()),
                 types: vec!["type".to_owned()],
+                behavior_hints: ManifestBehaviorHints::default(),
             },
             ManifestPreview {
                 id: "id".to_owned(),
@@ -23,56 +25,65 @@ fn manifest_preview() {
                 logo: None,
                 background: None,
                 types: vec![],
+                behavior_hints: ManifestBehaviorHints::default(),
             },
         ],
         &[
-            Token::Seq { len: Some(2) },
-            Token::Struct {
-                name: "ManifestPreview",
-                len: 7,
-            },
-            Token::Str("id"),
-            Token::Str("id"),
-            Token::Str("version"),
-            Token::Str("0.0.1"),
-            Token::Str("name"),
-            Token::Str("name"),
-            Token::Str("description"),
-            Token::Some,
-            Token::Str("description"),
-            Token::Str("logo"),
-            Token::Some,
-            Token::Str("logo"),
-            Token::Str("background"),
-            Token::Some,
-            Token::Str("background"),
-            Token::Str("types"),
-            Token::Seq { len: Some(1) },
-            Token::Str("type"),
-            Token::SeqEnd,
-            Token::StructEnd,
-            Token::Struct {
-                name: "ManifestPreview",
-                len: 7,
-            },
-            Token::Str("id"),
-            Token::Str("id"),
-            Token::Str("version"),
-            Token::Str("0.0.1"),
-            Token::Str("name"),
-            Token::Str("name"),
-            Token::Str("description"),
-            Token::None,
-            Token::Str("logo"),
-            Token::None,
-            Token::Str("background"),
-            Token::None,
-            Token::Str("types"),
-            Token::Seq { len: Some(0) },
-            Token::SeqEnd,
-            Token::StructEnd,
-            Token::SeqEnd,
-        ],
+            vec![
+                Token::Seq { len: Some(2) },
+                Token::Struct {
+                    name: "ManifestPreview",
+                    len: 8,
+                },
+                Token::Str("id"),
+                Token::Str("id"),
+                Token::Str("version"),
+                Token::Str("0.0.1"),
+                Token::Str("name"),
+                Token::Str("name"),
+                Token::Str("description"),
+                Token::Some,
+                Token::Str("description"),
+                Token::Str("logo"),
+                Token::Some,
+                Token::Str("logo"),
+                Token::Str("background"),
+                Token::Some,
+                Token::Str("background"),
+                Token::Str("types"),
+                Token::Seq { len: Some(1) },
+                Token::Str("type"),
+                Token::SeqEnd,
+                Token::Str("behaviorHints"),
+            ],
+            ManifestBehaviorHints::default_tokens(),
+            vec![
+                Token::StructEnd,
+                Token::Struct {
+                    name: "ManifestPreview",
+                    len: 8,
+                },
+                Token::Str("id"),
+                Token::Str("id"),
+                Token::Str("version"),
+                Token::Str("0.0.1"),
+                Token::Str("name"),
+                Token::Str("name"),
+                Token::Str("description"),
+                Token::None,
+                Token::Str("logo"),
+                Token::None,
+                Token::Str("background"),
+                Token::None,
+                Token::Str("types"),
+                Token::Seq { len: Some(0) },
+                Token::SeqEnd,
+                Token::Str("behaviorHints"),
+            ],
+            ManifestBehaviorHints::default_tokens(),
+            vec![Token::StructEnd, Token::SeqEnd],
+        ]
+        .concat(),
     );
     assert_de_tokens(
         &ManifestPreview {
@@ -83,6 +94,7 @@ fn manifest_preview() {
             logo: None,
             background: None,
             types: vec![],
+            behavior_hints: ManifestBehaviorHints::default(),
         },
         &[
             Token::Struct {
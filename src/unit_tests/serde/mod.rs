@@ -38,9 +38,11 @@ mod profile;
 mod resource_path;
 mod resource_request;
 mod resource_response;
+mod resource_response_with_metadata;
 mod series_info;
 mod settings;
 mod stream;
+mod stream_behavior_hints;
 mod stream_source;
 mod subtitles;
 mod success_response;
@@ -0,0 +1,70 @@
+use crate::types::addon::{
+    ResourceResponse, ResourceResponseMetadata, ResourceResponseWithMetadata,
+};
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+#[test]
+fn resource_response_with_metadata() {
+    assert_tokens(
+        &ResourceResponseWithMetadata {
+            content: Some(ResourceResponse::Streams { streams: vec![] }),
+            metadata: ResourceResponseMetadata {
+                cache_max_age: Some(3600),
+                stale_revalidate: Some(600),
+                stale_error: Some(86400),
+                redirect: None,
+            },
+        },
+        &[
+            Token::Map { len: None },
+            Token::Str("streams"),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::Str("cacheMaxAge"),
+            Token::Some,
+            Token::U64(3600),
+            Token::Str("staleRevalidate"),
+            Token::Some,
+            Token::U64(600),
+            Token::Str("staleError"),
+            Token::Some,
+            Token::U64(86400),
+            Token::MapEnd,
+        ],
+    );
+    assert_tokens(
+        &ResourceResponseWithMetadata {
+            content: None,
+            metadata: ResourceResponseMetadata {
+                redirect: Some("tt2".to_owned()),
+                ..ResourceResponseMetadata::default()
+            },
+        },
+        &[
+            Token::Map { len: None },
+            Token::Str("redirect"),
+            Token::Some,
+            Token::Str("tt2"),
+            Token::MapEnd,
+        ],
+    );
+    assert_de_tokens(
+        &ResourceResponseWithMetadata::from(ResourceResponse::Streams { streams: vec![] }),
+        &[
+            Token::Map { len: None },
+            Token::Str("streams"),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::MapEnd,
+        ],
+    );
+    assert_de_tokens_error::<ResourceResponseWithMetadata>(
+        &[
+            Token::Map { len: None },
+            Token::Str("cacheMaxAge"),
+            Token::U64(3600),
+            Token::MapEnd,
+        ],
+        "data did not match any variant of ResourceResponse",
+    );
+}
@@ -1,4 +1,5 @@
-use crate::types::profile::Settings;
+use crate::types::profile::{Settings, StreamDelivery, StreamPreferences, SubtitlesPreferences};
+use crate::types::resource::StreamResolution;
 use chrono::prelude::TimeZone;
 use chrono::Utc;
 use serde_test::{assert_tokens, Token};
@@ -26,11 +27,22 @@ fn settings() {
             subtitles_outline_color: "subtitles_outline_color".to_owned(),
             seek_time_duration: 1,
             streaming_server_warning_dismissed: Some(Utc.ymd(2021, 1, 1).and_hms_milli(0, 0, 0, 0)),
+            stream_preferences: StreamPreferences {
+                max_resolution: Some(StreamResolution::P1080),
+                preferred_languages: vec!["eng".to_owned()],
+                delivery: Some(StreamDelivery::P2P),
+                max_size: Some(1),
+            },
+            subtitles_preferences: SubtitlesPreferences {
+                secondary_language: Some("eng".to_owned()),
+                hearing_impaired: true,
+                series_overrides: vec![("tt1".to_owned(), None)].into_iter().collect(),
+            },
         },
         &[
             Token::Struct {
                 name: "Settings",
-                len: 18,
+                len: 20,
             },
             Token::Str("interfaceLanguage"),
             Token::Str("interface_language"),
@@ -69,7 +81,60 @@ fn settings() {
             Token::Str("streamingServerWarningDismissed"),
             Token::Some,
             Token::Str("2021-01-01T00:00:00Z"),
+            Token::Str("streamPreferences"),
+            Token::Struct {
+                name: "StreamPreferences",
+                len: 4,
+            },
+            Token::Str("maxResolution"),
+            Token::Some,
+            Token::UnitVariant {
+                name: "StreamResolution",
+                variant: "1080p",
+            },
+            Token::Str("preferredLanguages"),
+            Token::Seq { len: Some(1) },
+            Token::Str("eng"),
+            Token::SeqEnd,
+            Token::Str("delivery"),
+            Token::Some,
+            Token::UnitVariant {
+                name: "StreamDelivery",
+                variant: "p2p",
+            },
+            Token::Str("maxSize"),
+            Token::Some,
+            Token::U64(1),
+            Token::StructEnd,
+            Token::Str("subtitlesPreferences"),
+            Token::Struct {
+                name: "SubtitlesPreferences",
+                len: 3,
+            },
+            Token::Str("secondaryLanguage"),
+            Token::Some,
+            Token::Str("eng"),
+            Token::Str("hearingImpaired"),
+            Token::Bool(true),
+            Token::Str("seriesOverrides"),
+            Token::Map { len: Some(1) },
+            Token::Str("tt1"),
+            Token::None,
+            Token::MapEnd,
+            Token::StructEnd,
             Token::StructEnd,
         ],
     );
 }
+
+#[test]
+fn settings_de_without_preferences() {
+    let mut settings = serde_json::to_value(Settings::default()).unwrap();
+    let settings_object = settings.as_object_mut().unwrap();
+    settings_object.remove("streamPreferences");
+    settings_object.remove("subtitlesPreferences");
+    assert_eq!(
+        serde_json::from_value::<Settings>(settings).unwrap(),
+        Settings::default()
+    );
+}
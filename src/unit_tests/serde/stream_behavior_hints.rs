@@ -0,0 +1,94 @@
+use crate::types::addon::ResourceResponse;
+use crate::types::resource::{StreamBehaviorHints, StreamProxyHeaders};
+use serde_json::json;
+use serde_test::{assert_tokens, Token};
+
+#[test]
+fn stream_behavior_hints() {
+    assert_tokens(
+        &StreamBehaviorHints {
+            filename: Some("video.mkv".to_owned()),
+            video_hash: Some("8e245d9679d31e12".to_owned()),
+            video_size: Some(1),
+            proxy_headers: Some(StreamProxyHeaders {
+                request: vec![("User-Agent".to_owned(), "stremio".to_owned())]
+                    .into_iter()
+                    .collect(),
+                response: Default::default(),
+            }),
+            ..StreamBehaviorHints::default()
+        },
+        &[
+            Token::Map { len: None },
+            Token::Str("filename"),
+            Token::Some,
+            Token::Str("video.mkv"),
+            Token::Str("videoHash"),
+            Token::Some,
+            Token::Str("8e245d9679d31e12"),
+            Token::Str("videoSize"),
+            Token::Some,
+            Token::U64(1),
+            Token::Str("proxyHeaders"),
+            Token::Some,
+            Token::Struct {
+                name: "StreamProxyHeaders",
+                len: 1,
+            },
+            Token::Str("request"),
+            Token::Map { len: Some(1) },
+            Token::Str("User-Agent"),
+            Token::Str("stremio"),
+            Token::MapEnd,
+            Token::StructEnd,
+            Token::MapEnd,
+        ],
+    );
+}
+
+#[test]
+fn stream_behavior_hints_de_invalid() {
+    let response = serde_json::from_value::<ResourceResponse>(json!({
+        "streams": [
+            {
+                "url": "https://example.com/invalid.mkv",
+                "behaviorHints": {
+                    "bingeGroup": "group",
+                    "filename": 1,
+                    "videoHash": [],
+                    "videoSize": "123",
+                    "proxyHeaders": "headers",
+                },
+            },
+            {
+                "url": "https://example.com/valid.mkv",
+                "behaviorHints": {
+                    "filename": "valid.mkv",
+                    "videoSize": 123,
+                },
+            },
+        ],
+    }))
+    .unwrap();
+    let streams = match response {
+        ResourceResponse::Streams { streams } => streams,
+        _ => panic!("streams expected"),
+    };
+    assert_eq!(
+        streams[0].behavior_hints,
+        StreamBehaviorHints {
+            binge_group: Some("group".to_owned()),
+            ..StreamBehaviorHints::default()
+        },
+        "Invalid hints are ignored"
+    );
+    assert_eq!(
+        streams[1].behavior_hints,
+        StreamBehaviorHints {
+            filename: Some("valid.mkv".to_owned()),
+            video_size: Some(123),
+            ..StreamBehaviorHints::default()
+        },
+        "Stream next to invalid hints is kept"
+    );
+}
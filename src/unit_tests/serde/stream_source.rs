@@ -1,4 +1,4 @@
-use crate::types::resource::StreamSource;
+use crate::types::resource::{ArchiveUrl, StreamSource};
 use serde_test::{assert_de_tokens, assert_ser_tokens, Token};
 use url::Url;
 
@@ -204,3 +204,128 @@ fn stream_source() {
         ],
     );
 }
+
+#[test]
+fn stream_source_archives() {
+    assert_ser_tokens(
+        &vec![
+            StreamSource::Rar {
+                rar_urls: vec![ArchiveUrl {
+                    url: Url::parse("https://rar_url/1.rar").unwrap(),
+                    bytes: Some(1),
+                }],
+                file_idx: Some(1),
+                file_must_include: vec!["mkv".to_owned()],
+            },
+            StreamSource::Zip {
+                zip_urls: vec![ArchiveUrl {
+                    url: Url::parse("https://zip_url/1.zip").unwrap(),
+                    bytes: None,
+                }],
+                file_idx: None,
+                file_must_include: vec![],
+            },
+            StreamSource::Nzb {
+                nzb_url: Url::parse("https://nzb_url/1.nzb").unwrap(),
+                servers: vec!["nntps://host:563/4".to_owned()],
+            },
+        ],
+        &[
+            Token::Seq { len: Some(3) },
+            Token::Struct {
+                name: "StreamSource",
+                len: 3,
+            },
+            Token::Str("rarUrls"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "ArchiveUrl",
+                len: 2,
+            },
+            Token::Str("url"),
+            Token::Str("https://rar_url/1.rar"),
+            Token::Str("bytes"),
+            Token::Some,
+            Token::U64(1),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::Str("fileIdx"),
+            Token::Some,
+            Token::U16(1),
+            Token::Str("fileMustInclude"),
+            Token::Seq { len: Some(1) },
+            Token::Str("mkv"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::Struct {
+                name: "StreamSource",
+                len: 1,
+            },
+            Token::Str("zipUrls"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "ArchiveUrl",
+                len: 1,
+            },
+            Token::Str("url"),
+            Token::Str("https://zip_url/1.zip"),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::Struct {
+                name: "StreamSource",
+                len: 2,
+            },
+            Token::Str("nzbUrl"),
+            Token::Str("https://nzb_url/1.nzb"),
+            Token::Str("servers"),
+            Token::Seq { len: Some(1) },
+            Token::Str("nntps://host:563/4"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::SeqEnd,
+        ],
+    );
+    assert_de_tokens(
+        &vec![
+            StreamSource::Rar {
+                rar_urls: vec![
+                    ArchiveUrl {
+                        url: Url::parse("https://rar_url/1.rar").unwrap(),
+                        bytes: None,
+                    },
+                    ArchiveUrl {
+                        url: Url::parse("https://rar_url/2.rar").unwrap(),
+                        bytes: Some(1),
+                    },
+                ],
+                file_idx: None,
+                file_must_include: vec![],
+            },
+            StreamSource::Nzb {
+                nzb_url: Url::parse("https://nzb_url/1.nzb").unwrap(),
+                servers: vec![],
+            },
+        ],
+        &[
+            Token::Seq { len: Some(2) },
+            Token::Map { len: Some(1) },
+            Token::Str("rarUrls"),
+            Token::Seq { len: Some(2) },
+            Token::Str("https://rar_url/1.rar"),
+            Token::Map { len: Some(2) },
+            Token::Str("url"),
+            Token::Str("https://rar_url/2.rar"),
+            Token::Str("bytes"),
+            Token::U64(1),
+            Token::MapEnd,
+            Token::SeqEnd,
+            Token::MapEnd,
+            Token::Map { len: Some(1) },
+            Token::Str("nzb"),
+            Token::Str("https://nzb_url/1.nzb"),
+            Token::MapEnd,
+            Token::SeqEnd,
+        ],
+    );
+}
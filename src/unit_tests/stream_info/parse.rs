@@ -0,0 +1,43 @@
+use crate::types::resource::{Stream, StreamInfo, StreamSource};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Fixture {
+    name: Option<String>,
+    description: Option<String>,
+    info: StreamInfo,
+}
+
+#[test]
+fn parse_fixtures() {
+    let fixtures: Vec<Fixture> = serde_json::from_str(include_str!("streams.json")).unwrap();
+    for fixture in fixtures {
+        assert_eq!(
+            StreamInfo::parse(fixture.name.as_deref(), fixture.description.as_deref()),
+            fixture.info,
+            "{:?} {:?}",
+            fixture.name,
+            fixture.description
+        );
+    }
+}
+
+#[test]
+fn stream_info() {
+    let stream = Stream {
+        source: StreamSource::Url {
+            url: "https://example.com/video.mp4".parse().unwrap(),
+        },
+        name: Some("Addon\n1080p".to_owned()),
+        description: Some("Movie.2020.1080p.WEB-DL.x264-GROUP\n💾 1 GB".to_owned()),
+        thumbnail: None,
+        subtitles: vec![],
+        behavior_hints: Default::default(),
+    };
+    assert_eq!(
+        stream.info(),
+        StreamInfo::parse(stream.name.as_deref(), stream.description.as_deref())
+    );
+    assert_eq!(stream.info().size, Some(1024 * 1024 * 1024));
+    assert_eq!(stream.info().release_group, Some("GROUP".to_owned()));
+}
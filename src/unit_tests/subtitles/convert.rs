@@ -0,0 +1,68 @@
+use crate::subtitles::{SubtitlesCue, SubtitlesTrack};
+
+fn track() -> SubtitlesTrack {
+    SubtitlesTrack {
+        cues: vec![
+            SubtitlesCue {
+                start: 1000,
+                end: 2000,
+                text: "<i>First</i> & <b>bold</b>".to_owned(),
+            },
+            SubtitlesCue {
+                start: 3_723_004,
+                end: 3_724_000,
+                text: "Line 1\n\nLine 2 <c>".to_owned(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn to_vtt() {
+    assert_eq!(
+        track().to_vtt(),
+        "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\n<i>First</i> &amp; <b>bold</b>\n\n01:02:03.004 --> 01:02:04.000\nLine 1\nLine 2 &lt;c&gt;\n"
+    );
+}
+
+#[test]
+fn offset() {
+    let mut track = track();
+    track.offset(500);
+    assert_eq!(
+        track
+            .cues
+            .iter()
+            .map(|cue| (cue.start, cue.end))
+            .collect::<Vec<_>>(),
+        vec![(1500, 2500), (3_723_504, 3_724_500)]
+    );
+    track.offset(-2000);
+    assert_eq!(
+        track
+            .cues
+            .iter()
+            .map(|cue| (cue.start, cue.end))
+            .collect::<Vec<_>>(),
+        vec![(0, 500), (3_721_504, 3_722_500)]
+    );
+    track.offset(-1_000_000_000);
+    assert!(track.cues.is_empty());
+}
+
+#[test]
+fn convert_frame_rate() {
+    let mut converted = track();
+    converted.convert_frame_rate(25.0, 23.976);
+    assert_eq!(
+        converted
+            .cues
+            .iter()
+            .map(|cue| (cue.start, cue.end))
+            .collect::<Vec<_>>(),
+        vec![(1043, 2085), (3_882_011, 3_883_050)]
+    );
+    let mut unchanged = track();
+    unchanged.convert_frame_rate(0.0, 25.0);
+    assert_eq!(unchanged, track());
+}
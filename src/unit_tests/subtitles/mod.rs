@@ -0,0 +1,3 @@
+mod convert;
+mod parse;
+mod video_hash;
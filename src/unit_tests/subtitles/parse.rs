@@ -0,0 +1,138 @@
+use crate::subtitles::{
+    decode_subtitles, SubtitlesCue, SubtitlesError, SubtitlesFormat, SubtitlesTrack,
+};
+
+fn cue(start: u64, end: u64, text: &str) -> SubtitlesCue {
+    SubtitlesCue {
+        start,
+        end,
+        text: text.to_owned(),
+    }
+}
+
+#[test]
+fn srt() {
+    let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello <font color=\"#ff0000\">world</font>\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000 X1:10 X2:20 Y1:30 Y2:40\r\n<i>First line</i>\r\nSecond line\r\n\r\n3\r\nbroken --> timing\r\nSkipped\r\n";
+    assert_eq!(SubtitlesFormat::detect(srt), Some(SubtitlesFormat::Srt));
+    assert_eq!(
+        SubtitlesTrack::parse(srt.as_bytes()),
+        Ok(SubtitlesTrack {
+            cues: vec![
+                cue(1000, 2500, "Hello world"),
+                cue(3000, 4000, "<i>First line</i>\nSecond line"),
+            ],
+        })
+    );
+}
+
+#[test]
+fn srt_whitespace_separators() {
+    let srt = "1\n00:00:01,000 --> 00:00:02,000\nFirst\n \t\n2\n00:00:03,000 --> 00:00:04,000\nSecond\n   \n\n3\n00:00:05,000 --> 00:00:06,000\nThird";
+    assert_eq!(
+        SubtitlesTrack::parse_str(srt, SubtitlesFormat::Srt),
+        Ok(SubtitlesTrack {
+            cues: vec![
+                cue(1000, 2000, "First"),
+                cue(3000, 4000, "Second"),
+                cue(5000, 6000, "Third"),
+            ],
+        })
+    );
+}
+
+#[test]
+fn srt_oversized_timestamps() {
+    let srt = "1\n18446744073709551:00:00,000 --> 18446744073709551:00:01,000\nOverflow\n\n2\n00:00:00,000 --> 5124095576030431:00:00,000\nOverflow\n\n3\n00:00:01,000 --> 00:00:02,000\nValid\n";
+    assert_eq!(
+        SubtitlesTrack::parse_str(srt, SubtitlesFormat::Srt),
+        Ok(SubtitlesTrack {
+            cues: vec![cue(1000, 2000, "Valid")],
+        })
+    );
+}
+
+#[test]
+fn vtt() {
+    let vtt = "\u{feff}WEBVTT - title\n\nNOTE a comment\n\nSTYLE\n::cue { color: red }\n\nintro\n00:01.000 --> 00:02.000 align:start position:10%\nTom &amp; Jerry\n\n01:00:00.500 --> 01:00:01.250\n&lt;music&gt;\n";
+    assert_eq!(SubtitlesFormat::detect(vtt), Some(SubtitlesFormat::WebVtt));
+    assert_eq!(
+        SubtitlesTrack::parse(vtt.as_bytes()),
+        Ok(SubtitlesTrack {
+            cues: vec![
+                cue(1000, 2000, "Tom & Jerry"),
+                cue(3_600_500, 3_601_250, "<music>"),
+            ],
+        })
+    );
+}
+
+#[test]
+fn vtt_whitespace_separators() {
+    let vtt = "WEBVTT\n \n00:01.000 --> 00:02.000\nFirst\n\t\n00:03.000 --> 00:04.000\nSecond\n";
+    assert_eq!(
+        SubtitlesTrack::parse_str(vtt, SubtitlesFormat::WebVtt),
+        Ok(SubtitlesTrack {
+            cues: vec![cue(1000, 2000, "First"), cue(3000, 4000, "Second")],
+        })
+    );
+}
+
+#[test]
+fn ass() {
+    let ass = "[Script Info]\nTitle: Test\nScriptType: v4.00+\n\n[V4+ Styles]\nFormat: Name, Fontname\nStyle: Default,Arial\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nComment: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,Ignored\nDialogue: 0,0:00:05.50,0:00:07.00,Default,,0,0,0,,Second, with a comma\nDialogue: 0,0:00:01.00,0:00:02.25,Default,,0,0,0,,{\\an8\\i1}Top{\\i0}\\Nline{\\pos(10,20)}\n";
+    assert_eq!(SubtitlesFormat::detect(ass), Some(SubtitlesFormat::Ass));
+    assert_eq!(
+        SubtitlesTrack::parse(ass.as_bytes()),
+        Ok(SubtitlesTrack {
+            cues: vec![
+                cue(1000, 2250, "<i>Top</i>\nline"),
+                cue(5500, 7000, "Second, with a comma"),
+            ],
+        })
+    );
+}
+
+#[test]
+fn ass_custom_format() {
+    let ass = "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01.00,0:00:02.00,Text\n";
+    assert_eq!(
+        SubtitlesTrack::parse_str(ass, SubtitlesFormat::Ass),
+        Ok(SubtitlesTrack {
+            cues: vec![cue(1000, 2000, "Text")],
+        })
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        SubtitlesTrack::parse(b"plain text"),
+        Err(SubtitlesError::UnknownFormat)
+    );
+    assert_eq!(
+        SubtitlesTrack::parse(b"WEBVTT\n\nNOTE nothing here\n"),
+        Err(SubtitlesError::NoCues)
+    );
+}
+
+#[test]
+fn encoding() {
+    // "Здравей" in windows-1251
+    let windows_1251 =
+        b"1\n00:00:01,000 --> 00:00:02,000\n\xc7\xe4\xf0\xe0\xe2\xe5\xe9, \xf1\xe2\xff\xf2\n";
+    assert_eq!(
+        SubtitlesTrack::parse(windows_1251).unwrap().cues[0].text,
+        "Здравей, свят"
+    );
+    // UTF-16LE with a byte order mark
+    let utf16 = "WEBVTT\n\n00:01.000 --> 00:02.000\nÀ bientôt\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    let utf16 = [vec![0xff, 0xfe], utf16].concat();
+    assert_eq!(
+        SubtitlesTrack::parse(&utf16).unwrap().cues[0].text,
+        "À bientôt"
+    );
+    assert_eq!(decode_subtitles("Привет".as_bytes()), "Привет");
+}
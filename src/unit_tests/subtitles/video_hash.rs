@@ -0,0 +1,149 @@
+use crate::models::ctx::Ctx;
+use crate::models::player::{Player, Selected};
+use crate::runtime::msg::{Action, ActionLoad};
+use crate::runtime::{Effects, EnvFutureExt, Runtime, RuntimeAction, TryEnvFuture};
+use crate::subtitles::{local_video_hash, remote_video_hash, video_hash};
+use crate::types::addon::{
+    Descriptor, Manifest, ManifestResource, ResourcePath, ResourceResponse,
+    ResourceResponseWithMetadata,
+};
+use crate::types::profile::Profile;
+use crate::types::resource::{Stream, StreamSource};
+use crate::unit_tests::{Request, TestEnv, FETCH_HANDLER, FILES, REQUESTS};
+use futures::{future, FutureExt};
+use std::any::Any;
+use stremio_derive::Model;
+use url::Url;
+
+fn video_bytes(len: usize) -> Vec<u8> {
+    (0..len).map(|i| ((i * 7 + i / 256) % 251) as u8).collect()
+}
+
+#[test]
+fn video_hash_of_chunks() {
+    let bytes = video_bytes(200000);
+    assert_eq!(
+        video_hash(
+            bytes.len() as u64,
+            &bytes[..65536],
+            &bytes[bytes.len() - 65536..]
+        ),
+        "6a7a9bb7b5cddae3"
+    );
+    let bytes = video_bytes(1001);
+    assert_eq!(
+        video_hash(bytes.len() as u64, &bytes, &bytes),
+        "717c879495a0b0ed",
+        "The last word is zero padded"
+    );
+}
+
+#[test]
+fn local_video_hash_reads_head_and_tail() {
+    let _env_mutex = TestEnv::reset();
+    FILES
+        .write()
+        .unwrap()
+        .insert("/videos/movie.mkv".to_owned(), video_bytes(200000));
+    let hash = local_video_hash::<TestEnv>("/videos/movie.mkv")
+        .now_or_never()
+        .expect("hash future is ready")
+        .expect("hash computed");
+    assert_eq!(hash, "6a7a9bb7b5cddae3");
+}
+
+#[test]
+fn remote_video_hash_with_range_requests() {
+    let _env_mutex = TestEnv::reset();
+    FILES
+        .write()
+        .unwrap()
+        .insert("https://cdn.com/movie.mkv".to_owned(), video_bytes(200000));
+    let url = Url::parse("https://cdn.com/movie.mkv").unwrap();
+    let hash = remote_video_hash::<TestEnv>(&url)
+        .now_or_never()
+        .expect("hash future is ready")
+        .expect("hash computed");
+    assert_eq!(hash, "6a7a9bb7b5cddae3");
+    let ranges = REQUESTS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|request| request.headers.get("range").cloned().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ranges, vec!["bytes=0-65535", "bytes=134464-199999"]);
+}
+
+#[test]
+fn player_requests_subtitles_with_local_video_hash() {
+    #[derive(Model, Default)]
+    #[model(TestEnv)]
+    struct TestModel {
+        ctx: Ctx,
+        player: Player,
+    }
+    fn fetch_handler(_request: Request) -> TryEnvFuture<Box<dyn Any + Send>> {
+        future::ok(Box::new(ResourceResponseWithMetadata::from(
+            ResourceResponse::Subtitles { subtitles: vec![] },
+        )) as Box<dyn Any + Send>)
+        .boxed_env()
+    }
+    let _env_mutex = TestEnv::reset();
+    *FETCH_HANDLER.write().unwrap() = Box::new(fetch_handler);
+    FILES
+        .write()
+        .unwrap()
+        .insert("/videos/my movie.mkv".to_owned(), video_bytes(200000));
+    let (runtime, _rx) = Runtime::<TestEnv, _>::new(
+        TestModel {
+            ctx: Ctx {
+                profile: Profile {
+                    addons: vec![Descriptor {
+                        manifest: Manifest {
+                            id: "subs".to_owned(),
+                            types: vec!["movie".to_owned()],
+                            resources: vec![ManifestResource::Short("subtitles".to_owned())],
+                            ..Default::default()
+                        },
+                        transport_url: Url::parse("https://subs.com/manifest.json").unwrap(),
+                        flags: Default::default(),
+                    }],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Effects::none().unchanged(),
+        1000,
+    );
+    TestEnv::run(|| {
+        runtime.dispatch(RuntimeAction {
+            field: None,
+            action: Action::Load(ActionLoad::Player(Selected {
+                stream: Stream {
+                    source: StreamSource::Url {
+                        url: Url::parse("file:///videos/my%20movie.mkv").unwrap(),
+                    },
+                    name: None,
+                    description: None,
+                    thumbnail: None,
+                    subtitles: vec![],
+                    behavior_hints: Default::default(),
+                },
+                stream_request: None,
+                meta_request: None,
+                subtitles_path: Some(ResourcePath::without_extra("subtitles", "movie", "tt1")),
+            })),
+        })
+    });
+    assert_eq!(
+        runtime.model().unwrap().player.video_hash,
+        Some("6a7a9bb7b5cddae3".to_owned())
+    );
+    assert_eq!(
+        REQUESTS.read().unwrap().last().unwrap().url,
+        "https://subs.com/subtitles/movie/tt1/videoHash=6a7a9bb7b5cddae3.json",
+        "Subtitles requested again with the computed hash"
+    );
+}
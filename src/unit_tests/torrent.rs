@@ -0,0 +1,167 @@
+use crate::types::resource::{
+    SeriesInfo, Stream, StreamSource, Torrent, TorrentError, TorrentFile,
+};
+
+const SERIES_PACK: &[u8] = b"d8:announce14:udp://a.com:8013:announce-listll14:udp://a.com:80el14:udp://b.com:80ee4:infod5:filesld6:lengthi100e4:pathl6:Sample22:Show.S01E01.sample.mkveed6:lengthi900e4:pathl21:Show.S01E01.1080p.mkveed6:lengthi700e4:pathl20:Show.S01E02.720p.mkveed6:lengthi800e4:pathl13:Show 1x03.mp4eed6:lengthi5000e4:pathl10:Extras.isoeee4:name4:Show12:piece lengthi16384e6:pieces0:ee";
+const SINGLE_FILE: &[u8] =
+    b"d4:infod6:lengthi1234e4:name9:Movie.mp412:piece lengthi16384e6:pieces0:ee";
+
+fn info_hash(hex: &str) -> [u8; 20] {
+    let mut info_hash = [0; 20];
+    hex::decode_to_slice(hex, &mut info_hash).unwrap();
+    info_hash
+}
+
+#[test]
+fn magnet() {
+    let stream = Stream::from_magnet("magnet:?xt=urn:btih:C9E15763F722F23E98A29DECDFAE341B98D53056&dn=Cosmos%20Laundromat&tr=udp%3A%2F%2Fexplodie.org%3A6969&tr=udp%3A%2F%2Ftracker.opentrackr.org%3A1337&tr=udp%3A%2F%2Fexplodie.org%3A6969").unwrap();
+    assert_eq!(
+        stream.source,
+        StreamSource::Torrent {
+            info_hash: info_hash("c9e15763f722f23e98a29decdfae341b98d53056"),
+            file_idx: None,
+            announce: vec![
+                "tracker:udp://explodie.org:6969".to_owned(),
+                "tracker:udp://tracker.opentrackr.org:1337".to_owned(),
+            ],
+        }
+    );
+    assert_eq!(stream.name, Some("Cosmos Laundromat".to_owned()));
+    assert_eq!(
+        Stream::from_magnet(&stream.magnet_url().unwrap().to_string()),
+        Ok(stream),
+        "Round trip through magnet_url"
+    );
+}
+
+#[test]
+fn magnet_base32() {
+    let torrent =
+        Torrent::from_magnet("magnet:?xt=urn:btih:ZHQVOY7XELZD5GFCTXWN7LRUDOMNKMCW").unwrap();
+    assert_eq!(
+        torrent.info_hash,
+        info_hash("c9e15763f722f23e98a29decdfae341b98d53056")
+    );
+    assert_eq!(torrent.name, None);
+    assert!(torrent.trackers.is_empty());
+}
+
+#[test]
+fn magnet_invalid() {
+    assert_eq!(
+        Torrent::from_magnet(
+            "https://example.com/?xt=urn:btih:C9E15763F722F23E98A29DECDFAE341B98D53056"
+        ),
+        Err(TorrentError::NotAMagnet)
+    );
+    assert_eq!(
+        Torrent::from_magnet("magnet:?xt=urn:btih:C9E15763"),
+        Err(TorrentError::InvalidInfoHash)
+    );
+    assert_eq!(
+        Torrent::from_magnet("magnet:?dn=Missing+hash"),
+        Err(TorrentError::InvalidInfoHash)
+    );
+}
+
+#[test]
+fn torrent_file() {
+    let torrent = Torrent::from_bytes(SERIES_PACK).unwrap();
+    assert_eq!(
+        torrent.info_hash,
+        info_hash("3a1833d28dd1582dc9dea32cc433eafaf30247ad")
+    );
+    assert_eq!(torrent.name, Some("Show".to_owned()));
+    assert_eq!(
+        torrent.trackers,
+        vec!["udp://a.com:80".to_owned(), "udp://b.com:80".to_owned()]
+    );
+    assert_eq!(
+        torrent.files[0],
+        TorrentFile {
+            path: "Sample/Show.S01E01.sample.mkv".to_owned(),
+            length: 100,
+        }
+    );
+    assert_eq!(torrent.files.len(), 5);
+}
+
+#[test]
+fn torrent_file_idx() {
+    let torrent = Torrent::from_bytes(SERIES_PACK).unwrap();
+    assert_eq!(torrent.file_idx(None), Some(1), "Largest video file");
+    assert_eq!(
+        torrent.file_idx(Some(&SeriesInfo {
+            season: 1,
+            episode: 2
+        })),
+        Some(2)
+    );
+    assert_eq!(
+        torrent.file_idx(Some(&SeriesInfo {
+            season: 1,
+            episode: 3
+        })),
+        Some(3)
+    );
+    assert_eq!(
+        torrent.file_idx(Some(&SeriesInfo {
+            season: 2,
+            episode: 1
+        })),
+        Some(1),
+        "Largest video file when no episode matches"
+    );
+    let stream = Stream::from_torrent(
+        SERIES_PACK,
+        Some(&SeriesInfo {
+            season: 1,
+            episode: 2,
+        }),
+    )
+    .unwrap();
+    assert!(matches!(
+        stream.source,
+        StreamSource::Torrent {
+            file_idx: Some(2),
+            ref announce,
+            ..
+        } if announce[0] == "tracker:udp://a.com:80"
+    ));
+    assert_eq!(stream.description, Some("Show.S01E02.720p.mkv".to_owned()));
+}
+
+#[test]
+fn torrent_single_file() {
+    let stream = Stream::from_torrent(SINGLE_FILE, None).unwrap();
+    assert_eq!(
+        stream.source,
+        StreamSource::Torrent {
+            info_hash: info_hash("fc6dee771dc73bebc04721f87e2caae3d0970eaf"),
+            file_idx: Some(0),
+            announce: vec![],
+        }
+    );
+    assert_eq!(stream.name, Some("Movie.mp4".to_owned()));
+}
+
+#[test]
+fn torrent_invalid() {
+    assert_eq!(
+        Torrent::from_bytes(b"d4:infod6:lengthi1234e4:name9:Movie"),
+        Err(TorrentError::InvalidBencode(29))
+    );
+    assert_eq!(
+        Torrent::from_bytes(b"d8:announce3:urle"),
+        Err(TorrentError::MissingInfo)
+    );
+    assert_eq!(
+        Torrent::from_bytes(b"i1ei2e"),
+        Err(TorrentError::InvalidBencode(3))
+    );
+    let nested = [b"l".repeat(100_000), b"e".repeat(100_000)].concat();
+    assert_eq!(
+        Torrent::from_bytes(&nested),
+        Err(TorrentError::BencodeTooDeep(32))
+    );
+}
@@ -0,0 +1,86 @@
+use crate::constants::{CATALOG_PAGE_SIZE, HISTORY_MAX_ENTRIES};
+use crate::models::ctx::Ctx;
+use crate::models::library_with_filters::LibraryRequestPage;
+use crate::models::watch_history::{HistoryRequest, Selected, WatchHistory};
+use crate::runtime::msg::{Action, ActionLoad, Msg};
+use crate::runtime::UpdateWithCtx;
+use crate::types::history::{HistoryBucket, HistoryEntry};
+use crate::unit_tests::TestEnv;
+use chrono::prelude::TimeZone;
+use chrono::{Duration, Utc};
+use std::num::NonZeroUsize;
+
+fn history_entry(index: usize) -> HistoryEntry {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap() + Duration::hours(index as i64);
+    HistoryEntry {
+        id: format!("tt{}", index),
+        r#type: "movie".to_owned(),
+        name: format!("Movie {}", index),
+        video_id: format!("tt{}", index),
+        start,
+        end: start + Duration::minutes(90),
+        duration: 5400000,
+    }
+}
+
+fn load(watch_history: &mut WatchHistory, ctx: &Ctx, page: usize) {
+    UpdateWithCtx::<TestEnv>::update(
+        watch_history,
+        &Msg::Action(Action::Load(ActionLoad::WatchHistory(Selected {
+            request: HistoryRequest {
+                page: LibraryRequestPage(NonZeroUsize::new(page).unwrap()),
+            },
+        }))),
+        ctx,
+    );
+}
+
+#[test]
+fn history_bucket_retention() {
+    let history = HistoryBucket::new(
+        None,
+        (0..HISTORY_MAX_ENTRIES + 10)
+            .rev()
+            .map(history_entry)
+            .collect(),
+    );
+    assert_eq!(history.entries.len(), HISTORY_MAX_ENTRIES);
+    assert_eq!(
+        history.entries.first(),
+        Some(&history_entry(10)),
+        "oldest sessions are dropped"
+    );
+    assert_eq!(
+        history.entries.last(),
+        Some(&history_entry(HISTORY_MAX_ENTRIES + 9)),
+        "sessions are sorted by start"
+    );
+}
+
+#[test]
+fn load_watch_history_pages() {
+    let ctx = Ctx {
+        history: HistoryBucket::new(
+            None,
+            (0..CATALOG_PAGE_SIZE + 5).map(history_entry).collect(),
+        ),
+        ..Default::default()
+    };
+    let mut watch_history = WatchHistory::default();
+    load(&mut watch_history, &ctx, 1);
+    assert_eq!(watch_history.catalog.len(), CATALOG_PAGE_SIZE);
+    assert_eq!(
+        watch_history.catalog.first(),
+        Some(&history_entry(CATALOG_PAGE_SIZE + 4)),
+        "most recent session first"
+    );
+    assert!(watch_history.selectable.prev_page.is_none());
+    assert!(watch_history.selectable.next_page.is_some());
+    load(&mut watch_history, &ctx, 2);
+    assert_eq!(
+        watch_history.catalog,
+        (0..5).rev().map(history_entry).collect::<Vec<_>>()
+    );
+    assert!(watch_history.selectable.prev_page.is_some());
+    assert!(watch_history.selectable.next_page.is_none());
+}
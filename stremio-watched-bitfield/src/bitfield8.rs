@@ -14,10 +14,10 @@ pub struct BitField8 {
 
 impl BitField8 {
     pub fn new(length: usize) -> BitField8 {
-        let length = (length as f64 / 8.0).ceil() as usize;
+        let bytes = (length as f64 / 8.0).ceil() as usize;
         BitField8 {
             length,
-            values: vec![0; length],
+            values: vec![0; bytes],
         }
     }
 
@@ -49,12 +49,7 @@ impl BitField8 {
     }
 
     pub fn last_index_of(&self, val: bool) -> Option<usize> {
-        for i in (0..self.length - 1).rev() {
-            if self.get(i) == val {
-                return Some(i);
-            }
-        }
-        None
+        (0..self.length).rev().find(|i| self.get(*i) == val)
     }
 }
 
@@ -98,4 +93,21 @@ mod tests {
         let bf = BitField8::try_from((watched, None)).unwrap();
         assert_eq!(bf.length, 16);
     }
+
+    #[test]
+    fn last_index_of() {
+        let mut bf = BitField8::new(9);
+        assert_eq!(bf.length, 9);
+        assert_eq!(bf.last_index_of(true), None);
+        assert_eq!(bf.last_index_of(false), Some(8));
+
+        // The last bit is included
+        bf.set(8, true);
+        assert_eq!(bf.last_index_of(true), Some(8));
+        assert_eq!(bf.last_index_of(false), Some(7));
+
+        bf.set(8, false);
+        bf.set(0, true);
+        assert_eq!(bf.last_index_of(true), Some(0));
+    }
 }
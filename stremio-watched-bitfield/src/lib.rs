@@ -71,4 +71,80 @@ mod tests {
             assert_eq!(wb2.get_video(val), i % 2 == 0);
         }
     }
+    #[test]
+    fn union_serialized() {
+        let video_ids = (1..=20)
+            .map(|i| format!("tt2934286:1:{}", i))
+            .collect::<Vec<_>>();
+        let mut a = WatchedBitField::construct_from_array(vec![], video_ids.clone());
+        a.set(0, true);
+        a.set(4, true);
+        let mut b = WatchedBitField::construct_from_array(vec![], video_ids.clone());
+        b.set(2, true);
+        b.set(11, true);
+
+        let union = WatchedBitField::union_serialized(&a.to_string(), &b.to_string()).unwrap();
+        assert!(union.starts_with("tt2934286:1:12:12:"));
+        let union = WatchedBitField::construct_and_resize(&union, video_ids).unwrap();
+        for i in 0..20 {
+            assert_eq!(union.get(i), [0, 2, 4, 11].contains(&i));
+        }
+
+        assert_eq!(
+            WatchedBitField::union_serialized(&a.to_string(), "tt2934286:1:1:1:eJxjBAAAAgAC")
+                .unwrap(),
+            a.to_string(),
+            "The bitfield which contains the other is kept as it is"
+        );
+        assert!(WatchedBitField::union_serialized("invalid", &a.to_string()).is_err());
+
+        let other_season = b
+            .to_string()
+            .replacen("tt2934286:1:12:12:", "tt2934286:2:12:12:", 1);
+        assert_eq!(
+            WatchedBitField::union_serialized(&other_season, &b.to_string()).unwrap(),
+            other_season,
+            "The newer bitfield is kept when the anchors are inconsistent"
+        );
+        let misplaced_anchor = a
+            .to_string()
+            .replacen("tt2934286:1:5:5:", "tt2934286:1:7:7:", 1);
+        assert_eq!(
+            WatchedBitField::union_serialized(&b.to_string(), &misplaced_anchor).unwrap(),
+            b.to_string(),
+            "The newer bitfield is kept when an anchor is not the last watched video"
+        );
+    }
+    #[test]
+    fn display_last_video_watched() {
+        let video_ids = (1..=9)
+            .map(|i| format!("tt2934286:1:{}", i))
+            .collect::<Vec<_>>();
+        let mut wb = WatchedBitField::construct_from_array(vec![], video_ids.clone());
+        wb.set(8, true);
+        let serialized = wb.to_string();
+        assert!(serialized.starts_with("tt2934286:1:9:9:"));
+        let wb = WatchedBitField::construct_and_resize(&serialized, video_ids).unwrap();
+        assert!(wb.get(8));
+        assert!((0..8).all(|i| !wb.get(i)));
+    }
+    #[test]
+    fn count_serialized() {
+        let video_ids = (1..=20)
+            .map(|i| format!("tt2934286:1:{}", i))
+            .collect::<Vec<_>>();
+        let mut wb = WatchedBitField::construct_from_array(vec![], video_ids);
+        assert_eq!(
+            WatchedBitField::count_serialized(&wb.to_string()).unwrap(),
+            0
+        );
+        wb.set(0, true);
+        wb.set(4, true);
+        wb.set(19, true);
+        assert_eq!(
+            WatchedBitField::count_serialized(&wb.to_string()).unwrap(),
+            3
+        );
+        assert!(WatchedBitField::count_serialized("invalid").is_err());
+    }
 }
@@ -101,6 +101,72 @@ impl WatchedBitField {
             self.bitfield.set(pos, v);
         }
     }
+
+    // Unions two serialized bitfields without knowing the video ids.
+    // Both are expected to be serialized against the same list of videos, so the bits are merged by index
+    // and the anchor of the longer one (the last watched video of the union) is kept.
+    // When the anchors show that the lists differ, the newer bitfield is kept as it is.
+    pub fn union_serialized(newer: &str, older: &str) -> Result<String, Error> {
+        let (anchor_newer, length_newer, bitfield_newer) = deserialize(newer)?;
+        let (anchor_older, length_older, bitfield_older) = deserialize(older)?;
+        if (anchor_newer == anchor_older) != (length_newer == length_older)
+            || !is_anchored(length_newer, &bitfield_newer)
+            || !is_anchored(length_older, &bitfield_older)
+        {
+            return Ok(newer.to_owned());
+        }
+        let length = length_newer.max(length_older);
+        // Keep the original serialization when one of the bitfields already contains the other
+        if length == length_newer
+            && (0..length).all(|i| bitfield_newer.get(i) || !bitfield_older.get(i))
+        {
+            return Ok(newer.to_owned());
+        }
+        if length == length_older
+            && (0..length).all(|i| bitfield_older.get(i) || !bitfield_newer.get(i))
+        {
+            return Ok(older.to_owned());
+        }
+        let anchor = if length == length_newer {
+            anchor_newer
+        } else {
+            anchor_older
+        };
+        let mut bitfield = BitField8::new(length);
+        for i in 0..length {
+            bitfield.set(i, bitfield_newer.get(i) || bitfield_older.get(i));
+        }
+        let packed = String::try_from(&bitfield)?;
+        Ok(format!("{}:{}:{}", anchor, length, packed))
+    }
+
+    // Counts the watched videos of a serialized bitfield without knowing the video ids.
+    pub fn count_serialized(serialized: &str) -> Result<usize, Error> {
+        let (_, length, bitfield) = deserialize(serialized)?;
+        Ok((0..length).filter(|i| bitfield.get(*i)).count())
+    }
+}
+
+// The anchor length is the position of the last watched video, as written by `Display`
+fn is_anchored(anchor_length: usize, bitfield: &BitField8) -> bool {
+    bitfield.last_index_of(true).unwrap_or(0) + 1 == anchor_length
+}
+
+fn deserialize(serialized: &str) -> Result<(String, usize, BitField8), Error> {
+    let mut components = serialized.split(':').collect::<Vec<&str>>();
+    if components.len() < 3 {
+        return Err(Error("Not enough components".to_string()));
+    }
+    let serialized_buf = components
+        .pop()
+        .ok_or("Cannot obtain the serialized data")?
+        .to_string();
+    let anchor_length = components
+        .pop()
+        .ok_or("Cannot obtain the length field")?
+        .parse::<usize>()?;
+    let bitfield = BitField8::try_from((serialized_buf, None))?;
+    Ok((components.join(":"), anchor_length, bitfield))
 }
 
 impl fmt::Display for WatchedBitField {